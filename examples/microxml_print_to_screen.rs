@@ -6,8 +6,8 @@
 //! `cargo run ../t3.xml`
 //!
 
-use std::fs;
 use std::env;
+use std::fs;
 use std::io::Read;
 use std::process;
 
@@ -30,7 +30,7 @@ fn main() {
 
 /// load file
 fn load_file(path: &str) -> String {
-    let mut file = fs::File::open(&path).unwrap();
+    let mut file = fs::File::open(path).unwrap();
     let mut text = String::new();
     file.read_to_string(&mut text).unwrap();
     text
@@ -7,8 +7,8 @@
 //! `cargo run ../t3.xml`
 //!
 
-use std::fs;
 use std::env;
+use std::fs;
 use std::io::Read;
 use std::process;
 
@@ -21,6 +21,8 @@ pub enum Node {
     Comment(String),
 }
 
+// the fields are only printed with Debug
+#[allow(dead_code)]
 #[derive(Debug)]
 pub struct Element {
     name: String,
@@ -29,6 +31,7 @@ pub struct Element {
     nodes: Vec<Node>,
 }
 
+#[allow(dead_code)]
 #[derive(Debug)]
 pub struct Attribute {
     name: String,
@@ -54,7 +57,7 @@ fn main() {
 
 /// load file
 fn load_file(path: &str) -> String {
-    let mut file = fs::File::open(&path).unwrap();
+    let mut file = fs::File::open(path).unwrap();
     let mut text = String::new();
     file.read_to_string(&mut text).unwrap();
     text
@@ -110,10 +113,7 @@ fn get_root_element(input: &str) -> Result<Element, String> {
 
 /// recursive function to fill the tree with nodes
 /// returns Result only because of errors
-fn fill_element(
-    reader_iterator: &mut ReaderForMicroXml,
-    element: &mut Element,
-) -> Result<(), String> {
+fn fill_element(reader_iterator: &mut ReaderForMicroXml, element: &mut Element) -> Result<(), String> {
     loop {
         if let Some(result_token) = reader_iterator.next() {
             match result_token {
@@ -0,0 +1,342 @@
+//! reader_for_microxml_derive - `#[derive(FromMicroXml)]` for the module `extract` of `reader_for_microxml`
+//!
+//! Use it with the feature `derive` of `reader_for_microxml`, the documentation is in the module `extract`.
+//! The macro has no dependencies: the struct is read from the tokens and the impl is written as text.
+//! The generated code uses only `core` and `reader_for_microxml::extract`, so it works also with `no_std`.
+
+use proc_macro::{Delimiter, TokenStream, TokenTree};
+use std::fmt::Write;
+
+const ERR_NOT_STRUCT: &str = "Error: FromMicroXml can be derived only for a struct with named fields.";
+const ERR_GENERICS: &str = "Error: FromMicroXml supports only one lifetime parameter.";
+const ERR_MX_ATTRIBUTE: &str = "Error: The mx attribute is one of attr = \"name\", child = \"name\", children = \"name\" or text.";
+const ERR_NAME_LITERAL: &str = "Error: The name in the mx attribute must be a simple string literal.";
+const ERR_CHILDREN_VEC: &str = "Error: The field with mx(children) must be a Vec.";
+const ERR_NOT_VEC: &str = "Error: The field with mx(attr) or mx(child) cannot be a Vec, use mx(children).";
+const ERR_TEXT_VEC: &str = "Error: The field with mx(text) cannot be a Vec, it is one value or Option.";
+const ERR_MORE_TEXT: &str = "Error: Only one field can have mx(text).";
+
+#[proc_macro_derive(FromMicroXml, attributes(mx))]
+pub fn derive_from_micro_xml(input: TokenStream) -> TokenStream {
+    let code = match parse_struct(input) {
+        Ok(item) => generate(&item),
+        Err(err_msg) => format!("compile_error!({:?});", err_msg),
+    };
+    code.parse().expect("the generated code is valid tokens")
+}
+
+/// where the value of the field comes from
+enum Source {
+    Attribute(String),
+    Child(String),
+    Children(String),
+    Text,
+    Default,
+}
+
+struct Field {
+    ident: String,
+    source: Source,
+    /// the type is `Option<..>`
+    is_option: bool,
+    /// the type is `Vec<..>`
+    is_vec: bool,
+}
+
+struct Struct {
+    name: String,
+    lifetime: Option<String>,
+    fields: Vec<Field>,
+}
+
+fn parse_struct(input: TokenStream) -> Result<Struct, &'static str> {
+    let mut tokens = input.into_iter();
+    // the outer attributes and the visibility are before `struct`
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "struct" => break,
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "enum" || ident.to_string() == "union" => return Err(ERR_NOT_STRUCT),
+            Some(_) => {}
+            None => return Err(ERR_NOT_STRUCT),
+        }
+    }
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err(ERR_NOT_STRUCT),
+    };
+    let mut lifetime = None;
+    let mut next = tokens.next();
+    if is_punct(&next, '<') {
+        let mut generics = String::new();
+        let mut depth = 1;
+        loop {
+            next = tokens.next();
+            match &next {
+                Some(TokenTree::Punct(punct)) if punct.as_char() == '<' => depth += 1,
+                Some(TokenTree::Punct(punct)) if punct.as_char() == '>' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                Some(_) => {}
+                None => return Err(ERR_GENERICS),
+            }
+            if let Some(token) = &next {
+                generics.push_str(&token.to_string());
+            }
+        }
+        // only `'a`
+        let is_lifetime = generics.starts_with('\'') && generics[1..].chars().all(|ch| ch.is_alphanumeric() || ch == '_');
+        if !is_lifetime || generics.len() < 2 {
+            return Err(ERR_GENERICS);
+        }
+        lifetime = Some(generics);
+        next = tokens.next();
+    }
+    let body = match next {
+        Some(TokenTree::Group(group)) if group.delimiter() == Delimiter::Brace => group.stream(),
+        Some(TokenTree::Ident(ident)) if ident.to_string() == "where" => return Err(ERR_GENERICS),
+        _ => return Err(ERR_NOT_STRUCT),
+    };
+    let mut fields = Vec::new();
+    for field_tokens in split_fields(body) {
+        if let Some(field) = parse_field(field_tokens)? {
+            fields.push(field);
+        }
+    }
+    if fields.iter().filter(|field| matches!(field.source, Source::Text)).count() > 1 {
+        return Err(ERR_MORE_TEXT);
+    }
+    Ok(Struct { name, lifetime, fields })
+}
+
+fn is_punct(token: &Option<TokenTree>, ch: char) -> bool {
+    matches!(token, Some(TokenTree::Punct(punct)) if punct.as_char() == ch)
+}
+
+/// The fields are separated by commas outside of `< >`.
+fn split_fields(body: TokenStream) -> Vec<Vec<TokenTree>> {
+    let mut fields = vec![Vec::new()];
+    let mut depth = 0;
+    for token in body {
+        if let TokenTree::Punct(punct) = &token {
+            match punct.as_char() {
+                '<' => depth += 1,
+                '>' if depth > 0 => depth -= 1,
+                ',' if depth == 0 => {
+                    fields.push(Vec::new());
+                    continue;
+                }
+                _ => {}
+            }
+        }
+        if let Some(field) = fields.last_mut() {
+            field.push(token);
+        }
+    }
+    fields.retain(|field| !field.is_empty());
+    fields
+}
+
+fn parse_field(tokens: Vec<TokenTree>) -> Result<Option<Field>, &'static str> {
+    let mut source = Source::Default;
+    let mut tokens = tokens.into_iter().peekable();
+    // the attributes `#[..]`, the visibility `pub` or `pub(crate)`
+    let ident = loop {
+        match tokens.next() {
+            Some(TokenTree::Punct(punct)) if punct.as_char() == '#' => {
+                if let Some(TokenTree::Group(group)) = tokens.next() {
+                    if let Some(mx_source) = parse_mx_attribute(group.stream())? {
+                        source = mx_source;
+                    }
+                }
+            }
+            Some(TokenTree::Ident(ident)) if ident.to_string() == "pub" => {
+                if let Some(TokenTree::Group(group)) = tokens.peek() {
+                    if group.delimiter() == Delimiter::Parenthesis {
+                        tokens.next();
+                    }
+                }
+            }
+            Some(TokenTree::Ident(ident)) => break ident.to_string(),
+            Some(_) => return Err(ERR_NOT_STRUCT),
+            None => return Ok(None),
+        }
+    };
+    if !is_punct(&tokens.next(), ':') {
+        return Err(ERR_NOT_STRUCT);
+    }
+    // the outer type is the name before the first `<`
+    let mut outer_type = String::new();
+    for token in tokens {
+        match token {
+            TokenTree::Ident(ident) => outer_type = ident.to_string(),
+            TokenTree::Punct(punct) if punct.as_char() == '<' => break,
+            _ => {}
+        }
+    }
+    let field = Field {
+        ident,
+        source,
+        is_option: outer_type == "Option",
+        is_vec: outer_type == "Vec",
+    };
+    match field.source {
+        Source::Children(_) if !field.is_vec => Err(ERR_CHILDREN_VEC),
+        Source::Attribute(_) | Source::Child(_) if field.is_vec => Err(ERR_NOT_VEC),
+        Source::Text if field.is_vec => Err(ERR_TEXT_VEC),
+        _ => Ok(Some(field)),
+    }
+}
+
+/// The content of `#[..]`, None if it is not `mx`.
+fn parse_mx_attribute(stream: TokenStream) -> Result<Option<Source>, &'static str> {
+    let mut tokens = stream.into_iter();
+    match tokens.next() {
+        Some(TokenTree::Ident(ident)) if ident.to_string() == "mx" => {}
+        _ => return Ok(None),
+    }
+    let arguments: Vec<TokenTree> = match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Group(group)), None) if group.delimiter() == Delimiter::Parenthesis => group.stream().into_iter().collect(),
+        _ => return Err(ERR_MX_ATTRIBUTE),
+    };
+    let key = match arguments.first() {
+        Some(TokenTree::Ident(ident)) => ident.to_string(),
+        _ => return Err(ERR_MX_ATTRIBUTE),
+    };
+    if key == "text" && arguments.len() == 1 {
+        return Ok(Some(Source::Text));
+    }
+    let name = match (arguments.len(), arguments.get(1), arguments.get(2)) {
+        (3, Some(TokenTree::Punct(punct)), Some(TokenTree::Literal(literal))) if punct.as_char() == '=' => {
+            let literal = literal.to_string();
+            let name = literal.strip_prefix('"').and_then(|name| name.strip_suffix('"'));
+            match name {
+                Some(name) if !name.is_empty() && !name.contains(['\\', '"']) => name.to_string(),
+                _ => return Err(ERR_NAME_LITERAL),
+            }
+        }
+        _ => return Err(ERR_MX_ATTRIBUTE),
+    };
+    match key.as_str() {
+        "attr" => Ok(Some(Source::Attribute(name))),
+        "child" => Ok(Some(Source::Child(name))),
+        "children" => Ok(Some(Source::Children(name))),
+        _ => Err(ERR_MX_ATTRIBUTE),
+    }
+}
+
+/// the local variable for the field, also for raw identifiers
+fn variable(field: &Field) -> String {
+    format!("field_{}", field.ident.trim_start_matches("r#"))
+}
+
+fn generate(item: &Struct) -> String {
+    let lifetime = item.lifetime.as_deref().unwrap_or("'a");
+    let self_type = match &item.lifetime {
+        Some(lifetime) => format!("{}<{}>", item.name, lifetime),
+        None => item.name.clone(),
+    };
+    let is_name_used = item.fields.iter().any(|field| matches!(field.source, Source::Text));
+    let is_pos_used = item.fields.iter().any(|field| match field.source {
+        Source::Attribute(_) | Source::Child(_) => !field.is_option,
+        Source::Text => true,
+        _ => false,
+    });
+    let mut code = String::new();
+    let _ = write!(
+        code,
+        "impl<{lifetime}> ::reader_for_microxml::extract::FromMicroXml<{lifetime}> for {self_type} {{
+    fn read_element(
+        cursor: &mut ::reader_for_microxml::extract::Cursor<{lifetime}>,
+        {name}: &{lifetime} str,
+        {pos}: usize,
+    ) -> ::core::result::Result<Self, ::reader_for_microxml::extract::Violation> {{
+        use ::reader_for_microxml::extract as mx;
+",
+        lifetime = lifetime,
+        self_type = self_type,
+        name = if is_name_used { "name" } else { "_name" },
+        pos = if is_pos_used { "pos" } else { "_pos" },
+    );
+    for field in item.fields.iter() {
+        let init = match field.source {
+            Source::Attribute(_) | Source::Child(_) | Source::Text => "::core::option::Option::None",
+            Source::Children(_) => "::core::default::Default::default()",
+            Source::Default => continue,
+        };
+        let _ = writeln!(code, "        let mut {} = {};", variable(field), init);
+    }
+    let mut attribute_arms = String::new();
+    for field in item.fields.iter() {
+        if let Source::Attribute(name) = &field.source {
+            let _ = writeln!(
+                attribute_arms,
+                "                {:?} => {} = ::core::option::Option::Some(mx::attribute(value, value_pos, attribute_name)?),",
+                name,
+                variable(field)
+            );
+        }
+    }
+    if attribute_arms.is_empty() {
+        code.push_str("        while cursor.attribute()?.is_some() {}\n");
+    } else {
+        let _ = write!(
+            code,
+            "        while let ::core::option::Option::Some((attribute_name, value, value_pos)) = cursor.attribute()? {{
+            match attribute_name {{
+{}                _ => {{}}
+            }}
+        }}
+",
+            attribute_arms
+        );
+    }
+    code.push_str("        loop {\n            match cursor.child()? {\n");
+    for field in item.fields.iter() {
+        let (name, store) = match &field.source {
+            Source::Child(name) => (
+                name,
+                format!("mx::Cursor::set_once(&mut {}, value, child_pos, {:?})?", variable(field), name),
+            ),
+            Source::Children(name) => (name, format!("mx::push(&mut {}, value)", variable(field))),
+            _ => continue,
+        };
+        let _ = write!(
+            code,
+            "                mx::Child::Element({name:?}, child_pos) => {{
+                    let value = mx::FromMicroXml::read_element(cursor, {name:?}, child_pos)?;
+                    {store};
+                }}
+",
+            name = name,
+            store = store
+        );
+    }
+    code.push_str("                mx::Child::Element(_, _) => cursor.skip_element()?,\n");
+    match item.fields.iter().find(|field| matches!(field.source, Source::Text)) {
+        Some(field) => {
+            let _ = writeln!(
+                code,
+                "                mx::Child::Text(txt, _) => mx::push_text(&mut {}, txt),",
+                variable(field)
+            );
+        }
+        None => code.push_str("                mx::Child::Text(_, _) => {}\n"),
+    }
+    code.push_str("                mx::Child::End => break,\n            }\n        }\n        ::core::result::Result::Ok(Self {\n");
+    for field in item.fields.iter() {
+        let value = match &field.source {
+            Source::Attribute(name) if !field.is_option => format!("mx::required_attribute({}, pos, {:?})?", variable(field), name),
+            Source::Child(name) if !field.is_option => format!("mx::required_child({}, pos, {:?})?", variable(field), name),
+            Source::Attribute(_) | Source::Child(_) | Source::Children(_) => variable(field),
+            Source::Text if field.is_option => format!("mx::optional_text({}, pos, name)?", variable(field)),
+            Source::Text => format!("mx::text({}, pos, name)?", variable(field)),
+            Source::Default => "::core::default::Default::default()".to_string(),
+        };
+        let _ = writeln!(code, "            {}: {},", field.ident, value);
+    }
+    code.push_str("        })\n    }\n}\n");
+    code
+}
@@ -0,0 +1,234 @@
+//! microxml
+//!
+//! command-line tool for MicroXml, behind the feature `cli`
+//! `cargo run --features cli --bin microxml -- check examples/t2.html`
+//! Reads the files or the standard input if there is no file or the file is `-`.
+//! Exit codes: 0 success, 1 problems found, 2 wrong usage or input/output error.
+//!
+
+use std::env;
+use std::fmt::Write;
+use std::fs;
+use std::io::{self, Read};
+use std::process;
+
+use reader_for_microxml::dom::{self, Node};
+use reader_for_microxml::fmt::FormatOptions;
+use reader_for_microxml::selector::Selector;
+use reader_for_microxml::{diagnostics, fmt, json, minify, ReaderForMicroXml, Token};
+
+const USAGE: &str = "Usage: microxml <command> [options] [file...]
+
+Commands:
+  check                 validate, print errors with position and source snippet
+  tokens                print the token stream
+  tree                  print the tree
+  fmt [--check] [--indent N] [--width N]
+                        pretty print, or only check if it is already formatted
+  minify                print compact MicroXml
+  query <selector>      print the elements that match the CSS selector
+  to-json               print the MicroXml data model as JSON
+
+Without file or with the file - it reads the standard input.
+Exit codes: 0 success, 1 problems found, 2 wrong usage or input/output error.";
+
+const EXIT_SUCCESS: i32 = 0;
+const EXIT_PROBLEMS: i32 = 1;
+const EXIT_USAGE: i32 = 2;
+
+/// parsed command line
+struct Command {
+    name: String,
+    /// the selector for query
+    selector: Option<Selector>,
+    format_check: bool,
+    format_options: FormatOptions,
+    files: Vec<String>,
+}
+
+/// starting function
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let command = match parse_args(&args) {
+        Ok(command) => command,
+        Err(err_msg) => {
+            eprintln!("{}\n\n{}", err_msg, USAGE);
+            process::exit(EXIT_USAGE);
+        }
+    };
+    let mut exit_code = EXIT_SUCCESS;
+    for file_name in command.files.iter() {
+        let input = match load_file(file_name) {
+            Ok(input) => input,
+            Err(err) => {
+                eprintln!("{}: {}", file_name, err);
+                process::exit(EXIT_USAGE);
+            }
+        };
+        let mut output = String::new();
+        let file_exit_code = run(&command, file_name, &input, &mut output);
+        print!("{}", output);
+        exit_code = exit_code.max(file_exit_code);
+    }
+    process::exit(exit_code);
+}
+
+fn parse_args(args: &[String]) -> Result<Command, String> {
+    let mut args = args.iter();
+    let name = args.next().ok_or("Missing command.")?.clone();
+    let mut command = Command {
+        name,
+        selector: None,
+        format_check: false,
+        format_options: FormatOptions::default(),
+        files: Vec::new(),
+    };
+    match command.name.as_str() {
+        "check" | "tokens" | "tree" | "fmt" | "minify" | "to-json" => {}
+        "query" => {
+            let expression = args.next().ok_or("Missing selector for query.")?;
+            let selector = Selector::parse(expression).map_err(|err| format!("Selector {}", err))?;
+            command.selector = Some(selector);
+        }
+        _ => return Err(format!("Unknown command {}.", command.name)),
+    }
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--check" if command.name == "fmt" => command.format_check = true,
+            "--indent" if command.name == "fmt" => command.format_options.indent = parse_number(args.next())?,
+            "--width" if command.name == "fmt" => command.format_options.max_width = parse_number(args.next())?,
+            "-" => command.files.push(arg.clone()),
+            _ if arg.starts_with("--") => return Err(format!("Unknown option {}.", arg)),
+            _ => command.files.push(arg.clone()),
+        }
+    }
+    if command.files.is_empty() {
+        command.files.push("-".to_string());
+    }
+    Ok(command)
+}
+
+fn parse_number(arg: Option<&String>) -> Result<usize, String> {
+    arg.and_then(|number| number.parse().ok())
+        .ok_or_else(|| "The option needs a number.".to_string())
+}
+
+/// load file or standard input
+fn load_file(file_name: &str) -> io::Result<String> {
+    let mut text = String::new();
+    if file_name == "-" {
+        io::stdin().read_to_string(&mut text)?;
+    } else {
+        fs::File::open(file_name)?.read_to_string(&mut text)?;
+    }
+    Ok(text)
+}
+
+/// runs the command for one input and returns the exit code
+fn run(command: &Command, file_name: &str, input: &str, output: &mut String) -> i32 {
+    let result = match command.name.as_str() {
+        "check" => return check(file_name, input, output),
+        "tokens" => tokens(input, output),
+        "tree" => dom::parse_fragment(input).map(|nodes| write_tree(&nodes, 0, output)),
+        "fmt" if command.format_check => match fmt::check(input, command.format_options) {
+            Ok(None) => Ok(()),
+            Ok(Some(line)) => {
+                eprintln!("{}:{}: not formatted", file_name, line);
+                return EXIT_PROBLEMS;
+            }
+            Err(err_msg) => Err(err_msg),
+        },
+        "fmt" => fmt::format_to(input, command.format_options, output),
+        "minify" => minify::minify(input, output).map(|()| output.push('\n')),
+        "query" => {
+            return match dom::parse_fragment(input) {
+                Ok(nodes) => {
+                    let elements = command
+                        .selector
+                        .as_ref()
+                        .map(|selector| selector.select(&nodes))
+                        .unwrap_or_default();
+                    for element in elements.iter() {
+                        let _ = writeln!(output, "{}", element);
+                    }
+                    if elements.is_empty() {
+                        EXIT_PROBLEMS
+                    } else {
+                        EXIT_SUCCESS
+                    }
+                }
+                Err(err_msg) => {
+                    eprintln!("{}: {}", file_name, err_msg);
+                    EXIT_PROBLEMS
+                }
+            };
+        }
+        // a single root element is written alone, else the list of the top-level nodes
+        "to-json" => json::write_document(input, output)
+            .or_else(|_| {
+                output.clear();
+                json::write_fragment(input, output)
+            })
+            .map(|()| output.push('\n')),
+        _ => Ok(()),
+    };
+    match result {
+        Ok(()) => EXIT_SUCCESS,
+        Err(err_msg) => {
+            eprintln!("{}: {}", file_name, err_msg);
+            EXIT_PROBLEMS
+        }
+    }
+}
+
+/// all errors and warnings with position and source snippet
+fn check(file_name: &str, input: &str, output: &mut String) -> i32 {
+    let mut reader_iterator = ReaderForMicroXml::new(input).with_recovery(true);
+    for _token in reader_iterator.by_ref() {}
+    for diagnostic in reader_iterator.diagnostics() {
+        let (line, column) = diagnostics::line_column(input, diagnostic.pos);
+        let _ = writeln!(output, "{}:{}:{}: {}", file_name, line, column, diagnostic.message);
+        let source_line = input.lines().nth(line - 1).unwrap_or("");
+        let _ = writeln!(output, "    {}", source_line);
+        let _ = writeln!(output, "    {}^", " ".repeat(column - 1));
+    }
+    if reader_iterator.diagnostics().is_empty() {
+        EXIT_SUCCESS
+    } else {
+        EXIT_PROBLEMS
+    }
+}
+
+/// one token per line
+fn tokens(input: &str, output: &mut String) -> Result<(), &'static str> {
+    for result_token in ReaderForMicroXml::new(input) {
+        let _ = match result_token? {
+            Token::StartElement(name) => writeln!(output, "start {}", name),
+            Token::Attribute(name, value) => writeln!(output, "attribute {}=\"{}\"", name, value),
+            Token::TextNode(txt) => writeln!(output, "text {:?}", txt),
+            Token::Comment(txt) => writeln!(output, "comment {:?}", txt),
+            Token::EndElement(name) => writeln!(output, "end {}", name),
+        };
+    }
+    Ok(())
+}
+
+/// one node per line with indentation
+fn write_tree(nodes: &[Node], depth: usize, output: &mut String) {
+    for node in nodes.iter() {
+        let indent = "  ".repeat(depth);
+        let _ = match node {
+            Node::Element(element) => {
+                let _ = write!(output, "{}{}", indent, element.name);
+                for attribute in element.attributes.iter() {
+                    let _ = write!(output, " {}={:?}", attribute.name, attribute.value);
+                }
+                output.push('\n');
+                write_tree(&element.nodes, depth + 1, output);
+                Ok(())
+            }
+            Node::Text(txt) => writeln!(output, "{}{:?}", indent, txt),
+            Node::Comment(txt) => writeln!(output, "{}<!--{}-->", indent, txt),
+        };
+    }
+}
@@ -0,0 +1,127 @@
+//! canonical.rs - canonical form of MicroXml for hashing and equality
+//!
+//! Semantically identical inputs have byte-exact identical canonical form:
+//! - attributes are sorted by name and written with double quotes
+//! - text and attribute values are decoded and then escaped minimally (`&amp;` `&lt;` `&gt;` `&quot;` `&#xD;`)
+//! - line endings `\r\n` and `\r` are normalized to `\n`
+//! - elements without children are written as `<name></name>`
+//! - comments and whitespace-only text between elements are removed, not closed elements are closed
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::fmt;
+
+use crate::{entities, ReaderForMicroXml, Token};
+
+pub const ERR_WRITE: &str = "Error: Canonical write failed.";
+
+/// Writes the canonical form of the input.
+pub fn canonicalize<W: fmt::Write>(input: &str, w: &mut W) -> Result<(), &'static str> {
+    let mut open_elements: Vec<&str> = Vec::new();
+    // the start tag is written when all the attributes are collected
+    let mut start_tag: Option<&str> = None;
+    let mut attributes: Vec<(&str, &str)> = Vec::new();
+    for result_token in ReaderForMicroXml::new_fragment(input) {
+        let token = result_token?;
+        if let Token::Attribute(name, value) = token {
+            attributes.push((name, value));
+            continue;
+        }
+        if let Some(name) = start_tag.take() {
+            write_start_tag(w, name, &mut attributes).map_err(|_| ERR_WRITE)?;
+            attributes.clear();
+            open_elements.push(name);
+        }
+        match token {
+            Token::StartElement(name) => start_tag = Some(name),
+            Token::TextNode(txt) => write_escaped(w, &entities::decode(&normalize_line_endings(txt)), false).map_err(|_| ERR_WRITE)?,
+            Token::EndElement(_name) => {
+                if let Some(name) = open_elements.pop() {
+                    write!(w, "</{}>", name).map_err(|_| ERR_WRITE)?;
+                }
+            }
+            Token::Comment(_) | Token::Attribute(..) => {}
+        }
+    }
+    if let Some(name) = start_tag.take() {
+        write_start_tag(w, name, &mut attributes).map_err(|_| ERR_WRITE)?;
+        open_elements.push(name);
+    }
+    while let Some(name) = open_elements.pop() {
+        write!(w, "</{}>", name).map_err(|_| ERR_WRITE)?;
+    }
+    Ok(())
+}
+
+/// The canonical form in a new string.
+pub fn to_canonical_string(input: &str) -> Result<String, &'static str> {
+    let mut text = String::new();
+    canonicalize(input, &mut text)?;
+    Ok(text)
+}
+
+/// Are the inputs semantically identical.
+pub fn canonical_eq(input_1: &str, input_2: &str) -> Result<bool, &'static str> {
+    Ok(to_canonical_string(input_1)? == to_canonical_string(input_2)?)
+}
+
+/// Stable 64-bit FNV-1a hash of the canonical form.
+/// It is the same on every platform and in every version with the same canonical form,
+/// so it can be used as a key of a persistent cache.
+pub fn canonical_hash(input: &str) -> Result<u64, &'static str> {
+    let mut hasher = FnvHasher(FNV_OFFSET_BASIS);
+    canonicalize(input, &mut hasher)?;
+    Ok(hasher.0)
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// hashes the canonical form while it is written, without allocation
+struct FnvHasher(u64);
+
+impl fmt::Write for FnvHasher {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        for byte in s.bytes() {
+            self.0 ^= u64::from(byte);
+            self.0 = self.0.wrapping_mul(FNV_PRIME);
+        }
+        Ok(())
+    }
+}
+
+fn write_start_tag<W: fmt::Write>(w: &mut W, name: &str, attributes: &mut [(&str, &str)]) -> fmt::Result {
+    attributes.sort_by_key(|(name, _value)| *name);
+    write!(w, "<{}", name)?;
+    for (name, value) in attributes.iter() {
+        write!(w, " {}=\"", name)?;
+        write_escaped(w, &entities::decode(&normalize_line_endings(value)), true)?;
+        w.write_char('"')?;
+    }
+    w.write_char('>')
+}
+
+fn normalize_line_endings(text: &str) -> Cow<'_, str> {
+    if !text.contains('\r') {
+        return Cow::Borrowed(text);
+    }
+    Cow::Owned(text.replace("\r\n", "\n").replace('\r', "\n"))
+}
+
+/// the decoded `\r` must be escaped, else it would be normalized when parsed again
+fn write_escaped<W: fmt::Write>(w: &mut W, text: &str, is_attribute: bool) -> fmt::Result {
+    let mut start = 0;
+    for (pos, ch) in text.char_indices() {
+        let escaped = match ch {
+            '&' => "&amp;",
+            '<' => "&lt;",
+            '>' => "&gt;",
+            '"' if is_attribute => "&quot;",
+            '\r' => "&#xD;",
+            _ => continue,
+        };
+        w.write_str(&text[start..pos])?;
+        w.write_str(escaped)?;
+        start = pos + 1;
+    }
+    w.write_str(&text[start..])
+}
@@ -0,0 +1,594 @@
+//! codegen.rs - Rust structs and typed parse functions from a schema
+//!
+//! `generate(&schema)` returns Rust source code to write from `build.rs`:
+//! ```ignore
+//! // build.rs
+//! let text = std::fs::read_to_string("library.schema").unwrap();
+//! let schema = reader_for_microxml::schema::Schema::parse(&text).unwrap();
+//! let code = reader_for_microxml::codegen::generate(&schema);
+//! std::fs::write(std::path::Path::new(&std::env::var("OUT_DIR").unwrap()).join("library.rs"), code).unwrap();
+//! // src/lib.rs
+//! mod library {
+//!     include!(concat!(env!("OUT_DIR"), "/library.rs"));
+//! }
+//! ```
+//! Every element with `{ }` content is a struct. The attributes and the child elements are fields:
+//! a value if it is required, `Option` if it is optional and `Vec` if it repeats.
+//! The elements with only text are values: `text` and `token` are `String`, `integer` is `i64`,
+//! `decimal` is `f64` and `boolean` is `bool`. Mixed content has the field `text` with all the text.
+//! A struct field that leads back to its own struct, like `section = { title section? }`, is a `Box`.
+//! The function `parse_<start>(input)` returns the struct or the `Violation` with the position.
+//! It validates the input with the schema before the typed reading, so it returns the first violation of `Schema::validate()`.
+//!
+//! The generated code has its own `use` lines, so it goes into its own module.
+//! It needs `String`, `Vec` and `Box` in scope, with `no_std` import them from `alloc`.
+//! The `Cursor` is the small runtime used by the generated code.
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::str::FromStr;
+
+use crate::schema::{self, Content, Datatype, ElementDef, Particle, Schema, Violation};
+use crate::{entities, ReaderForMicroXml, Token};
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "crate", "do", "dyn", "else", "enum", "extern",
+    "false", "final", "fn", "for", "if", "impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+    "ref", "return", "self", "Self", "static", "struct", "super", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+/// how many times the child element can be in the content, max 2 means many
+struct Occurrence {
+    name: String,
+    min: usize,
+    max: usize,
+}
+
+/// Returns the Rust source code for the schema.
+pub fn generate(schema: &Schema) -> String {
+    let mut code = String::new();
+    code.push_str(
+        "// generated by reader_for_microxml::codegen from the schema, do not edit
+
+#[allow(unused_imports)]
+use reader_for_microxml::codegen::{Child, Cursor};
+#[allow(unused_imports)]
+use reader_for_microxml::entities;
+#[allow(unused_imports)]
+use reader_for_microxml::schema::{self, Datatype, Violation};
+",
+    );
+    for element_def in schema.elements.iter() {
+        if let Content::Elements { model, mixed } = &element_def.content {
+            generate_struct(schema, element_def, model, *mixed, &mut code);
+        }
+    }
+    let parse_elements: Vec<&ElementDef> = match &schema.start {
+        Some(start) => schema.element(start).into_iter().collect(),
+        None => schema.elements.iter().collect(),
+    };
+    if !parse_elements.is_empty() {
+        // the lines of the string literal, the schema names have no newlines
+        code.push_str("\n/// the schema for the validation before the typed reading\nconst SCHEMA: &str = \"\\\n");
+        for line in schema.to_string().lines() {
+            let escaped = format!("{:?}", line);
+            let _ = writeln!(code, "{}", &escaped[1..escaped.len() - 1]);
+        }
+        code.push_str("\";\n");
+    }
+    for element_def in parse_elements {
+        let _ = write!(
+            code,
+            "
+/// Parses the document with the root element `{name}`.
+pub fn parse_{fn_name}(input: &str) -> Result<{value_type}, Violation> {{
+    Cursor::validate(SCHEMA, input)?;
+    let cursor = &mut Cursor::new(input);
+    let pos = cursor.root(\"{name}\")?;
+    let value = {read_value};
+    cursor.finish()?;
+    Ok(value)
+}}
+",
+            name = element_def.name,
+            fn_name = snake_case(&element_def.name),
+            value_type = value_type(element_def),
+            read_value = read_value(element_def, "pos"),
+        );
+    }
+    code
+}
+
+fn generate_struct(schema: &Schema, element_def: &ElementDef, model: &Particle, mixed: bool, code: &mut String) {
+    let struct_name = camel_case(&element_def.name);
+    let occurrences = occurrences(model);
+    // the fields with unique names: attributes, child elements and text
+    let mut field_names: Vec<String> = Vec::new();
+    let mut unique = |name: &str| {
+        let mut field_name = snake_case(name);
+        while field_names.contains(&field_name) {
+            field_name.push('_');
+        }
+        field_names.push(field_name.clone());
+        field_name
+    };
+    let attribute_fields: Vec<String> = element_def.attributes.iter().map(|attribute| unique(&attribute.name)).collect();
+    let child_fields: Vec<String> = occurrences.iter().map(|occurrence| unique(&occurrence.name)).collect();
+    let text_field = if mixed { Some(unique("text")) } else { None };
+
+    // region: struct
+    let mut fields = String::new();
+    for (attribute, field) in element_def.attributes.iter().zip(attribute_fields.iter()) {
+        let rust_type = datatype_type(attribute.datatype);
+        let rust_type = if attribute.optional {
+            format!("Option<{}>", rust_type)
+        } else {
+            rust_type.to_string()
+        };
+        let _ = writeln!(fields, "    /// attribute `{}`\n    pub {}: {},", attribute.name, field, rust_type);
+    }
+    // the recursive struct fields are boxed, else the struct has infinite size
+    let boxed: Vec<bool> = occurrences
+        .iter()
+        .map(|occurrence| occurrence.max != 2 && reaches(schema, &occurrence.name, &element_def.name, &mut Vec::new()))
+        .collect();
+    for ((occurrence, field), is_boxed) in occurrences.iter().zip(child_fields.iter()).zip(boxed.iter()) {
+        let child_type = schema.element(&occurrence.name).map(value_type).unwrap_or_default();
+        let child_type = if *is_boxed { format!("Box<{}>", child_type) } else { child_type };
+        let rust_type = match (occurrence.min, occurrence.max) {
+            (_, 2) => format!("Vec<{}>", child_type),
+            (0, _) => format!("Option<{}>", child_type),
+            _ => child_type,
+        };
+        let _ = writeln!(fields, "    /// element `{}`\n    pub {}: {},", occurrence.name, field, rust_type);
+    }
+    if let Some(field) = &text_field {
+        let _ = writeln!(fields, "    /// mixed text\n    pub {}: String,", field);
+    }
+    let _ = writeln!(code, "\n/// element `{}`", element_def.name);
+    code.push_str("#[derive(Clone, Debug, Default, PartialEq)]\n");
+    if fields.is_empty() {
+        let _ = writeln!(code, "pub struct {} {{}}", struct_name);
+    } else {
+        let _ = writeln!(code, "pub struct {} {{\n{}}}", struct_name, fields);
+    }
+    // endregion: struct
+
+    // region: read function
+    let is_pos_used =
+        element_def.attributes.iter().any(|attribute| !attribute.optional) || occurrences.iter().any(|occurrence| occurrence.min > 0);
+    let _ = write!(
+        code,
+        "\nfn read_{}(cursor: &mut Cursor, {}: usize) -> Result<{}, Violation> {{\n",
+        snake_case(&element_def.name),
+        if is_pos_used { "pos" } else { "_pos" },
+        struct_name,
+    );
+    for field in attribute_fields.iter() {
+        let _ = writeln!(code, "    let mut {} = None;", field);
+    }
+    if element_def.attributes.is_empty() {
+        code.push_str(
+            "    if let Some((name, _value, value_pos)) = cursor.attribute()? {
+        return Err(Cursor::violation(value_pos, schema::ERR_UNDECLARED_ATTRIBUTE, name));
+    }
+",
+        );
+    } else {
+        code.push_str("    while let Some((name, value, value_pos)) = cursor.attribute()? {\n        match name {\n");
+        for (attribute, field) in element_def.attributes.iter().zip(attribute_fields.iter()) {
+            let _ = writeln!(
+                code,
+                "            \"{}\" => {} = Some(Cursor::attribute_value(value, Datatype::{:?}, value_pos, name)?),",
+                attribute.name, field, attribute.datatype
+            );
+        }
+        code.push_str(
+            "            _ => return Err(Cursor::violation(value_pos, schema::ERR_UNDECLARED_ATTRIBUTE, name)),
+        }
+    }
+",
+        );
+    }
+    for (occurrence, field) in occurrences.iter().zip(child_fields.iter()) {
+        let init = if occurrence.max == 2 { "Vec::new()" } else { "None" };
+        let _ = writeln!(code, "    let mut {} = {};", field, init);
+    }
+    if let Some(field) = &text_field {
+        let _ = writeln!(code, "    let mut {} = String::new();", field);
+    }
+    // without child elements and text only the end is allowed, the match does not loop
+    let is_loop = !occurrences.is_empty() || text_field.is_some();
+    let mut arms = String::new();
+    for ((occurrence, field), is_boxed) in occurrences.iter().zip(child_fields.iter()).zip(boxed.iter()) {
+        let read = schema
+            .element(&occurrence.name)
+            .map(|child_def| read_value(child_def, "child_pos"))
+            .unwrap_or_default();
+        let read = if *is_boxed { format!("Box::new({})", read) } else { read };
+        let store = if occurrence.max == 2 {
+            format!("{}.push({}),", field, read)
+        } else {
+            format!(
+                "{{\n                let value = {};\n                Cursor::set_once(&mut {}, value, child_pos, \"{}\")?\n            }}",
+                read, field, occurrence.name
+            )
+        };
+        let _ = writeln!(arms, "            Child::Element(\"{}\", child_pos) => {}", occurrence.name, store);
+    }
+    arms.push_str(
+        "            Child::Element(name, child_pos) => return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, name)),\n",
+    );
+    match &text_field {
+        Some(field) => {
+            let _ = writeln!(
+                arms,
+                "            Child::Text(txt, _txt_pos) => {}.push_str(&entities::decode(txt)),",
+                field
+            );
+        }
+        None => {
+            let _ = writeln!(
+                arms,
+                "            Child::Text(_txt, txt_pos) => return Err(Cursor::violation(txt_pos, schema::ERR_TEXT_NOT_ALLOWED, \"{}\")),",
+                element_def.name
+            );
+        }
+    }
+    if is_loop {
+        code.push_str("    loop {\n        match cursor.child()? {\n");
+        code.push_str(&arms);
+        code.push_str("            Child::End => break,\n        }\n    }\n");
+    } else {
+        code.push_str("    match cursor.child()? {\n");
+        for line in arms.lines() {
+            let _ = writeln!(code, "{}", &line[4..]);
+        }
+        code.push_str("        Child::End => {}\n    }\n");
+    }
+    let mut values = String::new();
+    for (attribute, field) in element_def.attributes.iter().zip(attribute_fields.iter()) {
+        if attribute.optional {
+            let _ = writeln!(values, "        {},", field);
+        } else {
+            let _ = writeln!(
+                values,
+                "        {}: {}.ok_or_else(|| Cursor::violation(pos, schema::ERR_MISSING_ATTRIBUTE, \"{}\"))?,",
+                field, field, attribute.name
+            );
+        }
+    }
+    for (occurrence, field) in occurrences.iter().zip(child_fields.iter()) {
+        let _ = match (occurrence.min, occurrence.max) {
+            (0, _) => writeln!(values, "        {},", field),
+            (min, 2) => writeln!(
+                values,
+                "        {}: Cursor::at_least({}, {}, pos, \"{}\")?,",
+                field, field, min, occurrence.name
+            ),
+            _ => writeln!(
+                values,
+                "        {}: {}.ok_or_else(|| Cursor::violation(pos, schema::ERR_CONTENT_NOT_COMPLETE, \"{}\"))?,",
+                field, field, occurrence.name
+            ),
+        };
+    }
+    if let Some(field) = &text_field {
+        let _ = writeln!(values, "        {},", field);
+    }
+    if values.is_empty() {
+        let _ = writeln!(code, "    Ok({} {{}})\n}}", struct_name);
+    } else {
+        let _ = writeln!(code, "    Ok({} {{\n{}    }})\n}}", struct_name, values);
+    }
+    // endregion: read function
+}
+
+/// The struct of the element `from` contains the struct of the element `to` without a `Vec` between them.
+fn reaches(schema: &Schema, from: &str, to: &str, visited: &mut Vec<String>) -> bool {
+    if from == to {
+        return true;
+    }
+    if visited.iter().any(|name| name == from) {
+        return false;
+    }
+    visited.push(from.to_string());
+    match schema.element(from).map(|element_def| &element_def.content) {
+        Some(Content::Elements { model, .. }) => occurrences(model)
+            .iter()
+            .any(|occurrence| occurrence.max != 2 && reaches(schema, &occurrence.name, to, visited)),
+        _ => false,
+    }
+}
+
+/// the Rust type of the element value
+fn value_type(element_def: &ElementDef) -> String {
+    match element_def.content {
+        Content::Datatype(datatype) => datatype_type(datatype).to_string(),
+        Content::Elements { .. } => camel_case(&element_def.name),
+    }
+}
+
+/// the expression that reads the element after its start tag
+fn read_value(element_def: &ElementDef, pos: &str) -> String {
+    match element_def.content {
+        Content::Datatype(datatype) => format!("cursor.text_value(Datatype::{:?}, {}, \"{}\")?", datatype, pos, element_def.name),
+        Content::Elements { .. } => format!("read_{}(cursor, {})?", snake_case(&element_def.name), pos),
+    }
+}
+
+fn datatype_type(datatype: Datatype) -> &'static str {
+    match datatype {
+        Datatype::Text | Datatype::Token => "String",
+        Datatype::Integer => "i64",
+        Datatype::Decimal => "f64",
+        Datatype::Boolean => "bool",
+    }
+}
+
+fn occurrences(particle: &Particle) -> Vec<Occurrence> {
+    match particle {
+        Particle::Element(name) => vec![Occurrence {
+            name: name.clone(),
+            min: 1,
+            max: 1,
+        }],
+        Particle::Text => Vec::new(),
+        Particle::Sequence(particles) => {
+            let mut all: Vec<Occurrence> = Vec::new();
+            for occurrence in particles.iter().flat_map(occurrences) {
+                match all.iter_mut().find(|existing| existing.name == occurrence.name) {
+                    Some(existing) => {
+                        existing.min += occurrence.min;
+                        existing.max = (existing.max + occurrence.max).min(2);
+                    }
+                    None => all.push(occurrence),
+                }
+            }
+            all
+        }
+        Particle::Choice(particles) => {
+            let alternatives: Vec<Vec<Occurrence>> = particles.iter().map(occurrences).collect();
+            let mut all: Vec<Occurrence> = Vec::new();
+            for occurrence in alternatives.iter().flatten() {
+                if all.iter().any(|existing| existing.name == occurrence.name) {
+                    continue;
+                }
+                let in_alternatives = alternatives
+                    .iter()
+                    .map(|alternative| alternative.iter().find(|other| other.name == occurrence.name));
+                let min = in_alternatives
+                    .clone()
+                    .map(|other| other.map_or(0, |other| other.min))
+                    .min()
+                    .unwrap_or(0);
+                let max = in_alternatives.map(|other| other.map_or(0, |other| other.max)).max().unwrap_or(0);
+                all.push(Occurrence {
+                    name: occurrence.name.clone(),
+                    min,
+                    max,
+                });
+            }
+            all
+        }
+        Particle::Optional(particle) => map_occurrences(particle, |occurrence| occurrence.min = 0),
+        Particle::ZeroOrMore(particle) => map_occurrences(particle, |occurrence| {
+            occurrence.min = 0;
+            occurrence.max = 2;
+        }),
+        Particle::OneOrMore(particle) => map_occurrences(particle, |occurrence| occurrence.max = 2),
+    }
+}
+
+fn map_occurrences(particle: &Particle, change: fn(&mut Occurrence)) -> Vec<Occurrence> {
+    let mut all = occurrences(particle);
+    all.iter_mut().for_each(change);
+    all
+}
+
+fn snake_case(name: &str) -> String {
+    let mut ident: String = name
+        .chars()
+        .map(|ch| if ch.is_ascii_alphanumeric() { ch.to_ascii_lowercase() } else { '_' })
+        .collect();
+    if ident.starts_with(|ch: char| ch.is_ascii_digit()) {
+        ident.insert(0, '_');
+    }
+    if KEYWORDS.contains(&ident.as_str()) {
+        ident.push('_');
+    }
+    ident
+}
+
+fn camel_case(name: &str) -> String {
+    let mut ident = String::new();
+    for part in name.split(|ch: char| !ch.is_ascii_alphanumeric()) {
+        let mut chars = part.chars();
+        if let Some(first) = chars.next() {
+            ident.push(first.to_ascii_uppercase());
+            ident.extend(chars);
+        }
+    }
+    if !ident.starts_with(|ch: char| ch.is_ascii_alphabetic()) {
+        ident.insert(0, 'E');
+    }
+    ident
+}
+
+// region: runtime for the generated code
+
+/// The next child of the element.
+pub enum Child<'a> {
+    /// the start tag is read
+    Element(&'a str, usize),
+    Text(&'a str, usize),
+    /// the end tag is read
+    End,
+}
+
+/// Reads the tokens for the generated code. Comments are skipped.
+pub struct Cursor<'a> {
+    input: &'a str,
+    reader_iterator: ReaderForMicroXml<'a>,
+    peeked: Option<(Token<'a>, usize)>,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(input: &'a str) -> Cursor<'a> {
+        Cursor {
+            input,
+            reader_iterator: ReaderForMicroXml::new(input),
+            peeked: None,
+        }
+    }
+
+    pub fn violation(pos: usize, message: &'static str, name: &str) -> Violation {
+        Violation {
+            pos,
+            message,
+            name: name.to_string(),
+        }
+    }
+
+    /// Validates the input with the schema text of the generated code.
+    /// The typed reading after it does not check the order and the choices of the child elements.
+    pub fn validate(schema_text: &str, input: &str) -> Result<(), Violation> {
+        let schema = Schema::parse(schema_text).map_err(|err| Cursor::violation(err.pos, err.message, ""))?;
+        match schema.validate(input).into_iter().next() {
+            Some(violation) => Err(violation),
+            None => Ok(()),
+        }
+    }
+
+    /// the next token and its position, None at the end of input
+    fn next_token(&mut self) -> Result<Option<(Token<'a>, usize)>, Violation> {
+        if let Some(peeked) = self.peeked.take() {
+            return Ok(Some(peeked));
+        }
+        loop {
+            match self.reader_iterator.next() {
+                None => return Ok(None),
+                Some(Err(err_msg)) => return Err(Cursor::violation(self.reader_iterator.pos(), err_msg, "")),
+                Some(Ok(Token::Comment(_txt))) => {}
+                Some(Ok(token)) => {
+                    let pos = schema::position_of(self.input, &token).unwrap_or_else(|| self.reader_iterator.pos());
+                    return Ok(Some((token, pos)));
+                }
+            }
+        }
+    }
+
+    /// Reads the start tag of the root element.
+    pub fn root(&mut self, name: &str) -> Result<usize, Violation> {
+        match self.next_token()? {
+            Some((Token::StartElement(root_name), pos)) if root_name == name => Ok(pos),
+            Some((Token::StartElement(root_name), pos)) => Err(Cursor::violation(pos, schema::ERR_ROOT_NOT_START, root_name)),
+            Some((_token, pos)) => Err(Cursor::violation(pos, schema::ERR_TEXT_NOT_ALLOWED, "")),
+            None => Err(Cursor::violation(self.input.len(), schema::ERR_NO_ROOT, "")),
+        }
+    }
+
+    /// After the root element there can be only comments.
+    pub fn finish(&mut self) -> Result<(), Violation> {
+        match self.next_token()? {
+            Some((Token::StartElement(name), pos)) => Err(Cursor::violation(pos, schema::ERR_ELEMENT_NOT_ALLOWED, name)),
+            Some((_token, pos)) => Err(Cursor::violation(pos, schema::ERR_TEXT_NOT_ALLOWED, "")),
+            None => Ok(()),
+        }
+    }
+
+    /// The next attribute of the start tag: name, raw value and position.
+    pub fn attribute(&mut self) -> Result<Option<(&'a str, &'a str, usize)>, Violation> {
+        match self.next_token()? {
+            Some((Token::Attribute(name, value), pos)) => Ok(Some((name, value, pos))),
+            other => {
+                self.peeked = other;
+                Ok(None)
+            }
+        }
+    }
+
+    /// The next child element, text or the end of the element.
+    /// Elements not closed at the end of input are closed.
+    pub fn child(&mut self) -> Result<Child<'a>, Violation> {
+        loop {
+            match self.next_token()? {
+                Some((Token::StartElement(name), pos)) => return Ok(Child::Element(name, pos)),
+                Some((Token::TextNode(txt), pos)) => return Ok(Child::Text(txt, pos)),
+                Some((Token::EndElement(_), _)) | None => return Ok(Child::End),
+                // attributes are read before the children, comments are skipped
+                Some((Token::Attribute(..), _)) | Some((Token::Comment(_), _)) => {}
+            }
+        }
+    }
+
+    /// Skips the rest of the element after its start tag.
+    pub fn skip_element(&mut self) -> Result<(), Violation> {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.next_token()? {
+                Some((Token::StartElement(_), _)) => depth += 1,
+                Some((Token::EndElement(_), _)) | None => depth -= 1,
+                Some(_) => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads the element with only text and converts it to the datatype.
+    pub fn text_value<T: FromStr>(&mut self, datatype: Datatype, pos: usize, name: &str) -> Result<T, Violation> {
+        if let Some((attribute_name, _value, attribute_pos)) = self.attribute()? {
+            return Err(Cursor::violation(attribute_pos, schema::ERR_UNDECLARED_ATTRIBUTE, attribute_name));
+        }
+        let mut text = String::new();
+        loop {
+            match self.child()? {
+                Child::Element(child_name, child_pos) => {
+                    return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, child_name))
+                }
+                Child::Text(txt, _txt_pos) => text.push_str(&entities::decode(txt)),
+                Child::End => break,
+            }
+        }
+        convert(&text, datatype).ok_or_else(|| Cursor::violation(pos, schema::ERR_TEXT_DATATYPE, name))
+    }
+
+    /// Decodes the raw attribute value and converts it to the datatype.
+    pub fn attribute_value<T: FromStr>(value: &str, datatype: Datatype, pos: usize, name: &str) -> Result<T, Violation> {
+        convert(&entities::decode(value), datatype).ok_or_else(|| Cursor::violation(pos, schema::ERR_ATTRIBUTE_DATATYPE, name))
+    }
+
+    /// The element that can be only once.
+    pub fn set_once<T>(field: &mut Option<T>, value: T, pos: usize, name: &str) -> Result<(), Violation> {
+        if field.is_some() {
+            return Err(Cursor::violation(pos, schema::ERR_ELEMENT_NOT_ALLOWED, name));
+        }
+        *field = Some(value);
+        Ok(())
+    }
+
+    /// The repeated element with the minimum count.
+    pub fn at_least<T>(values: Vec<T>, min: usize, pos: usize, name: &str) -> Result<Vec<T>, Violation> {
+        if values.len() < min {
+            return Err(Cursor::violation(pos, schema::ERR_CONTENT_NOT_COMPLETE, name));
+        }
+        Ok(values)
+    }
+}
+
+/// The leading and trailing whitespace is not significant, like in the validator. Only `text` is kept as it is.
+fn convert<T: FromStr>(value: &str, datatype: Datatype) -> Option<T> {
+    if !datatype.is_valid(value) {
+        return None;
+    }
+    match datatype {
+        Datatype::Text => value.parse().ok(),
+        _ => value.trim().parse().ok(),
+    }
+}
+
+// endregion
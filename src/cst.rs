@@ -0,0 +1,473 @@
+//! cst.rs - lossless concrete syntax tree
+//!
+//! Every piece of the CST is a slice of the source with its exact byte range.
+//! The whitespace-only text between tags is trivia: the `leading_trivia` of the next element or comment,
+//! or the `trailing_trivia` before the end tag or the end of the source.
+//! The whitespace inside tags, `<br/>` vs `<br />` and the entity spelling are kept.
+//! Printing the CST with `Display` reproduces the source byte-for-byte.
+//!
+//! The CST accepts the same source as `ReaderForMicroXml::new()` and follows its rules:
+//! - the whitespace after `<` is allowed also before `!` and `/`, like `< /a>`
+//! - the element name ends with whitespace, `/` or `>`, the end tag name with whitespace or `>`,
+//!   the attribute name with whitespace or `=`, so `<a=b/>` is the element `a=b` and `</a/>` closes with the name `a/`
+//! - the `=` before the attribute value is optional and the value is in double quotes
+//! - the comment starts with `<!` and two chars that are not checked
+//! - the end tag closes the open element without checking the name
+//!   and the end tag without a start tag is a `StrayEndTag` node
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+pub const ERR_NOT_CLOSED_COMMENT: &str = "Error: Comment is not closed with -->.";
+pub const ERR_NOT_CLOSED_TAG: &str = "Error: Tag is not closed with >.";
+pub const ERR_NOT_CLOSED_ATTRIBUTE_VALUE: &str = "Error: Attribute value is not closed with the quote.";
+pub const ERR_ATTRIBUTE_NOT_QUOTED: &str = "Error: Attribute value is not quoted.";
+pub const ERR_EMPTY_NAME: &str = "Error: Name is empty.";
+pub const ERR_NAME_HAS_LESS_THAN: &str = "Error: Name has the char < .";
+
+/// Error of the CST parser with the byte position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CstError {
+    pub pos: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for CstError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} Position: {}", self.message, self.pos)
+    }
+}
+
+/// The root of the CST.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cst<'a> {
+    pub source: &'a str,
+    pub nodes: Vec<Node<'a>>,
+    /// whitespace at the end of the source
+    pub trailing_trivia: &'a str,
+}
+
+/// Elements are the most common nodes, boxing them would not save memory.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node<'a> {
+    Element(Element<'a>),
+    Text(Text<'a>),
+    Comment(Comment<'a>),
+    StrayEndTag(StrayEndTag<'a>),
+}
+
+/// The element from `<` of the start tag to `>` of the end tag.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Element<'a> {
+    pub range: Range<usize>,
+    pub leading_trivia: &'a str,
+    pub start_tag: StartTag<'a>,
+    pub nodes: Vec<Node<'a>>,
+    /// whitespace before the end tag
+    pub trailing_trivia: &'a str,
+    /// None for self-closing elements and for elements not closed at the end of the source.
+    /// The name can be different from the start tag name, like in the reader.
+    pub end_tag: Option<EndTag<'a>>,
+}
+
+/// `<` before_name name attributes before_close close
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StartTag<'a> {
+    pub range: Range<usize>,
+    pub before_name: &'a str,
+    pub name: &'a str,
+    pub name_range: Range<usize>,
+    pub attributes: Vec<Attribute<'a>>,
+    pub before_close: &'a str,
+    /// `>` or `/>`, also with whitespace like `/ >`
+    pub close: &'a str,
+}
+
+/// `<` before_slash `/` before_name name before_close `>`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EndTag<'a> {
+    pub range: Range<usize>,
+    pub before_slash: &'a str,
+    pub before_name: &'a str,
+    pub name: &'a str,
+    pub name_range: Range<usize>,
+    pub before_close: &'a str,
+}
+
+/// End tag without a start tag, the reader returns it as `Token::EndElement`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StrayEndTag<'a> {
+    pub leading_trivia: &'a str,
+    pub end_tag: EndTag<'a>,
+}
+
+/// leading_trivia name before_equals equals after_equals `"` raw_value `"`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attribute<'a> {
+    /// from the name to the closing quote
+    pub range: Range<usize>,
+    pub leading_trivia: &'a str,
+    pub name: &'a str,
+    pub name_range: Range<usize>,
+    pub before_equals: &'a str,
+    /// `=` or empty, like in the reader the `=` is optional
+    pub equals: &'a str,
+    pub after_equals: &'a str,
+    /// the value "as is", with the entities not decoded
+    pub raw_value: &'a str,
+    pub value_range: Range<usize>,
+}
+
+/// Text with at least one non-whitespace char, "as is".
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Text<'a> {
+    pub range: Range<usize>,
+    pub text: &'a str,
+}
+
+/// open text `-->`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Comment<'a> {
+    pub range: Range<usize>,
+    pub leading_trivia: &'a str,
+    /// `<!--` as in the source, like in the reader it can be `< !--` and the two chars after `!` are not checked
+    pub open: &'a str,
+    pub text: &'a str,
+}
+
+impl<'a> Cst<'a> {
+    /// Parses the source. Elements not closed at the end of the source have no end tag.
+    pub fn parse(source: &'a str) -> Result<Cst<'a>, CstError> {
+        let mut parser = CstParser { source, pos: 0 };
+        parser.parse()
+    }
+}
+
+impl<'a> Node<'a> {
+    /// The range without the leading trivia.
+    pub fn range(&self) -> Range<usize> {
+        match self {
+            Node::Element(element) => element.range.clone(),
+            Node::Text(text) => text.range.clone(),
+            Node::Comment(comment) => comment.range.clone(),
+            Node::StrayEndTag(stray) => stray.end_tag.range.clone(),
+        }
+    }
+}
+
+impl<'a> Element<'a> {
+    pub fn name(&self) -> &'a str {
+        self.start_tag.name
+    }
+
+    pub fn attribute(&self, name: &str) -> Option<&Attribute<'a>> {
+        self.start_tag.attributes.iter().find(|attribute| attribute.name == name)
+    }
+}
+
+// region: Display reproduces the source
+
+impl fmt::Display for Cst<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for node in self.nodes.iter() {
+            write!(f, "{}", node)?;
+        }
+        f.write_str(self.trailing_trivia)
+    }
+}
+
+impl fmt::Display for Node<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Node::Element(element) => write!(f, "{}", element),
+            Node::Text(text) => f.write_str(text.text),
+            Node::Comment(comment) => write!(f, "{}{}{}-->", comment.leading_trivia, comment.open, comment.text),
+            Node::StrayEndTag(stray) => write!(f, "{}{}", stray.leading_trivia, stray.end_tag),
+        }
+    }
+}
+
+impl fmt::Display for Element<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}{}", self.leading_trivia, self.start_tag)?;
+        for node in self.nodes.iter() {
+            write!(f, "{}", node)?;
+        }
+        f.write_str(self.trailing_trivia)?;
+        if let Some(end_tag) = &self.end_tag {
+            write!(f, "{}", end_tag)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for StartTag<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}{}", self.before_name, self.name)?;
+        for attribute in self.attributes.iter() {
+            write!(f, "{}", attribute)?;
+        }
+        write!(f, "{}{}", self.before_close, self.close)
+    }
+}
+
+impl fmt::Display for EndTag<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}/{}{}{}>", self.before_slash, self.before_name, self.name, self.before_close)
+    }
+}
+
+impl fmt::Display for Attribute<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}{}{}{}{}\"{}\"",
+            self.leading_trivia, self.name, self.before_equals, self.equals, self.after_equals, self.raw_value
+        )
+    }
+}
+
+// endregion
+
+struct CstParser<'a> {
+    source: &'a str,
+    pos: usize,
+}
+
+impl<'a> CstParser<'a> {
+    /// Without recursion: the open elements are on the stack.
+    fn parse(&mut self) -> Result<Cst<'a>, CstError> {
+        let mut nodes = Vec::new();
+        let mut stack: Vec<Element<'a>> = Vec::new();
+        loop {
+            let text_start = self.pos;
+            let text_end = self.rest().find('<').map_or(self.source.len(), |i| self.pos + i);
+            self.pos = text_end;
+            let mut trivia = &self.source[text_start..text_end];
+            if !trivia.trim().is_empty() {
+                let text = Text {
+                    range: text_start..text_end,
+                    text: trivia,
+                };
+                push_node(&mut stack, &mut nodes, Node::Text(text));
+                trivia = "";
+            }
+            if self.pos == self.source.len() {
+                // the elements not closed
+                while let Some(mut element) = stack.pop() {
+                    element.trailing_trivia = trivia;
+                    element.range.end = self.source.len();
+                    trivia = "";
+                    push_node(&mut stack, &mut nodes, Node::Element(element));
+                }
+                return Ok(Cst {
+                    source: self.source,
+                    nodes,
+                    trailing_trivia: trivia,
+                });
+            }
+            // like the reader, the whitespace after `<` is allowed also before `!` and `/`
+            let start = self.pos;
+            self.pos += 1;
+            let after_less_than = self.read_whitespace();
+            let rest = self.rest();
+            if rest.is_empty() {
+                return Err(self.error(ERR_NOT_CLOSED_TAG));
+            } else if rest.starts_with('!') {
+                let comment = self.parse_comment(start, trivia)?;
+                push_node(&mut stack, &mut nodes, Node::Comment(comment));
+            } else if rest.starts_with('/') {
+                let end_tag = self.parse_end_tag(start, after_less_than)?;
+                // like the reader, the end tag closes the open element without checking the name
+                let mut element = match stack.pop() {
+                    Some(element) => element,
+                    None => {
+                        let stray = StrayEndTag {
+                            leading_trivia: trivia,
+                            end_tag,
+                        };
+                        nodes.push(Node::StrayEndTag(stray));
+                        continue;
+                    }
+                };
+                element.trailing_trivia = trivia;
+                element.range.end = end_tag.range.end;
+                element.end_tag = Some(end_tag);
+                push_node(&mut stack, &mut nodes, Node::Element(element));
+            } else {
+                let start_tag = self.parse_start_tag(start, after_less_than)?;
+                let element = Element {
+                    range: start_tag.range.clone(),
+                    leading_trivia: trivia,
+                    start_tag,
+                    nodes: Vec::new(),
+                    trailing_trivia: "",
+                    end_tag: None,
+                };
+                if element.start_tag.close.starts_with('/') {
+                    push_node(&mut stack, &mut nodes, Node::Element(element));
+                } else {
+                    stack.push(element);
+                }
+            }
+        }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.source[self.pos..]
+    }
+
+    fn error(&self, message: &'static str) -> CstError {
+        CstError { pos: self.pos, message }
+    }
+
+    fn read_whitespace(&mut self) -> &'a str {
+        let rest = self.rest();
+        let len = rest.len() - rest.trim_start().len();
+        self.pos += len;
+        &rest[..len]
+    }
+
+    /// moves after the delimiter and returns the content before it
+    fn read_until(&mut self, delimiter: &str, message: &'static str) -> Result<&'a str, CstError> {
+        let rest = self.rest();
+        let end = rest.find(delimiter).ok_or_else(|| self.error(message))?;
+        self.pos += end + delimiter.len();
+        Ok(&rest[..end])
+    }
+
+    /// Like the reader, the name ends with whitespace or one of the delimiters and it cannot have `<`.
+    fn read_name(&mut self, delimiters: &str) -> Result<(&'a str, Range<usize>), CstError> {
+        let rest = self.rest();
+        let end = rest
+            .find(|ch: char| ch.is_whitespace() || ch == '<' || delimiters.contains(ch))
+            .ok_or_else(|| self.error(ERR_NOT_CLOSED_TAG))?;
+        if rest[end..].starts_with('<') {
+            self.pos += end;
+            return Err(self.error(ERR_NAME_HAS_LESS_THAN));
+        }
+        if end == 0 {
+            return Err(self.error(ERR_EMPTY_NAME));
+        }
+        let range = self.pos..self.pos + end;
+        self.pos += end;
+        Ok((&rest[..end], range))
+    }
+
+    /// the position is on `!`, like the reader the two chars after it are not checked
+    fn parse_comment(&mut self, start: usize, leading_trivia: &'a str) -> Result<Comment<'a>, CstError> {
+        let rest = self.rest();
+        if rest.chars().nth(2).is_none() {
+            return Err(self.error(ERR_NOT_CLOSED_COMMENT));
+        }
+        self.pos += rest.chars().take(3).map(char::len_utf8).sum::<usize>();
+        let open = &self.source[start..self.pos];
+        let text = self.read_until("-->", ERR_NOT_CLOSED_COMMENT)?;
+        Ok(Comment {
+            range: start..self.pos,
+            leading_trivia,
+            open,
+            text,
+        })
+    }
+
+    /// the position is after `<` and the whitespace
+    fn parse_start_tag(&mut self, start: usize, before_name: &'a str) -> Result<StartTag<'a>, CstError> {
+        let (name, name_range) = self.read_name("/>")?;
+        let mut attributes = Vec::new();
+        loop {
+            let whitespace = self.read_whitespace();
+            let rest = self.rest();
+            let close_len = if rest.starts_with('>') {
+                Some(1)
+            } else if let Some(after_slash) = rest.strip_prefix('/') {
+                let after_whitespace = after_slash.trim_start();
+                if !after_whitespace.starts_with('>') {
+                    return Err(self.error(ERR_NOT_CLOSED_TAG));
+                }
+                Some(rest.len() - after_whitespace.len() + 1)
+            } else if rest.is_empty() {
+                return Err(self.error(ERR_NOT_CLOSED_TAG));
+            } else {
+                None
+            };
+            if let Some(close_len) = close_len {
+                let close = &rest[..close_len];
+                self.pos += close_len;
+                return Ok(StartTag {
+                    range: start..self.pos,
+                    before_name,
+                    name,
+                    name_range,
+                    attributes,
+                    before_close: whitespace,
+                    close,
+                });
+            }
+            attributes.push(self.parse_attribute(whitespace)?);
+        }
+    }
+
+    fn parse_attribute(&mut self, leading_trivia: &'a str) -> Result<Attribute<'a>, CstError> {
+        // like the reader, the attribute name can have `/` and `>`
+        let (name, name_range) = self.read_name("=")?;
+        let before_equals = self.read_whitespace();
+        // like the reader, the `=` is optional
+        let equals_len = usize::from(self.rest().starts_with('='));
+        let equals = &self.rest()[..equals_len];
+        self.pos += equals_len;
+        let after_equals = self.read_whitespace();
+        if self.rest().is_empty() {
+            return Err(self.error(ERR_NOT_CLOSED_TAG));
+        }
+        // MicroXml has only double quotes
+        if !self.rest().starts_with('"') {
+            return Err(self.error(ERR_ATTRIBUTE_NOT_QUOTED));
+        }
+        self.pos += 1;
+        let value_start = self.pos;
+        let raw_value = self.read_until("\"", ERR_NOT_CLOSED_ATTRIBUTE_VALUE)?;
+        Ok(Attribute {
+            range: name_range.start..self.pos,
+            leading_trivia,
+            name,
+            name_range,
+            before_equals,
+            equals,
+            after_equals,
+            raw_value,
+            value_range: value_start..value_start + raw_value.len(),
+        })
+    }
+
+    /// the position is on `/`
+    fn parse_end_tag(&mut self, start: usize, before_slash: &'a str) -> Result<EndTag<'a>, CstError> {
+        self.pos += 1;
+        let before_name = self.read_whitespace();
+        // like the reader, the end tag name can have `/`
+        let (name, name_range) = self.read_name(">")?;
+        let before_close = self.read_whitespace();
+        if !self.rest().starts_with('>') {
+            return Err(self.error(ERR_NOT_CLOSED_TAG));
+        }
+        self.pos += 1;
+        Ok(EndTag {
+            range: start..self.pos,
+            before_slash,
+            before_name,
+            name,
+            name_range,
+            before_close,
+        })
+    }
+}
+
+/// the node goes into the open element or into the root
+fn push_node<'a>(stack: &mut [Element<'a>], nodes: &mut Vec<Node<'a>>, node: Node<'a>) {
+    match stack.last_mut() {
+        Some(parent) => parent.nodes.push(node),
+        None => nodes.push(node),
+    }
+}
@@ -0,0 +1,51 @@
+//! diagnostics.rs - errors and warnings collected in the recovery mode
+
+/// How bad is the diagnostic.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// well-formed, but probably not what the author wanted, like a not matching end tag
+    Warning,
+    /// mal-formed input, the reader skipped some of it
+    Error,
+}
+
+/// One problem found in the recovery mode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// byte position in the input
+    pub pos: usize,
+    pub severity: Severity,
+    /// the same message that the reader returns as error
+    pub message: &'static str,
+}
+
+/// Warning when the end tag has not the same name as the start tag.
+pub const WARN_END_ELEMENT_NOT_MATCHING: &str = "Warning: End element does not match the start element.";
+/// Warning when the input ends before the element is closed.
+pub const WARN_ELEMENT_NOT_CLOSED: &str = "Warning: Element is not closed.";
+
+impl Diagnostic {
+    /// Line and column (both start with 1) of the diagnostic in the input.
+    pub fn line_column(&self, input: &str) -> (usize, usize) {
+        line_column(input, self.pos)
+    }
+}
+
+/// Converts the byte position to line and column (both start with 1).
+/// The column counts characters, not bytes.
+pub fn line_column(input: &str, pos: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut column = 1;
+    for (char_pos, ch) in input.char_indices() {
+        if char_pos >= pos {
+            break;
+        }
+        if ch == '\n' {
+            line += 1;
+            column = 1;
+        } else {
+            column += 1;
+        }
+    }
+    (line, column)
+}
@@ -0,0 +1,373 @@
+//! diff.rs - structural diff between two dom trees
+//!
+//! `diff()` compares the nodes and returns a `Patch`: a list of edits that transforms the old nodes into the new ones.
+//! The same list is the report of the changes: insertions, deletions, moves, attribute changes and text edits.
+//! The edits have index paths: `[0, 2]` is the third child of the first top-level node.
+//! Every path is valid at the moment the edit is applied, so the edits must be applied in order.
+//! `describe_path()` returns a readable path like `/html[1]/body[1]/p[2]`.
+//!
+//! Children are matched by the element name and the `id` attribute (if both have it), text nodes with text nodes
+//! and comments with comments. The order of attributes is not significant.
+
+use alloc::{format, string::String, vec, vec::Vec};
+use core::fmt;
+
+use crate::dom::{Element, Node};
+
+pub const ERR_PATCH_PATH: &str = "Error: Patch path does not exist.";
+pub const ERR_PATCH_NODE_KIND: &str = "Error: Patch edit does not fit the kind of node.";
+
+/// One edit of the patch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Edit {
+    /// The node is inserted at the path.
+    Insert { path: Vec<usize>, node: Node },
+    /// The node at the path is deleted. The old node is only for the report.
+    Delete { path: Vec<usize>, node: Node },
+    /// The node is removed from the path `from` and then inserted at the path `to` with the same parent.
+    Move { from: Vec<usize>, to: Vec<usize> },
+    /// The attribute of the element is set. The old value is only for the report.
+    SetAttribute {
+        path: Vec<usize>,
+        name: String,
+        old_value: Option<String>,
+        value: String,
+    },
+    /// The attribute of the element is removed. The old value is only for the report.
+    RemoveAttribute { path: Vec<usize>, name: String, old_value: String },
+    /// The content of the text or comment is replaced. The old text is only for the report.
+    SetText { path: Vec<usize>, old_text: String, text: String },
+}
+
+/// List of edits. Empty if the trees are equal.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Patch {
+    pub edits: Vec<Edit>,
+}
+
+/// Compares the old and new nodes.
+pub fn diff(old: &[Node], new: &[Node]) -> Patch {
+    let mut edits = Vec::new();
+    diff_nodes(old, new, &mut Vec::new(), &mut edits);
+    Patch { edits }
+}
+
+impl Patch {
+    pub fn is_empty(&self) -> bool {
+        self.edits.is_empty()
+    }
+
+    /// Applies the edits in order to the old nodes.
+    /// After an error the nodes are partially changed.
+    pub fn apply(&self, nodes: &mut Vec<Node>) -> Result<(), &'static str> {
+        for edit in self.edits.iter() {
+            match edit {
+                Edit::Insert { path, node } => {
+                    let (siblings, index) = siblings_mut(nodes, path)?;
+                    if index > siblings.len() {
+                        return Err(ERR_PATCH_PATH);
+                    }
+                    siblings.insert(index, node.clone());
+                }
+                Edit::Delete { path, node: _ } => {
+                    let (siblings, index) = siblings_mut(nodes, path)?;
+                    if index >= siblings.len() {
+                        return Err(ERR_PATCH_PATH);
+                    }
+                    siblings.remove(index);
+                }
+                Edit::Move { from, to } => {
+                    // the paths come from the user, so they can be empty
+                    let (to_index, to_parent) = to.split_last().ok_or(ERR_PATCH_PATH)?;
+                    let (_from_index, from_parent) = from.split_last().ok_or(ERR_PATCH_PATH)?;
+                    if from_parent != to_parent {
+                        return Err(ERR_PATCH_PATH);
+                    }
+                    let (siblings, from_index) = siblings_mut(nodes, from)?;
+                    let to_index = *to_index;
+                    if from_index >= siblings.len() || to_index >= siblings.len() {
+                        return Err(ERR_PATCH_PATH);
+                    }
+                    let node = siblings.remove(from_index);
+                    siblings.insert(to_index, node);
+                }
+                Edit::SetAttribute { path, name, value, .. } => {
+                    element_mut(nodes, path)?.set_attribute(name, value);
+                }
+                Edit::RemoveAttribute { path, name, .. } => {
+                    element_mut(nodes, path)?.remove_attribute(name);
+                }
+                Edit::SetText { path, text, .. } => match node_mut(nodes, path)? {
+                    Node::Text(old_text) | Node::Comment(old_text) => *old_text = text.clone(),
+                    Node::Element(_) => return Err(ERR_PATCH_NODE_KIND),
+                },
+            }
+        }
+        Ok(())
+    }
+}
+
+/// One edit per line.
+impl fmt::Display for Patch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for edit in self.edits.iter() {
+            writeln!(f, "{}", edit)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for Edit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Edit::Insert { path, node } => write!(f, "insert {} {}", PathDisplay(path), node),
+            Edit::Delete { path, node } => write!(f, "delete {} {}", PathDisplay(path), node),
+            Edit::Move { from, to } => write!(f, "move {} {}", PathDisplay(from), PathDisplay(to)),
+            Edit::SetAttribute {
+                path,
+                name,
+                old_value,
+                value,
+            } => match old_value {
+                Some(old_value) => write!(f, "attribute {} {}: {:?} -> {:?}", PathDisplay(path), name, old_value, value),
+                None => write!(f, "attribute {} {}: {:?}", PathDisplay(path), name, value),
+            },
+            Edit::RemoveAttribute { path, name, old_value } => {
+                write!(f, "remove-attribute {} {}: {:?}", PathDisplay(path), name, old_value)
+            }
+            Edit::SetText { path, old_text, text } => write!(f, "text {}: {:?} -> {:?}", PathDisplay(path), old_text, text),
+        }
+    }
+}
+
+/// index path written like `/0/2`
+struct PathDisplay<'p>(&'p [usize]);
+
+impl fmt::Display for PathDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for index in self.0.iter() {
+            write!(f, "/{}", index)?;
+        }
+        Ok(())
+    }
+}
+
+/// Readable path like `/html[1]/body[1]/p[2]` or `/p[1]/text()[1]` for the index path.
+/// The position is 1-based among the siblings with the same name, like in the module `path`.
+/// None if the path does not exist.
+pub fn describe_path(nodes: &[Node], path: &[usize]) -> Option<String> {
+    let mut description = String::new();
+    let mut siblings = nodes;
+    for (depth, index) in path.iter().enumerate() {
+        let node = siblings.get(*index)?;
+        let position = siblings[..*index].iter().filter(|sibling| same_kind(sibling, node)).count() + 1;
+        match node {
+            Node::Element(element) => {
+                description.push_str(&format!("/{}[{}]", element.name, position));
+                siblings = &element.nodes;
+            }
+            Node::Text(_) => description.push_str(&format!("/text()[{}]", position)),
+            Node::Comment(_) => description.push_str(&format!("/comment()[{}]", position)),
+        }
+        // text and comment have no children
+        if depth + 1 < path.len() && node.as_element().is_none() {
+            return None;
+        }
+    }
+    Some(description)
+}
+
+fn same_kind(node_1: &Node, node_2: &Node) -> bool {
+    match (node_1, node_2) {
+        (Node::Element(element_1), Node::Element(element_2)) => element_1.name == element_2.name,
+        (Node::Text(_), Node::Text(_)) | (Node::Comment(_), Node::Comment(_)) => true,
+        _ => false,
+    }
+}
+
+/// the nodes are matched if they have the same key
+fn same_key(node_1: &Node, node_2: &Node) -> bool {
+    match (node_1, node_2) {
+        (Node::Element(element_1), Node::Element(element_2)) => {
+            // the id is compared only if both elements have it
+            let same_id = match (element_1.attribute("id"), element_2.attribute("id")) {
+                (Some(id_1), Some(id_2)) => id_1 == id_2,
+                _ => true,
+            };
+            element_1.name == element_2.name && same_id
+        }
+        _ => same_kind(node_1, node_2),
+    }
+}
+
+fn diff_nodes(old: &[Node], new: &[Node], parent_path: &mut Vec<usize>, edits: &mut Vec<Edit>) {
+    // the matching old index for every new index
+    let mut old_of_new: Vec<Option<usize>> = longest_common_subsequence(old, new);
+    let mut is_old_matched = vec![false; old.len()];
+    for old_index in old_of_new.iter().flatten() {
+        is_old_matched[*old_index] = true;
+    }
+    // moves: not matched old nodes with the same key as a not matched new node
+    let mut is_moved_new = vec![false; new.len()];
+    for (new_index, new_node) in new.iter().enumerate() {
+        if old_of_new[new_index].is_some() {
+            continue;
+        }
+        if let Some(old_index) = (0..old.len()).find(|old_index| !is_old_matched[*old_index] && same_key(&old[*old_index], new_node)) {
+            is_old_matched[old_index] = true;
+            old_of_new[new_index] = Some(old_index);
+            is_moved_new[new_index] = true;
+        }
+    }
+    let child_path = |index: usize| {
+        let mut path = parent_path.clone();
+        path.push(index);
+        path
+    };
+
+    // first the deletions from the end, so the indexes of the old nodes stay valid
+    for old_index in (0..old.len()).rev() {
+        if !is_old_matched[old_index] {
+            edits.push(Edit::Delete {
+                path: child_path(old_index),
+                node: old[old_index].clone(),
+            });
+        }
+    }
+    // the list of the remaining old nodes, simulated while moving
+    let mut new_of_old: Vec<Option<usize>> = vec![None; old.len()];
+    for (new_index, old_index) in old_of_new.iter().enumerate() {
+        if let Some(old_index) = old_index {
+            new_of_old[*old_index] = Some(new_index);
+        }
+    }
+    let mut working: Vec<usize> = (0..old.len()).filter(|old_index| is_old_matched[*old_index]).collect();
+    let mut is_settled: Vec<bool> = (0..old.len())
+        .map(|old_index| new_of_old[old_index].is_some_and(|new_index| !is_moved_new[new_index]))
+        .collect();
+    // then the moves in the new order, after the last settled node that comes before
+    for new_index in (0..new.len()).filter(|new_index| is_moved_new[*new_index]) {
+        let Some(old_index) = old_of_new[new_index] else { continue };
+        let Some(from) = working.iter().position(|working_index| *working_index == old_index) else {
+            continue;
+        };
+        working.remove(from);
+        let to = working
+            .iter()
+            .rposition(|working_index| is_settled[*working_index] && new_of_old[*working_index].is_some_and(|other| other < new_index))
+            .map_or(0, |position| position + 1);
+        working.insert(to, old_index);
+        is_settled[old_index] = true;
+        if from != to {
+            edits.push(Edit::Move {
+                from: child_path(from),
+                to: child_path(to),
+            });
+        }
+    }
+    // then the insertions in the new order, the nodes before are already in place
+    for (new_index, new_node) in new.iter().enumerate() {
+        if old_of_new[new_index].is_none() {
+            edits.push(Edit::Insert {
+                path: child_path(new_index),
+                node: new_node.clone(),
+            });
+        }
+    }
+    // at the end the content of the matched nodes
+    for (new_index, old_index) in old_of_new.iter().enumerate() {
+        let Some(old_index) = old_index else { continue };
+        parent_path.push(new_index);
+        match (&old[*old_index], &new[new_index]) {
+            (Node::Element(old_element), Node::Element(new_element)) => {
+                diff_attributes(old_element, new_element, parent_path, edits);
+                diff_nodes(&old_element.nodes, &new_element.nodes, parent_path, edits);
+            }
+            (Node::Text(old_text), Node::Text(text)) | (Node::Comment(old_text), Node::Comment(text)) if old_text != text => {
+                edits.push(Edit::SetText {
+                    path: parent_path.clone(),
+                    old_text: old_text.clone(),
+                    text: text.clone(),
+                });
+            }
+            _ => {}
+        }
+        parent_path.pop();
+    }
+}
+
+fn diff_attributes(old: &Element, new: &Element, path: &[usize], edits: &mut Vec<Edit>) {
+    for attribute in new.attributes.iter() {
+        let old_value = old.attribute(&attribute.name);
+        if old_value != Some(attribute.value.as_str()) {
+            edits.push(Edit::SetAttribute {
+                path: path.to_vec(),
+                name: attribute.name.clone(),
+                old_value: old_value.map(String::from),
+                value: attribute.value.clone(),
+            });
+        }
+    }
+    for attribute in old.attributes.iter() {
+        if new.attribute(&attribute.name).is_none() {
+            edits.push(Edit::RemoveAttribute {
+                path: path.to_vec(),
+                name: attribute.name.clone(),
+                old_value: attribute.value.clone(),
+            });
+        }
+    }
+}
+
+/// Matches the nodes with the same key in the same order.
+/// Returns the matching old index for every new index.
+fn longest_common_subsequence(old: &[Node], new: &[Node]) -> Vec<Option<usize>> {
+    // lengths[i][j] is the length for old[i..] and new[j..]
+    let columns = new.len() + 1;
+    let mut lengths = vec![0usize; (old.len() + 1) * columns];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * columns + j] = if same_key(&old[i], &new[j]) {
+                lengths[(i + 1) * columns + j + 1] + 1
+            } else {
+                lengths[(i + 1) * columns + j].max(lengths[i * columns + j + 1])
+            };
+        }
+    }
+    let mut old_of_new = vec![None; new.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if same_key(&old[i], &new[j]) {
+            old_of_new[j] = Some(i);
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * columns + j] >= lengths[i * columns + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    old_of_new
+}
+
+/// the siblings list of the node at the path and its index
+fn siblings_mut<'n>(nodes: &'n mut Vec<Node>, path: &[usize]) -> Result<(&'n mut Vec<Node>, usize), &'static str> {
+    let (index, parent_path) = path.split_last().ok_or(ERR_PATCH_PATH)?;
+    let mut siblings = nodes;
+    for parent_index in parent_path.iter() {
+        siblings = match siblings.get_mut(*parent_index) {
+            Some(Node::Element(element)) => &mut element.nodes,
+            _ => return Err(ERR_PATCH_PATH),
+        };
+    }
+    Ok((siblings, *index))
+}
+
+fn node_mut<'n>(nodes: &'n mut Vec<Node>, path: &[usize]) -> Result<&'n mut Node, &'static str> {
+    let (siblings, index) = siblings_mut(nodes, path)?;
+    siblings.get_mut(index).ok_or(ERR_PATCH_PATH)
+}
+
+fn element_mut<'n>(nodes: &'n mut Vec<Node>, path: &[usize]) -> Result<&'n mut Element, &'static str> {
+    node_mut(nodes, path)?.as_element_mut().ok_or(ERR_PATCH_NODE_KIND)
+}
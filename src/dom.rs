@@ -0,0 +1,270 @@
+//! dom.rs - mutable tree of owned nodes built from the reader tokens
+//!
+//! The text and attribute values in the tree are decoded: `&amp;` is stored as `&`.
+//! They are escaped again when the tree is written with `write_to()` or `to_string()`.
+//! Unknown references like `&nbsp;` are not decoded and are written as they are.
+
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use core::fmt;
+
+use crate::entities;
+use crate::tree_sink::{self, DomSink};
+use crate::ReaderForMicroXml;
+
+/// Node in the tree: element, text or comment.
+/// Comments are "as is", like the reader returns them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Node {
+    Element(Element),
+    Text(String),
+    Comment(String),
+}
+
+/// Element with name, attributes and children nodes.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Element {
+    pub name: String,
+    pub attributes: Vec<Attribute>,
+    /// Children
+    pub nodes: Vec<Node>,
+}
+
+/// Attribute of an element.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Attribute {
+    pub name: String,
+    pub value: String,
+}
+
+impl Node {
+    /// Returns the element if the node is an element.
+    pub fn as_element(&self) -> Option<&Element> {
+        match self {
+            Node::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+
+    /// Returns the mutable element if the node is an element.
+    pub fn as_element_mut(&mut self) -> Option<&mut Element> {
+        match self {
+            Node::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+
+    /// Writes the node as MicroXml with escaping.
+    /// The element without children is written as `<name/>`.
+    pub fn write_to<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        match self {
+            Node::Element(element) => element.write_to(w),
+            Node::Text(txt) => entities::write_escaped_text(w, txt),
+            Node::Comment(txt) => write!(w, "<!--{}-->", txt),
+        }
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f)
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_to(f)
+    }
+}
+
+impl Element {
+    /// New element without attributes and children.
+    pub fn new(name: &str) -> Element {
+        Element {
+            name: name.to_owned(),
+            attributes: Vec::new(),
+            nodes: Vec::new(),
+        }
+    }
+
+    /// Value of the attribute with this name.
+    pub fn attribute(&self, name: &str) -> Option<&str> {
+        self.attributes
+            .iter()
+            .find(|attribute| attribute.name == name)
+            .map(|attribute| attribute.value.as_str())
+    }
+
+    /// Iterator over the children elements, without text and comments.
+    pub fn elements(&self) -> impl Iterator<Item = &Element> {
+        self.nodes.iter().filter_map(Node::as_element)
+    }
+
+    /// Iterator over the mutable children elements.
+    pub fn elements_mut(&mut self) -> impl Iterator<Item = &mut Element> {
+        self.nodes.iter_mut().filter_map(Node::as_element_mut)
+    }
+
+    // region: editing
+
+    /// Sets the value of the attribute. Adds the attribute at the end if it does not exist.
+    pub fn set_attribute(&mut self, name: &str, value: &str) {
+        match self.attributes.iter_mut().find(|attribute| attribute.name == name) {
+            Some(attribute) => value.clone_into(&mut attribute.value),
+            None => self.attributes.push(Attribute {
+                name: name.to_owned(),
+                value: value.to_owned(),
+            }),
+        }
+    }
+
+    /// Removes the attribute and returns its value.
+    pub fn remove_attribute(&mut self, name: &str) -> Option<String> {
+        let index = self.attributes.iter().position(|attribute| attribute.name == name)?;
+        Some(self.attributes.remove(index).value)
+    }
+
+    /// Appends the node as the last child.
+    pub fn append_child(&mut self, node: Node) {
+        self.nodes.push(node);
+    }
+
+    /// Inserts the node at the index. Panics if index > nodes.len(), like Vec::insert.
+    pub fn insert_child(&mut self, index: usize, node: Node) {
+        self.nodes.insert(index, node);
+    }
+
+    /// Removes the child and drops it. Returns false if there is no child at the index.
+    pub fn remove_child(&mut self, index: usize) -> bool {
+        self.detach_child(index).is_some()
+    }
+
+    /// Removes the child and returns it, so the subtree can be moved somewhere else.
+    pub fn detach_child(&mut self, index: usize) -> Option<Node> {
+        if index < self.nodes.len() {
+            Some(self.nodes.remove(index))
+        } else {
+            None
+        }
+    }
+
+    /// Replaces the child and returns the old one.
+    pub fn replace_child(&mut self, index: usize, node: Node) -> Option<Node> {
+        let child = self.nodes.get_mut(index)?;
+        Some(core::mem::replace(child, node))
+    }
+
+    /// The child at index is moved inside the wrapper element
+    /// (as its last child) and the wrapper takes its place.
+    /// Returns false if there is no child at the index.
+    pub fn wrap_child(&mut self, index: usize, mut wrapper: Element) -> bool {
+        match self.nodes.get_mut(index) {
+            Some(child) => {
+                let child = core::mem::replace(child, Node::Text(String::new()));
+                wrapper.nodes.push(child);
+                self.nodes[index] = Node::Element(wrapper);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Keeps only the nodes for which the predicate returns true, in all descendants.
+    pub fn retain_nodes<F: FnMut(&Node) -> bool>(&mut self, mut predicate: F) {
+        self.retain_nodes_recursive(&mut predicate);
+    }
+
+    fn retain_nodes_recursive<F: FnMut(&Node) -> bool>(&mut self, predicate: &mut F) {
+        self.nodes.retain(|node| predicate(node));
+        for element in self.elements_mut() {
+            element.retain_nodes_recursive(predicate);
+        }
+    }
+
+    /// Removes all comments in all descendants.
+    pub fn remove_comments(&mut self) {
+        self.retain_nodes(|node| !matches!(node, Node::Comment(_)));
+    }
+
+    /// Concatenated text of all descendants.
+    pub fn text(&self) -> String {
+        let mut text = String::new();
+        for node in self.nodes.iter() {
+            match node {
+                Node::Element(element) => text.push_str(&element.text()),
+                Node::Text(txt) => text.push_str(txt),
+                Node::Comment(_) => {}
+            }
+        }
+        text
+    }
+
+    /// Replaces all children with one text node.
+    pub fn set_text(&mut self, text: &str) {
+        self.nodes.clear();
+        self.nodes.push(Node::Text(text.to_owned()));
+    }
+
+    // endregion: editing
+
+    /// Writes the element as MicroXml with escaping.
+    /// The element without children is written as `<name/>`.
+    pub fn write_to<W: fmt::Write>(&self, w: &mut W) -> fmt::Result {
+        w.write_char('<')?;
+        w.write_str(&self.name)?;
+        for attribute in self.attributes.iter() {
+            write!(w, " {}=\"", attribute.name)?;
+            entities::write_escaped_attribute(w, &attribute.value)?;
+            w.write_char('"')?;
+        }
+        if self.nodes.is_empty() {
+            return w.write_str("/>");
+        }
+        w.write_char('>')?;
+        write_nodes(&self.nodes, w)?;
+        write!(w, "</{}>", self.name)
+    }
+}
+
+/// Writes all the nodes as MicroXml with escaping.
+pub fn write_nodes<W: fmt::Write>(nodes: &[Node], w: &mut W) -> fmt::Result {
+    for node in nodes.iter() {
+        node.write_to(w)?;
+    }
+    Ok(())
+}
+
+/// All the nodes as MicroXml string with escaping.
+pub fn nodes_to_string(nodes: &[Node]) -> String {
+    let mut text = String::new();
+    // writing to a String cannot fail
+    let _ = write_nodes(nodes, &mut text);
+    text
+}
+
+/// Reads all the tokens and returns the top-level nodes.
+/// The text and attribute values are decoded.
+/// Not closed elements are closed at the end of input, like the reader is lenient.
+/// End elements without start element are ignored.
+/// The reader can be configured before: limits, whitespace, document, html.
+pub fn read_nodes(reader_iterator: ReaderForMicroXml) -> Result<Vec<Node>, &'static str> {
+    // the builder keeps the stack of open elements instead of recursion, because of untrusted deep input
+    tree_sink::build(reader_iterator, DomSink::default())
+}
+
+/// Reads a fragment: top-level text, more root elements,...
+pub fn parse_fragment(input: &str) -> Result<Vec<Node>, &'static str> {
+    read_nodes(ReaderForMicroXml::new_fragment(input))
+}
+
+/// Reads a document and returns the root element.
+pub fn parse_document(input: &str) -> Result<Element, &'static str> {
+    let nodes = read_nodes(ReaderForMicroXml::new_document(input))?;
+    // the document reader guarantees exactly one root element
+    nodes
+        .into_iter()
+        .find_map(|node| match node {
+            Node::Element(element) => Some(element),
+            _ => None,
+        })
+        .ok_or("Error: Document has no root element.")
+}
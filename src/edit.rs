@@ -0,0 +1,190 @@
+//! edit.rs - format-preserving edits
+//!
+//! The edits are minimal text patches on the original source, everything else stays untouched.
+//! The elements and attributes are addressed by the slices of the reader tokens:
+//! the name of `Token::StartElement` and the value of `Token::Attribute` are slices of the source
+//! and their position is the span of the token.
+//!
+//! ```
+//! use reader_for_microxml::{edit::Editor, ReaderForMicroXml, Token};
+//! let source = "<a>\n  <b   x = \"1\" />\n</a>";
+//! let mut editor = Editor::new(source).unwrap();
+//! for result_token in ReaderForMicroXml::new(source) {
+//!     match result_token.unwrap() {
+//!         Token::Attribute("x", value) => editor.replace_attribute_value(value, "2").unwrap(),
+//!         Token::StartElement(name @ "a") => editor.rename_element(name, "root").unwrap(),
+//!         _ => {}
+//!     }
+//! }
+//! assert_eq!(editor.apply().unwrap(), "<root>\n  <b   x = \"2\" />\n</root>");
+//! ```
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::cst::{Cst, CstError, Element, Node};
+use crate::entities;
+
+pub const ERR_NOT_IN_SOURCE: &str = "Error: The slice is not a token of the source.";
+pub const ERR_NO_ELEMENT: &str = "Error: There is no element with this start tag name.";
+pub const ERR_NO_ATTRIBUTE: &str = "Error: There is no attribute with this value.";
+pub const ERR_OVERLAPPING_EDITS: &str = "Error: The edits overlap.";
+
+/// Replaces the range of the source with the text. An empty range is an insertion.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TextEdit {
+    pub range: Range<usize>,
+    pub text: String,
+}
+
+/// The span of the token slice in the source.
+pub fn span_of(source: &str, slice: &str) -> Option<Range<usize>> {
+    let start = (slice.as_ptr() as usize).checked_sub(source.as_ptr() as usize)?;
+    let end = start.checked_add(slice.len())?;
+    if end <= source.len() {
+        Some(start..end)
+    } else {
+        None
+    }
+}
+
+/// Applies the edits to the source. The edits can be in any order, but must not overlap.
+/// The insertions at the same position keep their order.
+pub fn apply(source: &str, edits: &[TextEdit]) -> Result<String, &'static str> {
+    let mut edits: Vec<&TextEdit> = edits.iter().collect();
+    edits.sort_by_key(|edit| (edit.range.start, edit.range.end));
+    let mut text = String::with_capacity(source.len());
+    let mut pos = 0;
+    for edit in edits.iter() {
+        if edit.range.start < pos || edit.range.end > source.len() {
+            return Err(ERR_OVERLAPPING_EDITS);
+        }
+        text.push_str(source.get(pos..edit.range.start).ok_or(ERR_NOT_IN_SOURCE)?);
+        text.push_str(&edit.text);
+        pos = edit.range.end;
+    }
+    text.push_str(source.get(pos..).ok_or(ERR_NOT_IN_SOURCE)?);
+    Ok(text)
+}
+
+/// Collects the edits for one source.
+pub struct Editor<'a> {
+    source: &'a str,
+    cst: Cst<'a>,
+    edits: Vec<TextEdit>,
+}
+
+impl<'a> Editor<'a> {
+    /// The source is parsed once into the lossless CST to find the tags.
+    pub fn new(source: &'a str) -> Result<Editor<'a>, CstError> {
+        Ok(Editor {
+            source,
+            cst: Cst::parse(source)?,
+            edits: Vec::new(),
+        })
+    }
+
+    /// The value is the slice from `Token::Attribute`. The new value is escaped.
+    pub fn replace_attribute_value(&mut self, value: &str, new_value: &str) -> Result<(), &'static str> {
+        let pos = self.position_of(value)?;
+        let element = find_element(&self.cst.nodes, &|element| {
+            element
+                .start_tag
+                .attributes
+                .iter()
+                .any(|attribute| attribute.value_range.start == pos)
+        })
+        .ok_or(ERR_NO_ATTRIBUTE)?;
+        let attribute = element
+            .start_tag
+            .attributes
+            .iter()
+            .find(|attribute| attribute.value_range.start == pos)
+            .ok_or(ERR_NO_ATTRIBUTE)?;
+        let text = escape_attribute(new_value);
+        self.edits.push(TextEdit {
+            range: attribute.value_range.clone(),
+            text,
+        });
+        Ok(())
+    }
+
+    /// The element name is the slice from `Token::StartElement`.
+    /// The attribute is inserted after the last attribute or after the name.
+    pub fn insert_attribute(&mut self, element_name: &str, name: &str, value: &str) -> Result<(), &'static str> {
+        let element = self.element_at(element_name)?;
+        let pos = element
+            .start_tag
+            .attributes
+            .last()
+            .map_or(element.start_tag.name_range.end, |attribute| attribute.range.end);
+        let text = alloc::format!(" {}=\"{}\"", name, escape_attribute(value));
+        self.edits.push(TextEdit { range: pos..pos, text });
+        Ok(())
+    }
+
+    /// Renames the start tag and the end tag.
+    pub fn rename_element(&mut self, element_name: &str, new_name: &str) -> Result<(), &'static str> {
+        let element = self.element_at(element_name)?;
+        let mut edits = Vec::new();
+        edits.push(TextEdit {
+            range: element.start_tag.name_range.clone(),
+            text: new_name.to_string(),
+        });
+        if let Some(end_tag) = &element.end_tag {
+            edits.push(TextEdit {
+                range: end_tag.name_range.clone(),
+                text: new_name.to_string(),
+            });
+        }
+        self.edits.append(&mut edits);
+        Ok(())
+    }
+
+    /// Removes the element with its content and the whitespace before it.
+    pub fn remove_element(&mut self, element_name: &str) -> Result<(), &'static str> {
+        let element = self.element_at(element_name)?;
+        let start = element.range.start - element.leading_trivia.len();
+        let end = element.range.end;
+        self.edits.push(TextEdit {
+            range: start..end,
+            text: String::new(),
+        });
+        Ok(())
+    }
+
+    pub fn edits(&self) -> &[TextEdit] {
+        &self.edits
+    }
+
+    /// The source with all the edits.
+    pub fn apply(&self) -> Result<String, &'static str> {
+        apply(self.source, &self.edits)
+    }
+
+    fn position_of(&self, slice: &str) -> Result<usize, &'static str> {
+        span_of(self.source, slice).map(|span| span.start).ok_or(ERR_NOT_IN_SOURCE)
+    }
+
+    fn element_at(&self, element_name: &str) -> Result<&Element<'a>, &'static str> {
+        let pos = self.position_of(element_name)?;
+        find_element(&self.cst.nodes, &|element| element.start_tag.name_range.start == pos).ok_or(ERR_NO_ELEMENT)
+    }
+}
+
+fn find_element<'c, 'a>(nodes: &'c [Node<'a>], predicate: &dyn Fn(&Element<'a>) -> bool) -> Option<&'c Element<'a>> {
+    nodes.iter().find_map(|node| match node {
+        Node::Element(element) if predicate(element) => Some(element),
+        Node::Element(element) => find_element(&element.nodes, predicate),
+        _ => None,
+    })
+}
+
+/// escaped for the double quotes of the attribute
+fn escape_attribute(value: &str) -> String {
+    let mut text = String::new();
+    // writing into a String does not fail
+    let _ = entities::write_escaped_attribute(&mut text, value);
+    text
+}
@@ -0,0 +1,99 @@
+//! entities.rs - escaping and decoding of the MicroXml character references
+//!
+//! The reader returns text "as is". This is the place to decode `&amp;`, `&lt;`, `&#x41;`,...
+//! and to escape text when writing MicroXml.
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, string::String};
+use core::fmt;
+
+/// Writes the text with `&`, `<` and `>` escaped.
+/// Unknown references like `&nbsp;` are not decoded by `decode()`, so they are written as they are.
+pub fn write_escaped_text<W: fmt::Write + ?Sized>(w: &mut W, text: &str) -> fmt::Result {
+    write_escaped(w, text, false)
+}
+
+/// Writes the attribute value with `&`, `<`, `>` and `"` escaped.
+/// The attribute value must be written inside double quotes.
+pub fn write_escaped_attribute<W: fmt::Write + ?Sized>(w: &mut W, value: &str) -> fmt::Result {
+    write_escaped(w, value, true)
+}
+
+fn write_escaped<W: fmt::Write + ?Sized>(w: &mut W, text: &str, is_attribute: bool) -> fmt::Result {
+    let mut start = 0;
+    for (pos, ch) in text.char_indices() {
+        let escaped = match ch {
+            '&' if !is_unknown_reference(&text[pos..]) => "&amp;",
+            '<' => "&lt;",
+            '>' => "&gt;",
+            '"' if is_attribute => "&quot;",
+            _ => continue,
+        };
+        w.write_str(&text[start..pos])?;
+        w.write_str(escaped)?;
+        start = pos + 1;
+    }
+    w.write_str(&text[start..])
+}
+
+/// The text starts with `&name;` and the name is not a known character reference.
+fn is_unknown_reference(text: &str) -> bool {
+    match text[1..].find(';') {
+        Some(semicolon_pos) => {
+            let reference = &text[1..semicolon_pos + 1];
+            !reference.is_empty()
+                && reference.chars().all(|ch| ch.is_ascii_alphanumeric() || ch == '#')
+                && decode_reference(reference).is_none()
+        }
+        None => false,
+    }
+}
+
+/// Decodes one character reference without `&` and `;` like `amp` or `#x41`.
+pub fn decode_reference(reference: &str) -> Option<char> {
+    match reference {
+        "amp" => Some('&'),
+        "lt" => Some('<'),
+        "gt" => Some('>'),
+        "quot" => Some('"'),
+        "apos" => Some('\''),
+        _ => {
+            let number = reference.strip_prefix('#')?;
+            let code = match number.strip_prefix('x') {
+                Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+                None => number.parse().ok()?,
+            };
+            core::char::from_u32(code)
+        }
+    }
+}
+
+/// Decodes all character references. Unknown references are left as they are.
+/// Allocates a new string only if there is something to decode.
+#[cfg(feature = "alloc")]
+pub fn decode(text: &str) -> Cow<'_, str> {
+    if !text.contains('&') {
+        return Cow::Borrowed(text);
+    }
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(amp_pos) = rest.find('&') {
+        decoded.push_str(&rest[..amp_pos]);
+        rest = &rest[amp_pos..];
+        let decoded_char = rest
+            .find(';')
+            .and_then(|semicolon_pos| Some((decode_reference(&rest[1..semicolon_pos])?, semicolon_pos)));
+        match decoded_char {
+            Some((ch, semicolon_pos)) => {
+                decoded.push(ch);
+                rest = &rest[semicolon_pos + 1..];
+            }
+            None => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    decoded.push_str(rest);
+    Cow::Owned(decoded)
+}
@@ -0,0 +1,172 @@
+//! extract.rs - typed extraction with `#[derive(FromMicroXml)]`
+//!
+//! The derive macro is in the crate `reader_for_microxml_derive`, with the feature `derive` it is
+//! re-exported here with the same name as the trait. The fields of the struct get the attribute `mx`:
+//! ```ignore
+//! use reader_for_microxml::extract::{self, FromMicroXml};
+//!
+//! #[derive(FromMicroXml)]
+//! struct Book<'a> {
+//!     #[mx(attr = "id")]
+//!     id: &'a str,
+//!     #[mx(attr = "year")]
+//!     year: Option<u32>,
+//!     #[mx(child = "title")]
+//!     title: &'a str,
+//!     #[mx(children = "author")]
+//!     authors: Vec<Author<'a>>,
+//! }
+//!
+//! #[derive(FromMicroXml)]
+//! struct Author<'a> {
+//!     #[mx(text)]
+//!     name: std::borrow::Cow<'a, str>,
+//! }
+//!
+//! let book: Book = extract::from_str(r#"<book id="1"><title>T</title><author>A</author></book>"#, "book").unwrap();
+//! ```
+//! `attr` and `child` are required, or optional with `Option`. `children` is a `Vec`.
+//! A child is a struct with the derive or a value from the text of the element.
+//! `text` is all the text of the element, with `Option` it is None if the element has no text.
+//! The fields without `mx` get `Default::default()`.
+//! The other attributes, elements and text are skipped.
+//!
+//! The values implement `FromText`. `&'a str` is a slice of the input, it fails if the text has
+//! references or more parts, `Cow<'a, str>` borrows when it can, `String` always allocates.
+//! The numbers, `bool` and `char` are parsed with `FromStr` without the leading and trailing whitespace,
+//! like the datatypes of the schema.
+//! The errors are `Violation` with the position, like the code generated from the schema.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+pub use crate::codegen::{Child, Cursor};
+use crate::entities;
+use crate::schema;
+pub use crate::schema::Violation;
+#[cfg(feature = "derive")]
+pub use reader_for_microxml_derive::FromMicroXml;
+
+pub const ERR_VALUE: &str = "Error: The value cannot be converted to the type of the field.";
+pub const ERR_NOT_BORROWED: &str = "Error: The value with references or in more parts cannot be borrowed.";
+
+/// The type read from an element. The derive macro implements it for structs.
+pub trait FromMicroXml<'a>: Sized {
+    /// Reads the element after its start tag: the attributes, the children and the end tag.
+    fn read_element(cursor: &mut Cursor<'a>, name: &'a str, pos: usize) -> Result<Self, Violation>;
+}
+
+/// The type of attribute values and text. The text is already decoded.
+pub trait FromText<'a>: Sized {
+    fn from_text(text: Cow<'a, str>) -> Result<Self, &'static str>;
+}
+
+impl<'a> FromText<'a> for &'a str {
+    fn from_text(text: Cow<'a, str>) -> Result<Self, &'static str> {
+        match text {
+            Cow::Borrowed(text) => Ok(text),
+            Cow::Owned(_) => Err(ERR_NOT_BORROWED),
+        }
+    }
+}
+
+impl<'a> FromText<'a> for Cow<'a, str> {
+    fn from_text(text: Cow<'a, str>) -> Result<Self, &'static str> {
+        Ok(text)
+    }
+}
+
+impl<'a> FromText<'a> for String {
+    fn from_text(text: Cow<'a, str>) -> Result<Self, &'static str> {
+        Ok(text.into_owned())
+    }
+}
+
+macro_rules! impl_from_text {
+    ($($value_type:ty),*) => {
+        $(
+            impl<'a> FromText<'a> for $value_type {
+                fn from_text(text: Cow<'a, str>) -> Result<Self, &'static str> {
+                    text.trim().parse().map_err(|_| ERR_VALUE)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_text!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, bool, char);
+
+macro_rules! impl_from_micro_xml {
+    ($($value_type:ty),*) => {
+        $(
+            impl<'a> FromMicroXml<'a> for $value_type {
+                fn read_element(cursor: &mut Cursor<'a>, name: &'a str, pos: usize) -> Result<Self, Violation> {
+                    read_text_element(cursor, name, pos)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_micro_xml!(&'a str, Cow<'a, str>, String);
+impl_from_micro_xml!(i8, i16, i32, i64, i128, isize, u8, u16, u32, u64, u128, usize, f32, f64, bool, char);
+
+/// Reads the root element with the name.
+pub fn from_str<'a, T: FromMicroXml<'a>>(input: &'a str, root: &'a str) -> Result<T, Violation> {
+    let cursor = &mut Cursor::new(input);
+    let pos = cursor.root(root)?;
+    let value = T::read_element(cursor, root, pos)?;
+    cursor.finish()?;
+    Ok(value)
+}
+
+/// The element with only text. The attributes are skipped.
+pub fn read_text_element<'a, T: FromText<'a>>(cursor: &mut Cursor<'a>, name: &'a str, pos: usize) -> Result<T, Violation> {
+    while cursor.attribute()?.is_some() {}
+    let mut text = None;
+    loop {
+        match cursor.child()? {
+            Child::Element(child_name, child_pos) => return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, child_name)),
+            Child::Text(txt, _txt_pos) => push_text(&mut text, txt),
+            Child::End => break,
+        }
+    }
+    self::text(text, pos, name)
+}
+
+/// Decodes the raw attribute value.
+pub fn attribute<'a, T: FromText<'a>>(value: &'a str, pos: usize, name: &str) -> Result<T, Violation> {
+    T::from_text(entities::decode(value)).map_err(|message| Cursor::violation(pos, message, name))
+}
+
+/// Adds the decoded text. The first part stays borrowed if it has no references.
+pub fn push_text<'a>(text: &mut Option<Cow<'a, str>>, txt: &'a str) {
+    match text {
+        Some(text) => text.to_mut().push_str(&entities::decode(txt)),
+        None => *text = Some(entities::decode(txt)),
+    }
+}
+
+/// The text of the element, empty if there is none.
+pub fn text<'a, T: FromText<'a>>(text: Option<Cow<'a, str>>, pos: usize, name: &str) -> Result<T, Violation> {
+    T::from_text(text.unwrap_or(Cow::Borrowed(""))).map_err(|message| Cursor::violation(pos, message, name))
+}
+
+/// The text of the element, None if there is none.
+pub fn optional_text<'a, T: FromText<'a>>(text: Option<Cow<'a, str>>, pos: usize, name: &str) -> Result<Option<T>, Violation> {
+    text.map(|text| T::from_text(text).map_err(|message| Cursor::violation(pos, message, name)))
+        .transpose()
+}
+
+pub fn required_attribute<T>(value: Option<T>, pos: usize, name: &str) -> Result<T, Violation> {
+    value.ok_or_else(|| Cursor::violation(pos, schema::ERR_MISSING_ATTRIBUTE, name))
+}
+
+pub fn required_child<T>(value: Option<T>, pos: usize, name: &str) -> Result<T, Violation> {
+    value.ok_or_else(|| Cursor::violation(pos, schema::ERR_CONTENT_NOT_COMPLETE, name))
+}
+
+pub fn push<T>(values: &mut Vec<T>, value: T) {
+    values.push(value);
+}
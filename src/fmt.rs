@@ -0,0 +1,253 @@
+//! fmt.rs - pretty printer for MicroXml
+//!
+//! The input is read with the reader, so the formatting always agrees with the parsing.
+//! Every element is on its own line with indentation. The start tag is split to one attribute per line
+//! if it is wider than `max_width`. Elements without children are written as `<name/>`.
+//! Whitespace-only text between elements is not significant and is replaced by the indentation.
+//! Elements with significant text (mixed content) are written exactly as they are, to preserve the whitespace.
+//! The text and attribute values are written "as is", without decoding.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::fmt;
+
+use crate::tree_sink::{self, TreeSink};
+use crate::{ReaderForMicroXml, WhitespacePolicy};
+
+pub const ERR_WRITE: &str = "Error: Format write failed.";
+
+/// Options for `format()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FormatOptions {
+    /// spaces for one level of indentation
+    pub indent: usize,
+    /// maximum width of a start tag on one line, including the indentation
+    pub max_width: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> FormatOptions {
+        FormatOptions { indent: 2, max_width: 100 }
+    }
+}
+
+/// Formats the input. The output ends with a newline.
+pub fn format(input: &str, options: FormatOptions) -> Result<String, &'static str> {
+    let mut text = String::new();
+    format_to(input, options, &mut text)?;
+    Ok(text)
+}
+
+/// Formats the input into any writer.
+pub fn format_to<W: fmt::Write>(input: &str, options: FormatOptions, w: &mut W) -> Result<(), &'static str> {
+    let reader_iterator = ReaderForMicroXml::new_fragment(input).with_whitespace(WhitespacePolicy::Preserve);
+    let nodes = tree_sink::build(reader_iterator, RawSink::default())?;
+    let formatter = Formatter { options, w };
+    formatter.write_top_level(&nodes).map_err(|_| ERR_WRITE)
+}
+
+/// Checks if the input is already formatted.
+/// Returns None if it is, else the 1-based number of the first line that is different.
+pub fn check(input: &str, options: FormatOptions) -> Result<Option<usize>, &'static str> {
+    let formatted = format(input, options)?;
+    if formatted == input {
+        return Ok(None);
+    }
+    let mut formatted_lines = formatted.split('\n');
+    let mut line_number = 1;
+    for line in input.split('\n') {
+        if formatted_lines.next() != Some(line) {
+            return Ok(Some(line_number));
+        }
+        line_number += 1;
+    }
+    Ok(Some(line_number))
+}
+
+/// node with the text "as is" from the reader
+enum RawNode<'a> {
+    Element(RawElement<'a>),
+    Text(Cow<'a, str>),
+    Comment(&'a str),
+}
+
+struct RawElement<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, &'a str)>,
+    nodes: Vec<RawNode<'a>>,
+}
+
+impl RawNode<'_> {
+    fn is_whitespace_text(&self) -> bool {
+        matches!(self, RawNode::Text(txt) if txt.trim().is_empty())
+    }
+}
+
+/// mixed content has text that is not only whitespace
+fn is_mixed(nodes: &[RawNode]) -> bool {
+    nodes
+        .iter()
+        .any(|node| matches!(node, RawNode::Text(_)) && !node.is_whitespace_text())
+}
+
+/// the same stack logic like `DomSink`, but with borrowed strings
+#[derive(Default)]
+struct RawSink<'a> {
+    open_elements: Vec<RawElement<'a>>,
+    nodes: Vec<RawNode<'a>>,
+}
+
+impl<'a> RawSink<'a> {
+    fn push_node(&mut self, parent: Option<&usize>, node: RawNode<'a>) {
+        match parent {
+            Some(index) => self.open_elements[*index].nodes.push(node),
+            None => self.nodes.push(node),
+        }
+    }
+}
+
+impl<'a> TreeSink<'a> for RawSink<'a> {
+    type Handle = usize;
+    type Output = Vec<RawNode<'a>>;
+
+    fn create_element(&mut self, name: &'a str) -> usize {
+        self.open_elements.push(RawElement {
+            name,
+            attributes: Vec::new(),
+            nodes: Vec::new(),
+        });
+        self.open_elements.len() - 1
+    }
+
+    fn set_attribute(&mut self, element: &usize, name: &'a str, value: &'a str) {
+        self.open_elements[*element].attributes.push((name, value));
+    }
+
+    fn append_child(&mut self, parent: Option<&usize>, _child: usize) {
+        if let Some(element) = self.open_elements.pop() {
+            self.push_node(parent, RawNode::Element(element));
+        }
+    }
+
+    fn append_text(&mut self, parent: Option<&usize>, text: Cow<'a, str>) {
+        self.push_node(parent, RawNode::Text(text));
+    }
+
+    fn append_comment(&mut self, parent: Option<&usize>, text: &'a str) {
+        self.push_node(parent, RawNode::Comment(text));
+    }
+
+    fn finish(self) -> Vec<RawNode<'a>> {
+        self.nodes
+    }
+}
+
+struct Formatter<'w, W: fmt::Write> {
+    options: FormatOptions,
+    w: &'w mut W,
+}
+
+impl<W: fmt::Write> Formatter<'_, W> {
+    fn write_top_level(mut self, nodes: &[RawNode]) -> fmt::Result {
+        if is_mixed(nodes) {
+            // the trailing whitespace is replaced by the final newline, so the output is stable
+            match nodes.split_last() {
+                Some((RawNode::Text(txt), rest)) => {
+                    self.write_inline(rest)?;
+                    self.w.write_str(txt.trim_end())?;
+                }
+                _ => self.write_inline(nodes)?,
+            }
+            return self.w.write_char('\n');
+        }
+        self.write_block(nodes, 0)
+    }
+
+    fn write_indent(&mut self, depth: usize) -> fmt::Result {
+        for _ in 0..depth * self.options.indent {
+            self.w.write_char(' ')?;
+        }
+        Ok(())
+    }
+
+    /// every node on its own line, whitespace-only text is dropped
+    fn write_block(&mut self, nodes: &[RawNode], depth: usize) -> fmt::Result {
+        for node in nodes.iter().filter(|node| !node.is_whitespace_text()) {
+            self.write_indent(depth)?;
+            match node {
+                RawNode::Element(element) => self.write_block_element(element, depth)?,
+                RawNode::Text(txt) => self.w.write_str(txt)?,
+                RawNode::Comment(txt) => write!(self.w, "<!--{}-->", txt)?,
+            }
+            self.w.write_char('\n')?;
+        }
+        Ok(())
+    }
+
+    fn write_block_element(&mut self, element: &RawElement, depth: usize) -> fmt::Result {
+        let is_empty = element.nodes.iter().all(|node| node.is_whitespace_text());
+        let tag_width = start_tag_width(element, is_empty);
+        if element.attributes.len() > 1 && depth * self.options.indent + tag_width > self.options.max_width {
+            write!(self.w, "<{}", element.name)?;
+            for (name, value) in element.attributes.iter() {
+                self.w.write_char('\n')?;
+                self.write_indent(depth + 1)?;
+                write!(self.w, "{}=\"{}\"", name, value)?;
+            }
+        } else {
+            self.write_start_tag_open(element)?;
+        }
+        if is_empty {
+            return self.w.write_str("/>");
+        }
+        if is_mixed(&element.nodes) {
+            self.w.write_char('>')?;
+            self.write_inline(&element.nodes)?;
+        } else {
+            self.w.write_str(">\n")?;
+            self.write_block(&element.nodes, depth + 1)?;
+            self.write_indent(depth)?;
+        }
+        write!(self.w, "</{}>", element.name)
+    }
+
+    /// start tag on one line without the closing `>` or `/>`
+    fn write_start_tag_open(&mut self, element: &RawElement) -> fmt::Result {
+        write!(self.w, "<{}", element.name)?;
+        for (name, value) in element.attributes.iter() {
+            write!(self.w, " {}=\"{}\"", name, value)?;
+        }
+        Ok(())
+    }
+
+    /// the nodes exactly as they are, only the self-closing syntax is normalized
+    fn write_inline(&mut self, nodes: &[RawNode]) -> fmt::Result {
+        for node in nodes.iter() {
+            match node {
+                RawNode::Element(element) => {
+                    self.write_start_tag_open(element)?;
+                    if element.nodes.is_empty() {
+                        self.w.write_str("/>")?;
+                    } else {
+                        self.w.write_char('>')?;
+                        self.write_inline(&element.nodes)?;
+                        write!(self.w, "</{}>", element.name)?;
+                    }
+                }
+                RawNode::Text(txt) => self.w.write_str(txt)?,
+                RawNode::Comment(txt) => write!(self.w, "<!--{}-->", txt)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// width of the start tag on one line
+fn start_tag_width(element: &RawElement, is_empty: bool) -> usize {
+    let attributes_width: usize = element
+        .attributes
+        .iter()
+        .map(|(name, value)| name.chars().count() + value.chars().count() + 4)
+        .sum();
+    let end_width = if is_empty { 2 } else { 1 };
+    1 + element.name.chars().count() + attributes_width + end_width
+}
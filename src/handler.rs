@@ -0,0 +1,110 @@
+//! handler.rs - SAX-style callbacks driven by the reader
+//!
+//! `drive()` collects the attributes of the start tag, keeps the stack of open elements
+//! and calls the handler. So the handler does not need to match on `Token`.
+
+use alloc::vec::Vec;
+
+use crate::{ReaderForMicroXml, Token};
+
+/// Callbacks for the events of the reader. All methods have an empty default.
+/// The depth of the root element is 1.
+pub trait MicroXmlHandler<'a> {
+    /// Start tag with all its attributes.
+    fn start_element(&mut self, _name: &'a str, _attributes: Attributes<'_, 'a>, _depth: usize) {}
+    /// End of element. The name is from the start tag, also for self-closing elements.
+    fn end_element(&mut self, _name: &'a str, _depth: usize) {}
+    fn text(&mut self, _txt: &'a str) {}
+    fn comment(&mut self, _txt: &'a str) {}
+    /// After the error `drive()` stops and returns the error.
+    fn error(&mut self, _err_msg: &'static str, _pos: usize) {}
+}
+
+/// Iterator over the attributes (name, value) of the start tag.
+#[derive(Clone, Debug)]
+pub struct Attributes<'s, 'a> {
+    attributes: &'s [(&'a str, &'a str)],
+    index: usize,
+}
+
+impl<'s, 'a> Attributes<'s, 'a> {
+    /// Value of the attribute with this name.
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.attributes
+            .iter()
+            .find(|(attr_name, _value)| *attr_name == name)
+            .map(|(_name, value)| *value)
+    }
+
+    /// All the attributes, also the ones already iterated.
+    pub fn as_slice(&self) -> &'s [(&'a str, &'a str)] {
+        self.attributes
+    }
+}
+
+impl<'s, 'a> Iterator for Attributes<'s, 'a> {
+    type Item = (&'a str, &'a str);
+    fn next(&mut self) -> Option<(&'a str, &'a str)> {
+        let attribute = self.attributes.get(self.index)?;
+        self.index += 1;
+        Some(*attribute)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.attributes.len() - self.index;
+        (len, Some(len))
+    }
+}
+
+impl<'s, 'a> ExactSizeIterator for Attributes<'s, 'a> {}
+
+/// Reads all the tokens and calls the handler.
+/// Not closed elements get their `end_element()` at the end of input.
+pub fn drive<'a, H: MicroXmlHandler<'a>>(mut reader_iterator: ReaderForMicroXml<'a>, handler: &mut H) -> Result<(), &'static str> {
+    let mut open_elements: Vec<&'a str> = Vec::new();
+    // the start tag is finished only when a token that is not an attribute comes
+    let mut start_tag: Option<&'a str> = None;
+    let mut attributes: Vec<(&'a str, &'a str)> = Vec::new();
+    loop {
+        let result_token = reader_iterator.next();
+        if let Some(Ok(Token::Attribute(name, value))) = result_token {
+            attributes.push((name, value));
+            continue;
+        }
+        if let Some(name) = start_tag.take() {
+            open_elements.push(name);
+            handler.start_element(
+                name,
+                Attributes {
+                    attributes: &attributes,
+                    index: 0,
+                },
+                open_elements.len(),
+            );
+            attributes.clear();
+        }
+        match result_token {
+            None => break,
+            Some(Err(err_msg)) => {
+                handler.error(err_msg, reader_iterator.pos());
+                return Err(err_msg);
+            }
+            Some(Ok(token)) => match token {
+                Token::StartElement(name) => start_tag = Some(name),
+                Token::TextNode(txt) => handler.text(txt),
+                Token::Comment(txt) => handler.comment(txt),
+                Token::EndElement(_name) => {
+                    let depth = open_elements.len();
+                    if let Some(name) = open_elements.pop() {
+                        handler.end_element(name, depth);
+                    }
+                }
+                Token::Attribute(_name, _value) => {}
+            },
+        }
+    }
+    while let Some(name) = open_elements.pop() {
+        handler.end_element(name, open_elements.len() + 1);
+    }
+    Ok(())
+}
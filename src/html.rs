@@ -0,0 +1,12 @@
+//! html.rs - html compatibility mode
+
+/// The html void elements. They never have content and never have an end tag.  
+/// <https://html.spec.whatwg.org/multipage/syntax.html#void-elements>
+pub const HTML_VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "param", "source", "track", "wbr",
+];
+
+/// Html element names are case-insensitive.
+pub fn is_void_element(name: &str) -> bool {
+    HTML_VOID_ELEMENTS.iter().any(|void_name| void_name.eq_ignore_ascii_case(name))
+}
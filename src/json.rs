@@ -0,0 +1,370 @@
+//! json.rs - MicroXml data model as JSON
+//!
+//! The element is the triple `["name", {"attribute":"value"}, [children]]` and the text is a JSON string:
+//! `["memo", {"lang":"en"}, ["I ", ["em", {}, ["love"]]]]`
+//! The text and attribute values are decoded. Comments are not part of the data model and are skipped.
+//!
+//! The writer streams the tokens of the reader and does not allocate.
+//! The parser (feature `alloc`) returns the dom nodes.
+
+#[cfg(feature = "alloc")]
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+
+#[cfg(feature = "alloc")]
+use crate::dom::{Attribute, Element, Node};
+use crate::{entities, ReaderForMicroXml, Token};
+
+pub const ERR_WRITE: &str = "Error: JSON write failed.";
+
+/// Writes the root element of the document.
+pub fn write_document<W: fmt::Write>(input: &str, w: &mut W) -> Result<(), &'static str> {
+    write_tokens(ReaderForMicroXml::new_document(input), false, w)
+}
+
+/// Writes the fragment as a list of top-level elements and texts.
+pub fn write_fragment<W: fmt::Write>(input: &str, w: &mut W) -> Result<(), &'static str> {
+    write_tokens(ReaderForMicroXml::new_fragment(input), true, w)
+}
+
+/// Without a stack: every element knows only if it needs the comma before it.
+fn write_tokens<W: fmt::Write>(reader_iterator: ReaderForMicroXml, is_list: bool, w: &mut W) -> Result<(), &'static str> {
+    let mut depth = 0;
+    let mut needs_comma = false;
+    // the attributes object is open until the first token that is not an attribute
+    let mut attributes_open = false;
+    let mut needs_attribute_comma = false;
+    if is_list {
+        w.write_char('[').map_err(|_| ERR_WRITE)?;
+    }
+    for result_token in reader_iterator {
+        let token = result_token?;
+        if attributes_open && !matches!(token, Token::Attribute(..)) {
+            attributes_open = false;
+            w.write_str("}, [").map_err(|_| ERR_WRITE)?;
+        }
+        match token {
+            Token::StartElement(name) => {
+                if needs_comma {
+                    w.write_str(", ").map_err(|_| ERR_WRITE)?;
+                }
+                w.write_char('[').map_err(|_| ERR_WRITE)?;
+                write_json_string(w, name, false).map_err(|_| ERR_WRITE)?;
+                w.write_str(", {").map_err(|_| ERR_WRITE)?;
+                depth += 1;
+                attributes_open = true;
+                needs_attribute_comma = false;
+                needs_comma = false;
+            }
+            Token::Attribute(name, value) => {
+                if needs_attribute_comma {
+                    w.write_str(", ").map_err(|_| ERR_WRITE)?;
+                }
+                write_json_string(w, name, false).map_err(|_| ERR_WRITE)?;
+                w.write_char(':').map_err(|_| ERR_WRITE)?;
+                write_json_string(w, value, true).map_err(|_| ERR_WRITE)?;
+                needs_attribute_comma = true;
+            }
+            Token::TextNode(txt) => {
+                if needs_comma {
+                    w.write_str(", ").map_err(|_| ERR_WRITE)?;
+                }
+                write_json_string(w, txt, true).map_err(|_| ERR_WRITE)?;
+                needs_comma = true;
+            }
+            Token::Comment(_txt) => {}
+            Token::EndElement(_name) => {
+                if depth > 0 {
+                    depth -= 1;
+                    w.write_str("]]").map_err(|_| ERR_WRITE)?;
+                    needs_comma = true;
+                }
+            }
+        }
+    }
+    // not closed elements are closed at the end of input
+    if attributes_open {
+        w.write_str("}, [").map_err(|_| ERR_WRITE)?;
+    }
+    for _ in 0..depth {
+        w.write_str("]]").map_err(|_| ERR_WRITE)?;
+    }
+    if is_list {
+        w.write_char(']').map_err(|_| ERR_WRITE)?;
+    }
+    Ok(())
+}
+
+/// JSON string with escaping, optionally with the MicroXml character references decoded
+fn write_json_string<W: fmt::Write>(w: &mut W, text: &str, is_decoded: bool) -> fmt::Result {
+    w.write_char('"')?;
+    let mut rest = text;
+    while let Some(ch) = rest.chars().next() {
+        let mut decoded = None;
+        if ch == '&' && is_decoded {
+            decoded = rest
+                .find(';')
+                .and_then(|semicolon_pos| Some((entities::decode_reference(&rest[1..semicolon_pos])?, semicolon_pos)));
+        }
+        let ch = match decoded {
+            Some((decoded_char, semicolon_pos)) => {
+                rest = &rest[semicolon_pos + 1..];
+                decoded_char
+            }
+            None => {
+                rest = &rest[ch.len_utf8()..];
+                ch
+            }
+        };
+        match ch {
+            '"' => w.write_str("\\\"")?,
+            '\\' => w.write_str("\\\\")?,
+            '\n' => w.write_str("\\n")?,
+            '\r' => w.write_str("\\r")?,
+            '\t' => w.write_str("\\t")?,
+            ch if (ch as u32) < 0x20 => write!(w, "\\u{:04x}", ch as u32)?,
+            ch => w.write_char(ch)?,
+        }
+    }
+    w.write_char('"')
+}
+
+// region: parser
+
+/// Error of the JSON parser with the byte position.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct JsonError {
+    pub pos: usize,
+    pub message: &'static str,
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} Position: {}", self.message, self.pos)
+    }
+}
+
+/// Parses one element `["name", {attributes}, [children]]`.
+/// The attributes and children are optional.
+#[cfg(feature = "alloc")]
+pub fn parse_element(json: &str) -> Result<Element, JsonError> {
+    let mut parser = JsonParser { json, pos: 0 };
+    parser.skip_whitespace();
+    let element = parser.parse_element()?;
+    parser.expect_end()?;
+    Ok(element)
+}
+
+/// Parses one element or a list of elements and texts, like `write_fragment()` writes it.
+/// The array is an element if it starts with a string followed by an object, like the writers write the elements.
+/// So `["text"]` is the list with one text, for `["br"]` use `parse_element()`.
+#[cfg(feature = "alloc")]
+pub fn parse_nodes(json: &str) -> Result<Vec<Node>, JsonError> {
+    let mut parser = JsonParser { json, pos: 0 };
+    parser.skip_whitespace();
+    let nodes = if parser.is_element_ahead() {
+        alloc::vec![Node::Element(parser.parse_element()?)]
+    } else {
+        parser.parse_nodes()?
+    };
+    parser.expect_end()?;
+    Ok(nodes)
+}
+
+#[cfg(feature = "alloc")]
+struct JsonParser<'a> {
+    json: &'a str,
+    pos: usize,
+}
+
+#[cfg(feature = "alloc")]
+impl JsonParser<'_> {
+    fn error(&self, message: &'static str) -> JsonError {
+        JsonError { pos: self.pos, message }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.json.as_bytes().get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8, message: &'static str) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        if self.peek() != Some(byte) {
+            return Err(self.error(message));
+        }
+        self.pos += 1;
+        self.skip_whitespace();
+        Ok(())
+    }
+
+    /// true and skips the comma, false if the array or object ends here
+    fn next_item(&mut self, end: u8) -> Result<bool, JsonError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b',') => {
+                self.pos += 1;
+                self.skip_whitespace();
+                Ok(true)
+            }
+            Some(byte) if byte == end => Ok(false),
+            _ => Err(self.error("Error: Expected , or the end.")),
+        }
+    }
+
+    fn expect_end(&mut self) -> Result<(), JsonError> {
+        self.skip_whitespace();
+        if self.pos < self.json.len() {
+            return Err(self.error("Error: Expected the end of JSON."));
+        }
+        Ok(())
+    }
+
+    /// looks ahead without moving: `[` string `,` `{`
+    fn is_element_ahead(&self) -> bool {
+        let mut lookahead = JsonParser {
+            json: self.json,
+            pos: self.pos,
+        };
+        if lookahead.expect(b'[', "").is_err() || lookahead.peek() != Some(b'"') || lookahead.parse_string().is_err() {
+            return false;
+        }
+        lookahead.skip_whitespace();
+        if lookahead.peek() != Some(b',') {
+            return false;
+        }
+        lookahead.pos += 1;
+        lookahead.skip_whitespace();
+        lookahead.peek() == Some(b'{')
+    }
+
+    fn parse_element(&mut self) -> Result<Element, JsonError> {
+        self.expect(b'[', "Error: Expected [ for the element.")?;
+        if self.peek() != Some(b'"') {
+            return Err(self.error("Error: Expected the element name."));
+        }
+        let name = self.parse_string()?;
+        if name.is_empty() {
+            return Err(self.error("Error: Element name is empty."));
+        }
+        let mut element = Element::new(&name);
+        if self.next_item(b']')? {
+            self.expect(b'{', "Error: Expected { for the attributes.")?;
+            if self.peek() != Some(b'}') {
+                loop {
+                    if self.peek() != Some(b'"') {
+                        return Err(self.error("Error: Expected the attribute name."));
+                    }
+                    let name = self.parse_string()?;
+                    self.expect(b':', "Error: Expected : after the attribute name.")?;
+                    if self.peek() != Some(b'"') {
+                        return Err(self.error("Error: Attribute value must be a string."));
+                    }
+                    let value = self.parse_string()?;
+                    element.attributes.push(Attribute { name, value });
+                    if !self.next_item(b'}')? {
+                        break;
+                    }
+                }
+            }
+            self.expect(b'}', "Error: Expected } after the attributes.")?;
+            if self.next_item(b']')? {
+                element.nodes = self.parse_nodes()?;
+            }
+        }
+        self.expect(b']', "Error: Expected ] after the element.")?;
+        Ok(element)
+    }
+
+    /// array of elements and strings
+    fn parse_nodes(&mut self) -> Result<Vec<Node>, JsonError> {
+        self.expect(b'[', "Error: Expected [ for the children.")?;
+        let mut nodes = Vec::new();
+        if self.peek() != Some(b']') {
+            loop {
+                match self.peek() {
+                    Some(b'"') => nodes.push(Node::Text(self.parse_string()?)),
+                    Some(b'[') => nodes.push(Node::Element(self.parse_element()?)),
+                    _ => return Err(self.error("Error: Expected an element or a string.")),
+                }
+                if !self.next_item(b']')? {
+                    break;
+                }
+            }
+        }
+        self.expect(b']', "Error: Expected ] after the children.")?;
+        Ok(nodes)
+    }
+
+    fn parse_string(&mut self) -> Result<String, JsonError> {
+        // skip the opening quote
+        self.pos += 1;
+        let mut text = String::new();
+        loop {
+            let rest = &self.json[self.pos..];
+            let Some(special_pos) = rest.find(['"', '\\']) else {
+                return Err(self.error("Error: String is not closed."));
+            };
+            text.push_str(&rest[..special_pos]);
+            self.pos += special_pos;
+            if self.peek() == Some(b'"') {
+                self.pos += 1;
+                return Ok(text);
+            }
+            // escape sequence
+            self.pos += 1;
+            let escaped = match self.peek() {
+                Some(b'"') => '"',
+                Some(b'\\') => '\\',
+                Some(b'/') => '/',
+                Some(b'b') => '\u{8}',
+                Some(b'f') => '\u{c}',
+                Some(b'n') => '\n',
+                Some(b'r') => '\r',
+                Some(b't') => '\t',
+                Some(b'u') => {
+                    let code = self.parse_hex4()?;
+                    let code = if (0xD800..0xDC00).contains(&code) {
+                        // the high surrogate must be followed by the low surrogate
+                        if !self.json[self.pos + 1..].starts_with("\\u") {
+                            return Err(self.error("Error: Invalid surrogate pair."));
+                        }
+                        self.pos += 2;
+                        let low = self.parse_hex4()?;
+                        if !(0xDC00..0xE000).contains(&low) {
+                            return Err(self.error("Error: Invalid surrogate pair."));
+                        }
+                        0x10000 + ((code - 0xD800) << 10) + (low - 0xDC00)
+                    } else {
+                        code
+                    };
+                    // the lone low surrogate is not a char
+                    core::char::from_u32(code).ok_or_else(|| self.error("Error: Invalid unicode escape."))?
+                }
+                _ => return Err(self.error("Error: Invalid escape in string.")),
+            };
+            text.push(escaped);
+            self.pos += 1;
+        }
+    }
+
+    /// 4 hex digits after `u`, the position stays on the last digit
+    fn parse_hex4(&mut self) -> Result<u32, JsonError> {
+        let hex = self
+            .json
+            .get(self.pos + 1..self.pos + 5)
+            .ok_or_else(|| self.error("Error: Invalid unicode escape."))?;
+        let code = u32::from_str_radix(hex, 16).map_err(|_| self.error("Error: Invalid unicode escape."))?;
+        self.pos += 4;
+        Ok(code)
+    }
+}
+
+// endregion: parser
@@ -60,6 +60,151 @@
 //! or\
 //! `let x: Option<Result<Token, &str>> = reader_iterator.next();`  
 //!
+//! ## limits
+//!
+//! For untrusted input set the limits for the nesting depth, attributes per element, name length, attribute value length, text node length, comment length and total token count.\
+//! Every exceeded limit returns its own error (constants in the module `limits`) and then the reader stops.  
+//! `ReaderForMicroXml::new(input).with_limits(Limits { max_depth: 32, ..Limits::default() })`
+//!
+//! ## whitespace
+//!
+//! By default the text nodes are returned exactly as in the source, but the whitespace-only text between tags is not returned.\
+//! For data files, where whitespace is mostly noise, choose a different `WhitespacePolicy`: `Preserve`, `DropWhitespaceOnly`, `Trim` or `Collapse`.  
+//! `ReaderForMicroXml::new(input).with_whitespace(WhitespacePolicy::Trim)`
+//!
+//! ## fragment and document
+//!
+//! `ReaderForMicroXml::new()` and `new_fragment()` accept anything: top-level text, more root elements, no element at all. That is fine for html templates.\
+//! `ReaderForMicroXml::new_document()` is for data files. It returns an error if there is not exactly one root element with only whitespace and comments around it.
+//!
+//! ## html compatibility
+//!
+//! Designers write html, not MicroXml: `<br>`, `<input disabled>`, `<td colspan=2>`.\
+//! The opt-in `ReaderForMicroXml::new(input).with_html_compat(true)` knows the html void elements and returns `EndElement("")` for them like for self-closing elements. It accepts boolean attributes (empty value), unquoted and single-quoted values.\
+//! The default stays strict MicroXml.
+//!
+//! ## recovery mode
+//!
+//! Normally the reader stops after an error. Also the input that ends inside of a tag, attribute value or comment is an error. For linters there is the recovery mode (feature `alloc`).\
+//! `ReaderForMicroXml::new(input).with_recovery(true)` does not return errors. It collects them with position and severity in `diagnostics()`, resynchronizes to the next `<` or `>` and keeps returning a best-effort stream of tokens. Not matching and not closed elements are collected as warnings.
+//!
+//! ## dom and selectors
+//!
+//! With the feature `alloc` the module `dom` builds a tree of owned nodes: `dom::parse_fragment(input)`, `dom::parse_document(input)` or `dom::read_nodes(reader)` for a configured reader.\
+//! The module `selector` finds elements with css selectors: type, `#id`, `.class`, `[a]`, `[a=v]`, `[a^=v]`,..., the combinators descendant, `>`, `+`, `~` and `:first-child`, `:nth-child(an+b)`.  
+//! `Selector::parse("nav a[href]")?.select(&nodes)`
+//!
+//! ## path expressions
+//!
+//! The module `path` extracts data from the dom tree with a subset of XPath: `/` and `//` steps, `name`, `*`, `text()`, `comment()`, `@name`, `@*` and predicates `[2]`, `[last()]`, `[@a='v']`, `[name='v']`,...\
+//! `Path::parse("/feed/entry[@lang='en']/title/text()")?.evaluate_strings(&nodes)`\
+//! Parse errors have the position in the expression.
+//!
+//! ## streaming matcher
+//!
+//! Some input is too big for a tree. The `StreamMatcher` from the module `stream_match` takes simple paths like `/catalog/book/title` or `/catalog/book/@id` and returns the matches while the tokens stream past. It keeps in memory only the stack of element names.
+//!
+//! ## editing and writing the dom
+//!
+//! The dom tree is mutable: `set_attribute()`, `remove_attribute()`, `append_child()`, `insert_child()`, `remove_child()`, `replace_child()`, `detach_child()`, `wrap_child()`, `remove_comments()`, `set_text()`.\
+//! The text and attribute values in the tree are decoded (module `entities`) and they are escaped again when written with `to_string()` or `write_to(&mut impl fmt::Write)`.
+//! Unknown references like `&nbsp;` stay in the text as they are, so the document round-trips.
+//!
+//! ## handler
+//!
+//! Matching on `Token` with manual depth is repetitive. Implement the trait `MicroXmlHandler` with the callbacks `start_element(name, attributes, depth)`, `end_element(name, depth)`, `text`, `comment`, `error` and call `handler::drive(reader, &mut handler)`. The attributes are delivered together with their start tag and self-closing elements get their name in `end_element`.
+//!
+//! ## tree sink
+//!
+//! Any tree type can be built from the reader: wasm virtual-dom, arena trees,... Implement the trait `TreeSink` with `create_element`, `set_attribute`, `append_child`, `append_text`, `append_comment`, `finish` and call `tree_sink::build(reader, sink)`. The child element is appended when it is complete, so also bottom-up builders work. The crate's own dom is built with `DomSink`.
+//!
+//! ## template
+//!
+//! The module `template` renders html templates. The template is a valid MicroXml with placeholders:
+//! `<!--t=name-->` replaces the next text node, `data-t-href="name"` replaces the attribute `href`, `data-t-if="name"` (or `"!name"`) renders the element conditionally and `data-t-repeat="name"` renders it for every item.\
+//! Implement the trait `TemplateData` and call `template::render(template, &data, &mut impl fmt::Write)`. The values are escaped. The whitespace and the unknown references like `&nbsp;` of the template stay as they are.
+//!
+//! ## precompiled template
+//!
+//! `CompiledTemplate::compile(template)` reads the template once and returns a list of instructions: static chunks of escaped MicroXml, placeholder slots, loops and conditionals. `render()` never parses the template again.\
+//! `to_bytes()` serializes the compiled template, for example in build.rs. A string longer than `u32::MAX` is an error. `CompiledTemplate::from_bytes(include_bytes!(...))` loads it without copying the strings.
+//!
+//! ## formatter
+//!
+//! `fmt::format(input, FormatOptions { indent, max_width })` reformats MicroXml: every element on its own line with indentation, one attribute per line past the width, `<name/>` for elements without children. The elements with significant text (mixed content) are written as they are, to preserve the whitespace.\
+//! `fmt::check()` returns the number of the first line that is not formatted.
+//!
+//! ## minify
+//!
+//! `minify::minify(input, &mut impl fmt::Write)` streams compact MicroXml: comments removed, every run of whitespace collapsed to one space, attributes separated by one space and `<name/>` for elements without children. `minify_with()` keeps the comments accepted by a predicate, like the template placeholders. It works also without the `alloc` feature.
+//!
+//! ## canonical form
+//!
+//! `canonical::canonicalize(input, &mut impl fmt::Write)` writes a byte-exact canonical form: sorted attributes, decoded and minimally escaped text, normalized line endings, `<name></name>` for empty elements, no comments. Semantically identical inputs have the same canonical form.\
+//! `canonical_eq()` compares two inputs and `canonical_hash()` returns a stable 64-bit FNV-1a hash, usable as a cache key.
+//!
+//! ## diff
+//!
+//! `diff::diff(&old_nodes, &new_nodes)` compares two dom trees and returns a `Patch`: insertions, deletions, moves, attribute changes and text edits with index paths. The patch is also the report, one edit per line with `to_string()`, and `patch.apply(&mut old_nodes)` transforms the old tree into the new one. `describe_path()` returns a readable path like `/html[1]/body[1]/p[2]`.
+//!
+//! ## command-line tool
+//!
+//! The binary `microxml` is behind the feature `cli`:\
+//! `cargo install reader_for_microxml --features cli`\
+//! `microxml check templates/*.html`
+//!
+//! The commands are `check` (errors with position and source snippet), `tokens`, `tree`, `fmt [--check] [--indent N] [--width N]`, `minify`, `query <selector>` and `to-json`. Without file or with the file `-` it reads the standard input. The exit code is 0 for success, 1 if problems are found and 2 for wrong usage or input/output errors, so it can be used in pre-commit hooks.
+//!
+//! ## JSON
+//!
+//! The module `json` converts the MicroXml data model to JSON and back. The element is `["name", {"attribute":"value"}, [children]]` and the text is a string:\
+//! `["memo", {"lang":"en"}, ["I ", ["em", {}, ["love"]]]]`\
+//! `json::write_document(input, &mut impl fmt::Write)` and `write_fragment()` stream the tokens and work also without the `alloc` feature. `json::parse_element(json)` and `parse_nodes(json)` return the dom.
+//!
+//! ## XML
+//!
+//! The module `xml` reads full XML leniently and lowers it to MicroXml. `XmlReader::new(input)` returns the normal `Token` stream: the XML declaration, processing instructions and DOCTYPE are skipped, CDATA is returned as text (see `is_cdata()`), single-quoted attribute values are accepted and the `xmlns` declarations are dropped.\
+//! The namespace prefixes are handled by `with_namespace_policy()`: `StripPrefix` (default) makes `svg:rect` to `rect`, `DropPrefixed` drops them and `Flatten` writes `svg-rect`.\
+//! `xml::convert(input, policy, &mut impl fmt::Write)` and `to_microxml()` write the result as MicroXml.
+//!
+//! ## Lossless CST
+//!
+//! The module `cst` is for formatting and refactoring tools that need every byte. `Cst::parse(source)` returns a concrete syntax tree where every node, tag and attribute has its exact byte range and its trivia: the whitespace inside tags, `<br/>` vs `<br />` and the entities "as is". The whitespace-only text between tags is the `leading_trivia` of the next node or the `trailing_trivia` of the parent.\
+//! It accepts the same source as `ReaderForMicroXml::new()`, the end tag without a start tag is a `StrayEndTag` node.\
+//! Printing the CST with `to_string()` reproduces the source byte-for-byte.
+//!
+//! ## Format-preserving edits
+//!
+//! The module `edit` changes a hand-written source with minimal text edits and leaves the rest of the layout untouched. The elements and attributes are addressed by the slices of the reader tokens: the name of `Token::StartElement` and the value of `Token::Attribute`.\
+//! `Editor::new(source)` collects the edits `replace_attribute_value()`, `insert_attribute()`, `rename_element()` (start and end tag) and `remove_element()`, and `apply()` returns the edited source. `edit::apply(source, &edits)` applies any list of non-overlapping `TextEdit`.
+//!
+//! ## Schema validation
+//!
+//! The module `schema` validates data documents against a compact schema language, one definition per element:
+//!
+//! ```text
+//! start = library
+//! library = { @version? book* }
+//! book = { @id @year:integer? title author+ (isbn | issn)? }
+//! title = text
+//! ```
+//!
+//! `name = datatype` is an element with only text of the datatype `text`, `token`, `integer`, `decimal` or `boolean`. The datatypes ignore the leading and trailing whitespace. `name = { ... }` lists the attributes `@name`, `@name:datatype`, `@name?` and then the content model with sequences, choices `|`, groups `( )` and repetitions `? * +`. `text` inside the content model allows mixed content.\
+//! `Schema::parse(text)?.validate(input)` runs streaming over the tokens of `ReaderForMicroXml` and returns the violations with the byte position. `Validator` can be fed with the tokens one by one.
+//!
+//! ## Code generator
+//!
+//! The module `codegen` writes Rust structs and a typed parse function from the schema, to call from `build.rs`:\
+//! `let code = codegen::generate(&Schema::parse(&schema_text)?);`\
+//! Every element with `{ }` content is a struct with the fields for the attributes and the child elements: a value if required, `Option` if optional and `Vec` if repeated. The recursive fields are in a `Box`. The elements with only text are `String`, `i64`, `f64` or `bool`. The function `parse_<start>(input)` is built on `ReaderForMicroXml`, it validates the input with the schema first and returns the `Violation` with the byte position. The generated code is an example in `tests/generated/library.rs`.
+//!
+//! ## Derive macro
+//!
+//! With the feature `derive` the module `extract` has `#[derive(FromMicroXml)]` from the workspace crate `reader_for_microxml_derive`, without `syn` and `quote`:\
+//! `#[mx(attr = "id")] id: &'a str`, `#[mx(child = "title")] title: Cow<'a, str>`, `#[mx(children = "item")] items: Vec<Item<'a>>`, `#[mx(text)] text: String` or `Option<&'a str>`\
+//! `let book: Book = extract::from_str(input, "book")?;`\
+//! Attributes and children are required, or optional with `Option`. The values are `&'a str` slices of the input, `Cow`, `String`, numbers or `bool`, and the children can be other derived structs. The other attributes and elements are skipped. The parser is built on `ReaderForMicroXml` and needs only `alloc`, so it works with `no_std`.
+//!
 //! ## Tests
 //!
 //! Run 16 tests with:\
@@ -110,7 +255,7 @@
 //!                 }
 //!             },
 //!             Err(err_msg) => {
-//!                 panic!(err_msg);
+//!                 panic!("{}", err_msg);
 //!             }
 //!         }
 //!     }
@@ -155,6 +300,61 @@
 #![no_std]
 #![forbid(unsafe_code)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+pub mod canonical;
+#[cfg(feature = "alloc")]
+pub mod codegen;
+#[cfg(feature = "alloc")]
+pub mod cst;
+pub mod diagnostics;
+#[cfg(feature = "alloc")]
+pub mod diff;
+#[cfg(feature = "alloc")]
+pub mod dom;
+#[cfg(feature = "alloc")]
+pub mod edit;
+pub mod entities;
+#[cfg(feature = "alloc")]
+pub mod extract;
+#[cfg(feature = "alloc")]
+pub mod fmt;
+#[cfg(feature = "alloc")]
+pub mod handler;
+pub mod html;
+pub mod json;
+pub mod limits;
+pub mod minify;
+#[cfg(feature = "alloc")]
+pub mod path;
+#[cfg(feature = "alloc")]
+pub mod schema;
+#[cfg(feature = "alloc")]
+pub mod selector;
+#[cfg(feature = "alloc")]
+pub mod stream_match;
+#[cfg(feature = "alloc")]
+pub mod template;
+#[cfg(feature = "alloc")]
+pub mod tree_sink;
+pub mod whitespace;
+pub mod xml;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+pub use diagnostics::{Diagnostic, Severity};
+pub use limits::Limits;
+pub use whitespace::WhitespacePolicy;
+
+pub const ERR_TAG_NOT_CLOSED: &str = "Error: The input ends inside of the tag.";
+pub const ERR_ATTRIBUTE_VALUE_NOT_CLOSED: &str = "Error: The input ends inside of the attribute value.";
+pub const ERR_COMMENT_NOT_CLOSED: &str = "Error: The input ends inside of the comment.";
+pub const ERR_EMPTY_NAME: &str = "Error: The name is empty.";
+pub const ERR_NAME_HAS_LESS_THAN: &str = "Error: The name has the char < .";
+
 pub struct PosChar {
     pub pos: usize,
     pub ch: char,
@@ -176,6 +376,37 @@ pub struct ReaderForMicroXml<'a> {
     last_char: PosChar,
     /// for significant whitespace (in TextNode beginning)
     start_of_text_node_before_whitespace: usize,
+    /// limits for untrusted input
+    limits: Limits,
+    /// nesting depth of elements
+    depth: usize,
+    /// count of attributes in the current element
+    attributes_count: usize,
+    /// count of tokens read until now
+    tokens_count: usize,
+    /// what to do with whitespace in text nodes
+    whitespace: WhitespacePolicy,
+    /// fragment or document
+    mode: ReaderMode,
+    /// the document has already the root element
+    root_element_read: bool,
+    /// the document was checked at the end of file
+    end_of_document_checked: bool,
+    /// html compatibility: void elements, boolean and unquoted attributes
+    html_compat: bool,
+    /// name of the last start element, for html void elements
+    current_element: &'a str,
+    /// the tag, attribute value or comment that is read now and the error if the input ends inside of it
+    unclosed: (usize, &'static str),
+    /// recovery mode: errors are collected and the reader keeps going
+    #[cfg(feature = "alloc")]
+    recovering: bool,
+    /// errors and warnings collected in the recovery mode
+    #[cfg(feature = "alloc")]
+    diagnostics: Vec<Diagnostic>,
+    /// stack of open elements in the recovery mode, for warnings
+    #[cfg(feature = "alloc")]
+    open_elements: Vec<&'a str>,
 }
 
 /// The reader_for_microxml returns tokens.  
@@ -195,6 +426,14 @@ pub enum Token<'a> {
     Comment(&'a str),
 }
 
+/// internal enum: what the reader accepts  
+enum ReaderMode {
+    /// anything: top-level text, many root elements, ...
+    Fragment,
+    /// exactly one root element, only whitespace and comments around it
+    Document,
+}
+
 /// internal enum: Tags are strings inside delimiters `< and >  like <div> or </div>`  
 enum TagState {
     /// outside of tag  
@@ -214,47 +453,142 @@ impl PosChar {
 
 impl<'a> ReaderForMicroXml<'a> {
     /// Constructor. String is immutably borrowed here. No allocation.  
-    pub fn new(input: &str) -> ReaderForMicroXml {
+    pub fn new(input: &str) -> ReaderForMicroXml<'_> {
         // CharIndices is an iterator that returns a tuple: (pos, ch).
         // I convert this into PosChar{pos, ch} for easier coding.
         // The "byte" position for using the string slice and the character.
         // This is a complication because one utf-8 character can have more bytes.
         // And the slices are defined by "bytes position", not by "character position".
         // Very important distinction!
-
-        let mut indices = input.char_indices();
-        let mut last_char = PosChar { pos: 0, ch: ' ' };
-        if input.is_empty() {
-            // unwrap because it cannot error if the string is not empty
-            last_char.set(indices.next().unwrap());
-        }
+        // The first last_char is a dummy whitespace before the input.
+        // It is skipped like any other whitespace. So an empty input is just Eof.
         ReaderForMicroXml {
             input,
-            indices,
+            indices: input.char_indices(),
             tag_state: TagState::OutsideOfTag,
-            last_char,
+            last_char: PosChar { pos: 0, ch: ' ' },
             start_of_text_node_before_whitespace: 0,
+            limits: Limits::default(),
+            depth: 0,
+            attributes_count: 0,
+            tokens_count: 0,
+            whitespace: WhitespacePolicy::default(),
+            mode: ReaderMode::Fragment,
+            root_element_read: false,
+            end_of_document_checked: false,
+            html_compat: false,
+            current_element: "",
+            unclosed: (0, ERR_TAG_NOT_CLOSED),
+            #[cfg(feature = "alloc")]
+            recovering: false,
+            #[cfg(feature = "alloc")]
+            diagnostics: Vec::new(),
+            #[cfg(feature = "alloc")]
+            open_elements: Vec::new(),
         }
     }
 
+    /// Constructor for a fragment. The same as `new()`.  
+    /// A fragment can have top-level text, more root elements, or no element at all.  
+    /// That is fine for my html templates.  
+    pub fn new_fragment(input: &str) -> ReaderForMicroXml<'_> {
+        ReaderForMicroXml::new(input)
+    }
+
+    /// Constructor for a document. This is good for data files.  
+    /// It enforces the MicroXml document production:  
+    /// exactly one root element and only whitespace and comments before and after it.  
+    /// The whitespace-only text outside of the root element is never returned.  
+    /// The reader does not check that start and end tag have the same name.  
+    pub fn new_document(input: &str) -> ReaderForMicroXml<'_> {
+        let mut reader = ReaderForMicroXml::new(input);
+        reader.mode = ReaderMode::Document;
+        reader
+    }
+
+    /// Sets the limits for untrusted input. By default there are no limits.  
+    /// `ReaderForMicroXml::new(input).with_limits(Limits { max_depth: 32, ..Limits::default() })`  
+    pub fn with_limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Sets the whitespace policy for text nodes. The default is `DropWhitespaceOnly`.  
+    /// With `Collapse` the token is the trimmed slice, because the reader never allocates.  
+    /// The collapse is done by `tree_sink::build()` and `dom::read_nodes()` or with `whitespace().apply(txt)`.  
+    pub fn with_whitespace(mut self, whitespace: WhitespacePolicy) -> Self {
+        self.whitespace = whitespace;
+        self
+    }
+
+    /// The whitespace policy for text nodes.  
+    pub fn whitespace(&self) -> WhitespacePolicy {
+        self.whitespace
+    }
+
+    /// Opt-in html compatibility. The default is the strict MicroXml.  
+    /// Void elements like `<br>` get a synthesized `EndElement("")` like self-closing elements,  
+    /// and their end tags like `</br>` are ignored.  
+    /// Boolean attributes like `<input disabled>` get an empty value.  
+    /// Unquoted `<td colspan=2>` and single-quoted attribute values are accepted.  
+    pub fn with_html_compat(mut self, html_compat: bool) -> Self {
+        self.html_compat = html_compat;
+        self
+    }
+
+    /// Recovery mode for linters. The reader does not return errors,  
+    /// it collects them in `diagnostics()` and resynchronizes to the next `<` or `>`.  
+    /// The tokens are a best-effort stream. Also not matching end tags are collected as warnings.  
+    /// An exceeded limit is still the end of reading.  
+    /// Iterate with `by_ref()` to be able to call `diagnostics()` after.  
+    #[cfg(feature = "alloc")]
+    pub fn with_recovery(mut self, recovering: bool) -> Self {
+        self.recovering = recovering;
+        self
+    }
+
+    /// Errors and warnings collected in the recovery mode.  
+    #[cfg(feature = "alloc")]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    /// Byte position of the reader in the input.  
+    /// After an error it is the position where the error was found.  
+    pub fn pos(&self) -> usize {
+        self.last_char.pos
+    }
+
     /// Reads the next token (internal).  
     /// The internal function can understand when the Eof is in a correct position  
     /// and stops the propagation of Option None.  
-    #[allow(clippy::integer_arithmetic, clippy::nonminimal_bool)]
+    #[allow(clippy::arithmetic_side_effects, clippy::nonminimal_bool)]
     fn read_token_internal(&mut self) -> Option<Result<Token<'a>, &'static str>> {
         match &self.tag_state {
             TagState::OutsideOfTag => {
                 if self.start_of_text_node_before_whitespace == 0 {
                     self.start_of_text_node_before_whitespace = self.last_char.pos;
                 }
-                self.move_over_whitespaces()?;
+                if self.move_over_whitespaces().is_none() {
+                    // correct Eof, maybe after a whitespace-only text
+                    self.tag_state = TagState::EndOfFile;
+                    let start_pos = self.start_of_text_node_before_whitespace;
+                    return self.whitespace_only_text_node(start_pos, self.input.len());
+                }
                 // Tags can look like this:
                 // Start Tags: < xxx >,  < xxx attr="val" >,  < xxx />
                 // End Tags: </xxx>
                 // Comments: <!-- xxx -->
                 // start delimiter is <
                 if self.last_char.ch == '<' {
+                    let start_pos = self.start_of_text_node_before_whitespace;
+                    if let Some(result_token) = self.whitespace_only_text_node(start_pos, self.last_char.pos) {
+                        // the tag will be read in the next call
+                        self.start_of_text_node_before_whitespace = 0;
+                        return Some(result_token);
+                    }
                     self.tag_state = TagState::InsideOfTag;
+                    self.unclosed = (self.last_char.pos, ERR_TAG_NOT_CLOSED);
                     self.move_next_char()?;
                     self.move_over_whitespaces()?;
                     // if it is not comment or end tag, must be the element name
@@ -282,22 +616,23 @@ impl<'a> ReaderForMicroXml<'a> {
                 // if it is not self-closing or > then must be an attribute
                 if self.last_char.ch == '>' {
                     // here must be the end of start tag >
-                    self.move_next_char()?;
-                    self.tag_state = TagState::OutsideOfTag;
-                    self.start_of_text_node_before_whitespace = 0;
-                    // recursive calling
-                    return self.read_token_internal();
+                    self.move_after_end_of_tag();
+                    if self.html_compat && html::is_void_element(self.current_element) {
+                        // html void element is like a self-closing element
+                        Some(Ok(Token::EndElement("")))
+                    } else {
+                        // recursive calling
+                        self.read_token_internal()
+                    }
                 } else if self.last_char.ch == '/' {
                     // self-closing element
                     self.move_next_char()?; // to >
                     self.move_over_whitespaces()?;
                     if self.last_char.ch != '>' {
-                        return Some(Err("Error: Tag has / but not />"));
+                        Some(Err("Error: Tag has / but not />"))
                     } else {
-                        self.move_next_char()?;
-                        self.tag_state = TagState::OutsideOfTag;
-                        self.start_of_text_node_before_whitespace = 0;
-                        return Some(Ok(Token::EndElement("")));
+                        self.move_after_end_of_tag();
+                        Some(Ok(Token::EndElement("")))
                     }
                 } else {
                     // attribute
@@ -320,23 +655,24 @@ impl<'a> ReaderForMicroXml<'a> {
         let end_pos;
         loop {
             // read until delimiter space, / or >
-            if self.last_char.ch.is_whitespace()
-                || self.last_char.ch == '/'
-                || self.last_char.ch == '>'
-            {
+            if self.last_char.ch.is_whitespace() || self.last_char.ch == '/' || self.last_char.ch == '>' {
                 end_pos = self.last_char.pos;
                 break;
+            } else if self.last_char.ch == '<' {
+                return Some(Err(ERR_NAME_HAS_LESS_THAN));
             } else {
                 self.move_next_char()?;
             }
         }
+        if start_pos == end_pos {
+            return Some(Err(ERR_EMPTY_NAME));
+        }
         self.move_over_whitespaces()?;
         self.tag_state = TagState::InsideOfTag;
 
         // unwrap because I am confident that start_pos or end_pos are correct
-        return Some(Ok(Token::StartElement(
-            self.input.get(start_pos..end_pos).unwrap(),
-        )));
+        self.current_element = self.input.get(start_pos..end_pos).unwrap();
+        Some(Ok(Token::StartElement(self.current_element)))
     }
 
     /// Reads the attribute name and value.  
@@ -347,13 +683,22 @@ impl<'a> ReaderForMicroXml<'a> {
         let end_pos;
         loop {
             // delimiters are whitespace or =
-            if self.last_char.ch.is_whitespace() || self.last_char.ch == '=' {
+            // html boolean attributes end also with > or /
+            if self.last_char.ch.is_whitespace()
+                || self.last_char.ch == '='
+                || (self.html_compat && (self.last_char.ch == '>' || self.last_char.ch == '/'))
+            {
                 end_pos = self.last_char.pos;
                 break;
+            } else if self.last_char.ch == '<' {
+                return Some(Err(ERR_NAME_HAS_LESS_THAN));
             } else {
                 self.move_next_char()?;
             }
         }
+        if start_pos == end_pos {
+            return Some(Err(ERR_EMPTY_NAME));
+        }
         // unwrap because I am confident that start_pos or end_pos are correct
         let attr_name = self.input.get(start_pos..end_pos).unwrap();
 
@@ -361,10 +706,18 @@ impl<'a> ReaderForMicroXml<'a> {
         self.move_over_whitespaces()?;
         if self.last_char.ch == '=' {
             self.move_next_char()?;
+        } else if self.html_compat {
+            // html boolean attribute has an empty value
+            return Some(Ok(Token::Attribute(attr_name, "")));
         }
         self.move_over_whitespaces()?;
-        if self.last_char.ch == '"' {
+        let quote = self.last_char.ch;
+        let tag_unclosed = self.unclosed;
+        if quote == '"' || (self.html_compat && quote == '\'') {
+            self.unclosed = (self.last_char.pos, ERR_ATTRIBUTE_VALUE_NOT_CLOSED);
             self.move_next_char()?;
+        } else if self.html_compat {
+            return self.read_unquoted_attribute_value(attr_name);
         } else {
             return Some(Err("Error: Attribute does not have the char = ."));
         }
@@ -373,9 +726,10 @@ impl<'a> ReaderForMicroXml<'a> {
         let start_pos = self.last_char.pos;
         let end_pos;
         loop {
-            // end delimiter is "
-            if self.last_char.ch == '"' {
+            // end delimiter is the same quote
+            if self.last_char.ch == quote {
                 end_pos = self.last_char.pos;
+                self.unclosed = tag_unclosed;
                 self.move_next_char()?;
                 break;
             } else {
@@ -389,6 +743,25 @@ impl<'a> ReaderForMicroXml<'a> {
         Some(Ok(Token::Attribute(attr_name, attr_value)))
     }
 
+    /// Reads html unquoted attribute value like `<td colspan=2>`.  
+    /// It ends with whitespace or >.  
+    fn read_unquoted_attribute_value(&mut self, attr_name: &'a str) -> Option<Result<Token<'a>, &'static str>> {
+        let start_pos = self.last_char.pos;
+        let end_pos;
+        loop {
+            if self.last_char.ch.is_whitespace() || self.last_char.ch == '>' {
+                end_pos = self.last_char.pos;
+                break;
+            } else {
+                self.move_next_char()?;
+            }
+        }
+        self.move_over_whitespaces()?;
+        // unwrap because I am confident that start_pos or end_pos are correct
+        let attr_value = self.input.get(start_pos..end_pos).unwrap();
+        Some(Ok(Token::Attribute(attr_name, attr_value)))
+    }
+
     /// reads end element  
     fn read_end_element(&mut self) -> Option<Result<Token<'a>, &'static str>> {
         // end tag for element  </ xxx >
@@ -402,30 +775,34 @@ impl<'a> ReaderForMicroXml<'a> {
             if self.last_char.ch.is_whitespace() || self.last_char.ch == '>' {
                 end_pos = self.last_char.pos;
                 break;
+            } else if self.last_char.ch == '<' {
+                return Some(Err(ERR_NAME_HAS_LESS_THAN));
             } else {
                 self.move_next_char()?;
             }
         }
+        if start_pos == end_pos {
+            return Some(Err(ERR_EMPTY_NAME));
+        }
         self.move_over_whitespaces()?;
         if self.last_char.ch == '>' {
             // after the End element is possible to have a correct Eof
+            // the whitespaces after it are skipped OutsideOfTag
             if let Some(()) = self.move_next_char() {
-                //dbg!(self.last_char.pos);
                 self.start_of_text_node_before_whitespace = self.last_char.pos;
-                if let Some(()) = self.move_over_whitespaces() {
-                    self.tag_state = TagState::OutsideOfTag;
-                } else {
-                    self.tag_state = TagState::EndOfFile;
-                }
+                self.tag_state = TagState::OutsideOfTag;
             } else {
                 self.tag_state = TagState::EndOfFile;
             }
-            return Some(Ok(Token::EndElement(
-                // unwrap because I am confident that start_pos or end_pos are correct
-                self.input.get(start_pos..end_pos).unwrap(),
-            )));
+            // unwrap because I am confident that start_pos or end_pos are correct
+            let name = self.input.get(start_pos..end_pos).unwrap();
+            if self.html_compat && html::is_void_element(name) {
+                // the void element has already the synthesized EndElement
+                return self.read_token_internal();
+            }
+            Some(Ok(Token::EndElement(name)))
         } else {
-            return Some(Err("End Element does not have > ."));
+            Some(Err("End Element does not have > ."))
         }
     }
 
@@ -450,20 +827,31 @@ impl<'a> ReaderForMicroXml<'a> {
             if self.last_char.ch == '<' {
                 self.tag_state = TagState::OutsideOfTag;
                 break;
-            } else {
-                if self.move_next_char().is_none() {
-                    end_pos += 1;
-                    self.tag_state = TagState::EndOfFile;
-                    break;
-                }
+            } else if self.move_next_char().is_none() {
+                // the text node ends with the input.
+                // The last char can be multi-byte, so not just +1.
+                end_pos = self.input.len();
+                self.tag_state = TagState::EndOfFile;
+                break;
             }
         }
         // unwrap because I am confident that start_pos or end_pos are correct
+        let txt = self.input.get(start_pos..end_pos).unwrap();
+        match self.whitespace.apply_borrowed(txt) {
+            Some(txt) => Some(Ok(Token::TextNode(txt))),
+            // the text node is dropped, read the next token
+            None => self.read_token_internal(),
+        }
+    }
 
-        //dbg!(end_pos);
-        return Some(Ok(Token::TextNode(
-            self.input.get(start_pos..end_pos).unwrap(),
-        )));
+    /// Returns the whitespace-only text between tags only if the policy preserves it.  
+    fn whitespace_only_text_node(&self, start_pos: usize, end_pos: usize) -> Option<Result<Token<'a>, &'static str>> {
+        if start_pos < end_pos && self.whitespace == WhitespacePolicy::Preserve {
+            // unwrap because I am confident that start_pos or end_pos are correct
+            Some(Ok(Token::TextNode(self.input.get(start_pos..end_pos).unwrap())))
+        } else {
+            None
+        }
     }
 
     /// Comments are not data for MicroXml standard,  
@@ -472,6 +860,7 @@ impl<'a> ReaderForMicroXml<'a> {
     fn read_comment(&mut self) -> Option<Result<Token<'a>, &'static str>> {
         // comments looks like this <!-- xxx -->
         // we should be now at the second character  <!
+        self.unclosed = (self.unclosed.0, ERR_COMMENT_NOT_CLOSED);
         self.move_next_char()?; // skip char !
         self.move_next_char()?; // skip char -
         self.move_next_char()?; // skip char -
@@ -485,7 +874,6 @@ impl<'a> ReaderForMicroXml<'a> {
             // end delimiter -->
             if ch1 == '-' && ch2 == '-' && ch3 == '>' {
                 end_pos = self.last_char.pos - 2;
-                self.move_next_char()?;
                 break;
             } else {
                 ch1 = ch2;
@@ -494,12 +882,187 @@ impl<'a> ReaderForMicroXml<'a> {
             }
         }
         // it is possible to have a comment in between 2 text nodes
-        self.start_of_text_node_before_whitespace = 0;
-        self.tag_state = TagState::OutsideOfTag;
+        // and after the comment is possible to have a correct Eof
+        self.move_after_end_of_tag();
         // unwrap because I am confident that start_pos or end_pos are correct
-        return Some(Ok(Token::Comment(
-            self.input.get(start_pos..end_pos).unwrap(),
-        )));
+        Some(Ok(Token::Comment(self.input.get(start_pos..end_pos).unwrap())))
+    }
+
+    /// Checks the token against the document production (only in document mode).  
+    /// It must be called before check_limits(), because that changes the depth.  
+    /// Returns Ok(false) if the token must be skipped.  
+    fn check_document(&mut self, token: &Token<'a>) -> Result<bool, &'static str> {
+        if let ReaderMode::Fragment = self.mode {
+            return Ok(true);
+        }
+        match token {
+            Token::StartElement(_name) if self.depth == 0 => {
+                if self.root_element_read {
+                    return Err("Error: Document has more than one root element.");
+                }
+                self.root_element_read = true;
+            }
+            Token::EndElement(_name) if self.depth == 0 => {
+                return Err("Error: End element without start element.");
+            }
+            Token::TextNode(txt) if self.depth == 0 => {
+                if !txt.chars().all(char::is_whitespace) {
+                    return Err("Error: Document has text outside of the root element.");
+                }
+                return Ok(false);
+            }
+            _ => {}
+        }
+        Ok(true)
+    }
+
+    /// At the end of file the document must have one closed root element.  
+    /// Returns the error only once, after that it is just None.  
+    fn check_end_of_document(&mut self) -> Option<Result<Token<'a>, &'static str>> {
+        if let ReaderMode::Fragment = self.mode {
+            return None;
+        }
+        if self.end_of_document_checked {
+            return None;
+        }
+        self.end_of_document_checked = true;
+        if !self.root_element_read {
+            Some(Err("Error: Document has no root element."))
+        } else if self.depth > 0 {
+            Some(Err("Error: Document ends before the root element is closed."))
+        } else {
+            None
+        }
+    }
+
+    /// Stores the diagnostic if the reader is in the recovery mode.  
+    /// Returns false if it is not in the recovery mode.  
+    fn recover(&mut self, pos: usize, severity: Severity, message: &'static str) -> bool {
+        #[cfg(feature = "alloc")]
+        if self.recovering {
+            self.diagnostics.push(Diagnostic { pos, severity, message });
+            return true;
+        }
+        let _ = (pos, severity, message);
+        false
+    }
+
+    /// After an error skip to the next `<` or over the next `>`.  
+    /// Then the reader is again outside of tag.  
+    fn resynchronize(&mut self) {
+        self.start_of_text_node_before_whitespace = 0;
+        loop {
+            if self.last_char.ch == '<' {
+                self.tag_state = TagState::OutsideOfTag;
+                return;
+            }
+            let is_end_of_tag = self.last_char.ch == '>';
+            if self.move_next_char().is_none() {
+                self.tag_state = TagState::EndOfFile;
+                return;
+            }
+            if is_end_of_tag {
+                self.tag_state = TagState::OutsideOfTag;
+                return;
+            }
+        }
+    }
+
+    /// Byte position of a token slice in the input.  
+    #[cfg(feature = "alloc")]
+    fn position_of(&self, slice: &str) -> usize {
+        (slice.as_ptr() as usize).saturating_sub(self.input.as_ptr() as usize)
+    }
+
+    /// In the recovery mode the open elements are checked against the end elements.  
+    #[cfg(feature = "alloc")]
+    fn check_nesting(&mut self, token: &Token<'a>) {
+        match token {
+            Token::StartElement(name) => self.open_elements.push(name),
+            Token::EndElement(name) => {
+                if let Some(start_name) = self.open_elements.pop() {
+                    // the self-closing element has an empty name
+                    if !name.is_empty() && *name != start_name {
+                        let pos = self.position_of(name);
+                        self.recover(pos, Severity::Warning, diagnostics::WARN_END_ELEMENT_NOT_MATCHING);
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The end of input checks the document and the not closed elements.  
+    fn end_of_input(&mut self) -> Option<Result<Token<'a>, &'static str>> {
+        let result_token = self.check_end_of_document();
+        #[cfg(feature = "alloc")]
+        if self.recovering {
+            if let Some(Err(err_msg)) = result_token {
+                self.recover(self.input.len(), Severity::Error, err_msg);
+            }
+            while let Some(name) = self.open_elements.pop() {
+                let pos = self.position_of(name);
+                self.recover(pos, Severity::Warning, diagnostics::WARN_ELEMENT_NOT_CLOSED);
+            }
+            return None;
+        }
+        result_token
+    }
+
+    /// Stops reading after an error that cannot be recovered.  
+    fn stop(&mut self) {
+        self.tag_state = TagState::EndOfFile;
+        self.end_of_document_checked = true;
+    }
+
+    /// Checks the token against the limits.  
+    /// Counts also the nesting depth and attributes of the current element.  
+    fn check_limits(&mut self, token: &Token<'a>) -> Result<(), &'static str> {
+        self.tokens_count = self.tokens_count.saturating_add(1);
+        if self.tokens_count > self.limits.max_tokens {
+            return Err(limits::ERR_MAX_TOKENS);
+        }
+        match token {
+            Token::StartElement(name) => {
+                self.depth = self.depth.saturating_add(1);
+                self.attributes_count = 0;
+                if self.depth > self.limits.max_depth {
+                    return Err(limits::ERR_MAX_DEPTH);
+                }
+                if name.len() > self.limits.max_name_len {
+                    return Err(limits::ERR_MAX_NAME_LEN);
+                }
+            }
+            Token::EndElement(name) => {
+                self.depth = self.depth.saturating_sub(1);
+                if name.len() > self.limits.max_name_len {
+                    return Err(limits::ERR_MAX_NAME_LEN);
+                }
+            }
+            Token::Attribute(name, value) => {
+                self.attributes_count = self.attributes_count.saturating_add(1);
+                if self.attributes_count > self.limits.max_attributes {
+                    return Err(limits::ERR_MAX_ATTRIBUTES);
+                }
+                if name.len() > self.limits.max_name_len {
+                    return Err(limits::ERR_MAX_NAME_LEN);
+                }
+                if value.len() > self.limits.max_attribute_value_len {
+                    return Err(limits::ERR_MAX_ATTRIBUTE_VALUE_LEN);
+                }
+            }
+            Token::TextNode(txt) => {
+                if txt.len() > self.limits.max_text_len {
+                    return Err(limits::ERR_MAX_TEXT_LEN);
+                }
+            }
+            Token::Comment(txt) => {
+                if txt.len() > self.limits.max_comment_len {
+                    return Err(limits::ERR_MAX_COMMENT_LEN);
+                }
+            }
+        }
+        Ok(())
     }
 
     // region: methods for iterator
@@ -523,6 +1086,17 @@ impl<'a> ReaderForMicroXml<'a> {
         Some(())
     }
 
+    /// Moves over the > at the end of the tag.  
+    /// After the end of tag is possible to have a correct Eof.  
+    fn move_after_end_of_tag(&mut self) {
+        self.start_of_text_node_before_whitespace = 0;
+        if let Some(()) = self.move_next_char() {
+            self.tag_state = TagState::OutsideOfTag;
+        } else {
+            self.tag_state = TagState::EndOfFile;
+        }
+    }
+
     /// Skips all whitespaces if there is any  
     /// and returns the last_char when it is not whitespace.  
     /// saves the whitespace beginning position, because
@@ -543,8 +1117,59 @@ impl<'a> ReaderForMicroXml<'a> {
 impl<'a> Iterator for ReaderForMicroXml<'a> {
     type Item = Result<Token<'a>, &'static str>;
     /// Reads the next token: StartElement, Attribute, Text, EndElement  
+    /// After an error the reader stops and returns None, also when the input ends inside of a tag or comment.  
+    /// In the recovery mode the errors are collected and the reader keeps going.  
     fn next(&mut self) -> Option<Result<Token<'a>, &'static str>> {
-        // return
-        self.read_token_internal()
+        loop {
+            let token = match self.read_token_internal() {
+                None if matches!(self.tag_state, TagState::EndOfFile) => return self.end_of_input(),
+                None => {
+                    // the input ends inside of a tag, attribute value or comment
+                    self.tag_state = TagState::EndOfFile;
+                    let (pos, err_msg) = self.unclosed;
+                    if self.recover(pos, Severity::Error, err_msg) {
+                        return self.end_of_input();
+                    }
+                    self.stop();
+                    return Some(Err(err_msg));
+                }
+                Some(Ok(token)) => token,
+                Some(Err(err_msg)) => {
+                    if self.recover(self.last_char.pos, Severity::Error, err_msg) {
+                        self.resynchronize();
+                        continue;
+                    }
+                    // without recovery the position of the error is not a place to continue
+                    self.stop();
+                    return Some(Err(err_msg));
+                }
+            };
+            match self.check_document(&token) {
+                Ok(true) => {}
+                // the whitespace outside of the root element is skipped
+                Ok(false) => continue,
+                Err(err_msg) => {
+                    // in the recovery mode the token is returned anyway
+                    let pos = self.pos();
+                    if !self.recover(pos, Severity::Error, err_msg) {
+                        self.stop();
+                        return Some(Err(err_msg));
+                    }
+                }
+            }
+            if let Err(err_msg) = self.check_limits(&token) {
+                self.stop();
+                if self.recover(self.last_char.pos, Severity::Error, err_msg) {
+                    return None;
+                }
+                return Some(Err(err_msg));
+            }
+            #[cfg(feature = "alloc")]
+            if self.recovering {
+                self.check_nesting(&token);
+            }
+            // return
+            return Some(Ok(token));
+        }
     }
 }
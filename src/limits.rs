@@ -0,0 +1,60 @@
+//! limits.rs - bounds for the work done on one document
+
+/// Limits for the reader, when the input comes from an untrusted source.
+/// Every limit is checked on the token just read.
+/// The first exceeded limit returns its own error and then the reader stops.
+/// The default is no limit at all, that is the same as before the limits existed.
+/// Lengths are in bytes, because the tokens are slices of the utf-8 input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// maximum nesting depth of elements
+    pub max_depth: usize,
+    /// maximum number of attributes in one element
+    pub max_attributes: usize,
+    /// maximum length of element and attribute names
+    pub max_name_len: usize,
+    /// maximum length of attribute value
+    pub max_attribute_value_len: usize,
+    /// maximum length of text node
+    pub max_text_len: usize,
+    /// maximum length of comment
+    pub max_comment_len: usize,
+    /// maximum number of tokens in the document
+    pub max_tokens: usize,
+}
+
+/// Error returned when the nesting of elements is deeper than `max_depth`.
+pub const ERR_MAX_DEPTH: &str = "Error: Limit max_depth exceeded.";
+/// Error returned when one element has more attributes than `max_attributes`.
+pub const ERR_MAX_ATTRIBUTES: &str = "Error: Limit max_attributes exceeded.";
+/// Error returned when an element or attribute name is longer than `max_name_len`.
+pub const ERR_MAX_NAME_LEN: &str = "Error: Limit max_name_len exceeded.";
+/// Error returned when an attribute value is longer than `max_attribute_value_len`.
+pub const ERR_MAX_ATTRIBUTE_VALUE_LEN: &str = "Error: Limit max_attribute_value_len exceeded.";
+/// Error returned when a text node is longer than `max_text_len`.
+pub const ERR_MAX_TEXT_LEN: &str = "Error: Limit max_text_len exceeded.";
+/// Error returned when a comment is longer than `max_comment_len`.
+pub const ERR_MAX_COMMENT_LEN: &str = "Error: Limit max_comment_len exceeded.";
+/// Error returned when the document has more tokens than `max_tokens`.
+pub const ERR_MAX_TOKENS: &str = "Error: Limit max_tokens exceeded.";
+
+impl Limits {
+    /// No limits. The same as `Limits::default()`.
+    pub const fn unlimited() -> Limits {
+        Limits {
+            max_depth: usize::MAX,
+            max_attributes: usize::MAX,
+            max_name_len: usize::MAX,
+            max_attribute_value_len: usize::MAX,
+            max_text_len: usize::MAX,
+            max_comment_len: usize::MAX,
+            max_tokens: usize::MAX,
+        }
+    }
+}
+
+impl Default for Limits {
+    fn default() -> Limits {
+        Limits::unlimited()
+    }
+}
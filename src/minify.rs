@@ -0,0 +1,107 @@
+//! minify.rs - compact MicroXml for shipping to the browser
+//!
+//! Streams the tokens of the reader into any writer, without building a tree.
+//! Comments are removed, every run of whitespace in the text is one space,
+//! attributes are separated by one space and elements without children are written as `<name/>`.
+//! The whitespace between inline elements like `<b>a</b> <i>b</i>` is significant in html, so it is kept as one space.
+//! Only the whitespace at the start and at the end of the input is dropped.
+//! The attribute values and the entities are written "as is".
+//! The minified output is parsed to the same tokens, only without the removed comments and with the collapsed whitespace.
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::fmt;
+
+use crate::{ReaderForMicroXml, Token, WhitespacePolicy};
+
+pub const ERR_WRITE: &str = "Error: Minify write failed.";
+
+/// Minifies the input and removes all comments.
+pub fn minify<W: fmt::Write>(input: &str, w: &mut W) -> Result<(), &'static str> {
+    minify_with(input, |_comment| false, w)
+}
+
+/// Minifies the input and keeps only the comments where `keep_comment` returns true,
+/// for example the template placeholders `<!--t=name-->`.
+pub fn minify_with<F, W>(input: &str, keep_comment: F, w: &mut W) -> Result<(), &'static str>
+where
+    F: Fn(&str) -> bool,
+    W: fmt::Write,
+{
+    // the start tag is closed when the next token comes: `/>` if it is the end element, else `>`
+    let mut start_tag_open = false;
+    let mut space = Space::default();
+    for result_token in ReaderForMicroXml::new_fragment(input).with_whitespace(WhitespacePolicy::Preserve) {
+        let token = result_token?;
+        if start_tag_open {
+            match token {
+                Token::Attribute(..) => {}
+                Token::EndElement(_name) => {
+                    start_tag_open = false;
+                    w.write_str("/>").map_err(|_| ERR_WRITE)?;
+                    continue;
+                }
+                _ => {
+                    start_tag_open = false;
+                    w.write_char('>').map_err(|_| ERR_WRITE)?;
+                }
+            }
+        }
+        match token {
+            Token::StartElement(name) => {
+                start_tag_open = true;
+                space.before_output(w).and_then(|_| write!(w, "<{}", name))
+            }
+            Token::Attribute(name, value) => write!(w, " {}=\"{}\"", name, value),
+            Token::TextNode(txt) => write_collapsed(txt, &mut space, w),
+            Token::Comment(txt) if keep_comment(txt) => space.before_output(w).and_then(|_| write!(w, "<!--{}-->", txt)),
+            Token::Comment(_txt) => Ok(()),
+            Token::EndElement(name) => space.before_output(w).and_then(|_| write!(w, "</{}>", name)),
+        }
+        .map_err(|_| ERR_WRITE)?;
+    }
+    if start_tag_open {
+        w.write_str("/>").map_err(|_| ERR_WRITE)?;
+    }
+    Ok(())
+}
+
+/// The run of whitespace is written as one space only before the next output,
+/// so it joins the runs around the removed comments and it is dropped at the start and at the end of the input.
+#[derive(Default)]
+struct Space {
+    pending: bool,
+    has_output: bool,
+}
+
+impl Space {
+    fn before_output<W: fmt::Write>(&mut self, w: &mut W) -> fmt::Result {
+        if self.pending && self.has_output {
+            w.write_char(' ')?;
+        }
+        self.pending = false;
+        self.has_output = true;
+        Ok(())
+    }
+}
+
+fn write_collapsed<W: fmt::Write>(txt: &str, space: &mut Space, w: &mut W) -> fmt::Result {
+    for (i, word) in txt.split(char::is_whitespace).enumerate() {
+        if i > 0 {
+            space.pending = true;
+        }
+        if !word.is_empty() {
+            space.before_output(w)?;
+            w.write_str(word)?;
+        }
+    }
+    Ok(())
+}
+
+/// Minifies the input into a new string and removes all comments.
+#[cfg(feature = "alloc")]
+pub fn minify_to_string(input: &str) -> Result<String, &'static str> {
+    let mut text = String::new();
+    minify(input, &mut text)?;
+    Ok(text)
+}
@@ -0,0 +1,521 @@
+//! path.rs - subset of XPath to extract data from the dom tree
+//!
+//! Supported:
+//! steps separated by `/` (child) and `//` (descendant),
+//! node tests `name`, `*`, `text()`, `comment()`, `node()`, `.`, `@name`, `@*`,
+//! predicates `[2]`, `[last()]`, `[@a]`, `[@a='v']`, `[@a!='v']`, `[name]`, `[name='v']`, `[text()='v']`.
+//! The path is always evaluated from the context of the given nodes,
+//! so `/feed/entry` and `feed/entry` are the same.
+
+use alloc::{
+    borrow::ToOwned,
+    collections::BTreeSet,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::fmt;
+
+use crate::dom::{Element, Node};
+
+/// Compiled path expression. Parse once and use it many times.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Path {
+    steps: Vec<Step>,
+}
+
+/// Error with the position in the path expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PathError {
+    /// byte position in the path expression
+    pub pos: usize,
+    pub message: &'static str,
+}
+
+/// One result of the path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathItem<'t> {
+    Element(&'t Element),
+    Text(&'t str),
+    Comment(&'t str),
+    /// name and value
+    Attribute(&'t str, &'t str),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Step {
+    /// after `//` the step looks also in all descendants
+    descendant: bool,
+    test: NodeTest,
+    predicates: Vec<Predicate>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum NodeTest {
+    /// `.`
+    SelfNode,
+    Name(String),
+    AnyElement,
+    Text,
+    Comment,
+    AnyNode,
+    Attribute(String),
+    AnyAttribute,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Predicate {
+    /// 1-based position
+    Position(usize),
+    Last,
+    AttributeExists(String),
+    /// name, value, is negated
+    AttributeEquals(String, String, bool),
+    ChildExists(String),
+    /// name, value, is negated
+    ChildEquals(String, String, bool),
+    /// value, is negated
+    TextEquals(String, bool),
+}
+
+/// Context for the next step: the nodes of the root or the element.
+#[derive(Clone, Copy)]
+enum Context<'t> {
+    Root(&'t [Node]),
+    Item(PathItem<'t>),
+}
+
+/// Identity of a node for removing duplicates and for the document order:
+/// the indexes of the nodes from the root and the index of the attribute.
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+struct NodeKey {
+    indexes: Vec<usize>,
+    attribute: Option<usize>,
+}
+
+impl fmt::Display for PathError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.pos)
+    }
+}
+
+impl<'t> PathItem<'t> {
+    /// String value: the text of an element is the concatenated text of all descendants.
+    pub fn string_value(&self) -> String {
+        match self {
+            PathItem::Element(element) => {
+                let mut text = String::new();
+                push_text(element, &mut text);
+                text
+            }
+            PathItem::Text(txt) | PathItem::Comment(txt) => (*txt).to_owned(),
+            PathItem::Attribute(_name, value) => (*value).to_owned(),
+        }
+    }
+
+    /// The element if the item is an element.
+    pub fn as_element(&self) -> Option<&'t Element> {
+        match self {
+            PathItem::Element(element) => Some(element),
+            _ => None,
+        }
+    }
+}
+
+impl Path {
+    /// Parses the path expression.
+    pub fn parse(expression: &str) -> Result<Path, PathError> {
+        let mut parser = Parser { expression, pos: 0 };
+        let mut steps = Vec::new();
+        let mut descendant = false;
+        if parser.eat("//") {
+            descendant = true;
+        } else {
+            parser.eat("/");
+        }
+        loop {
+            let mut step = parser.parse_step()?;
+            step.descendant = descendant;
+            steps.push(step);
+            if parser.pos == expression.len() {
+                break;
+            } else if parser.eat("//") {
+                descendant = true;
+            } else if parser.eat("/") {
+                descendant = false;
+            } else {
+                return Err(parser.error("Error: Expected / ."));
+            }
+        }
+        Ok(Path { steps })
+    }
+
+    /// Evaluates the path with the nodes as children of the context.
+    /// The results are without duplicates, in document order: the element, its attributes and then its children.
+    pub fn evaluate<'t>(&self, nodes: &'t [Node]) -> Vec<PathItem<'t>> {
+        let mut contexts = vec![(Context::Root(nodes), NodeKey::default())];
+        for step in self.steps.iter() {
+            contexts = step.evaluate(&contexts);
+        }
+        // the order of the keys is the document order
+        contexts.sort_by(|(_context, key), (_other_context, other_key)| key.cmp(other_key));
+        contexts
+            .into_iter()
+            .filter_map(|(context, _key)| match context {
+                Context::Item(item) => Some(item),
+                Context::Root(_) => None,
+            })
+            .collect()
+    }
+
+    /// The first result in document order.
+    pub fn evaluate_first<'t>(&self, nodes: &'t [Node]) -> Option<PathItem<'t>> {
+        self.evaluate(nodes).into_iter().next()
+    }
+
+    /// The string values of all results.
+    pub fn evaluate_strings(&self, nodes: &[Node]) -> Vec<String> {
+        self.evaluate(nodes).iter().map(PathItem::string_value).collect()
+    }
+}
+
+impl core::str::FromStr for Path {
+    type Err = PathError;
+    fn from_str(expression: &str) -> Result<Path, PathError> {
+        Path::parse(expression)
+    }
+}
+
+impl<'t> Context<'t> {
+    /// Children nodes, only the root and elements have them.
+    fn nodes(&self) -> &'t [Node] {
+        match self {
+            Context::Root(nodes) => nodes,
+            Context::Item(PathItem::Element(element)) => &element.nodes,
+            Context::Item(_) => &[],
+        }
+    }
+
+    /// The context itself and all descendant elements, in document order.
+    fn push_descendants_or_self(self, key: &NodeKey, result: &mut Vec<(Context<'t>, NodeKey)>) {
+        result.push((self, key.clone()));
+        for (index, node) in self.nodes().iter().enumerate() {
+            if let Node::Element(element) = node {
+                Context::Item(PathItem::Element(element)).push_descendants_or_self(&key.child(index), result);
+            }
+        }
+    }
+}
+
+impl NodeKey {
+    fn child(&self, index: usize) -> NodeKey {
+        let mut indexes = self.indexes.clone();
+        indexes.push(index);
+        NodeKey { indexes, attribute: None }
+    }
+
+    fn attribute(&self, index: usize) -> NodeKey {
+        NodeKey {
+            indexes: self.indexes.clone(),
+            attribute: Some(index),
+        }
+    }
+}
+
+impl Step {
+    fn evaluate<'t>(&self, contexts: &[(Context<'t>, NodeKey)]) -> Vec<(Context<'t>, NodeKey)> {
+        let mut expanded = Vec::new();
+        if self.descendant {
+            for (context, key) in contexts.iter() {
+                context.push_descendants_or_self(key, &mut expanded);
+            }
+        } else {
+            expanded.extend_from_slice(contexts);
+        }
+        let mut result = Vec::new();
+        let mut seen = BTreeSet::new();
+        for (context, key) in expanded.iter() {
+            let mut selected = self.select(*context, key);
+            for predicate in self.predicates.iter() {
+                let count = selected.len();
+                selected = selected
+                    .into_iter()
+                    .enumerate()
+                    .filter(|(index, (item, _key))| predicate.matches(item, index + 1, count))
+                    .map(|(_index, item_key)| item_key)
+                    .collect();
+            }
+            for (item, item_key) in selected {
+                if !seen.contains(&item_key) {
+                    seen.insert(item_key.clone());
+                    result.push((Context::Item(item), item_key));
+                }
+            }
+        }
+        result
+    }
+
+    /// The items selected by the node test from one context, with their keys.
+    fn select<'t>(&self, context: Context<'t>, key: &NodeKey) -> Vec<(PathItem<'t>, NodeKey)> {
+        let mut selected = Vec::new();
+        match &self.test {
+            NodeTest::SelfNode => {
+                if let Context::Item(item) = context {
+                    selected.push((item, key.clone()));
+                }
+            }
+            NodeTest::Attribute(name) => {
+                if let Context::Item(PathItem::Element(element)) = context {
+                    if let Some(index) = element.attributes.iter().position(|attribute| attribute.name == *name) {
+                        let attribute = &element.attributes[index];
+                        selected.push((PathItem::Attribute(&attribute.name, &attribute.value), key.attribute(index)));
+                    }
+                }
+            }
+            NodeTest::AnyAttribute => {
+                if let Context::Item(PathItem::Element(element)) = context {
+                    for (index, attribute) in element.attributes.iter().enumerate() {
+                        selected.push((PathItem::Attribute(&attribute.name, &attribute.value), key.attribute(index)));
+                    }
+                }
+            }
+            _ => {
+                for (index, node) in context.nodes().iter().enumerate() {
+                    let item = match node {
+                        Node::Element(element) => PathItem::Element(element),
+                        Node::Text(txt) => PathItem::Text(txt),
+                        Node::Comment(txt) => PathItem::Comment(txt),
+                    };
+                    let is_selected = match (&self.test, node) {
+                        (NodeTest::Name(name), Node::Element(element)) => element.name == *name,
+                        (NodeTest::AnyElement, Node::Element(_)) => true,
+                        (NodeTest::Text, Node::Text(_)) => true,
+                        (NodeTest::Comment, Node::Comment(_)) => true,
+                        (NodeTest::AnyNode, _) => true,
+                        _ => false,
+                    };
+                    if is_selected {
+                        selected.push((item, key.child(index)));
+                    }
+                }
+            }
+        }
+        selected
+    }
+}
+
+impl Predicate {
+    fn matches(&self, item: &PathItem, position: usize, count: usize) -> bool {
+        let element = item.as_element();
+        match self {
+            Predicate::Position(expected) => position == *expected,
+            Predicate::Last => position == count,
+            Predicate::AttributeExists(name) => element.and_then(|element| element.attribute(name)).is_some(),
+            Predicate::AttributeEquals(name, value, negated) => match element.and_then(|element| element.attribute(name)) {
+                Some(attr_value) => (attr_value == value) != *negated,
+                None => false,
+            },
+            Predicate::ChildExists(name) => element.is_some_and(|element| element.elements().any(|child| child.name == *name)),
+            Predicate::ChildEquals(name, value, negated) => element.is_some_and(|element| {
+                element
+                    .elements()
+                    .filter(|child| child.name == *name)
+                    .any(|child| (PathItem::Element(child).string_value() == *value) != *negated)
+            }),
+            Predicate::TextEquals(value, negated) => (item.string_value() == *value) != *negated,
+        }
+    }
+}
+
+/// Concatenates the text of all descendants.
+fn push_text(element: &Element, text: &mut String) {
+    for node in element.nodes.iter() {
+        match node {
+            Node::Element(child) => push_text(child, text),
+            Node::Text(txt) => text.push_str(txt),
+            Node::Comment(_) => {}
+        }
+    }
+}
+
+/// Is the char part of a name.
+fn is_name_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '-' || ch == '_' || ch == '.' || ch == ':' || !ch.is_ascii()
+}
+
+/// simple recursive descent parser for the path expression
+struct Parser<'e> {
+    expression: &'e str,
+    pos: usize,
+}
+
+impl<'e> Parser<'e> {
+    fn peek(&self) -> Option<char> {
+        self.expression[self.pos..].chars().next()
+    }
+
+    fn error(&self, message: &'static str) -> PathError {
+        PathError { pos: self.pos, message }
+    }
+
+    /// Moves over the expected text if it is there.
+    fn eat(&mut self, expected: &str) -> bool {
+        if self.expression[self.pos..].starts_with(expected) {
+            self.pos += expected.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(ch) = self.peek() {
+            if !ch.is_whitespace() {
+                break;
+            }
+            self.pos += ch.len_utf8();
+        }
+    }
+
+    fn parse_name(&mut self) -> Result<&'e str, PathError> {
+        let start = self.pos;
+        while let Some(ch) = self.peek() {
+            // the dot alone is the self step, not a name
+            if !is_name_char(ch) || (ch == '.' && self.pos == start) {
+                break;
+            }
+            self.pos += ch.len_utf8();
+        }
+        if self.pos == start {
+            return Err(self.error("Error: Expected a name."));
+        }
+        Ok(&self.expression[start..self.pos])
+    }
+
+    fn parse_step(&mut self) -> Result<Step, PathError> {
+        let start = self.pos;
+        let test = if self.eat("..") {
+            return Err(PathError {
+                pos: start,
+                message: "Error: Parent step is not supported.",
+            });
+        } else if self.eat(".") {
+            NodeTest::SelfNode
+        } else if self.eat("@") {
+            if self.eat("*") {
+                NodeTest::AnyAttribute
+            } else {
+                NodeTest::Attribute(self.parse_name()?.to_owned())
+            }
+        } else if self.eat("*") {
+            NodeTest::AnyElement
+        } else {
+            let name = self.parse_name()?;
+            if self.eat("(") {
+                if !self.eat(")") {
+                    return Err(self.error("Error: Expected ) ."));
+                }
+                match name {
+                    "text" => NodeTest::Text,
+                    "comment" => NodeTest::Comment,
+                    "node" => NodeTest::AnyNode,
+                    _ => {
+                        return Err(PathError {
+                            pos: start,
+                            message: "Error: Unsupported function.",
+                        })
+                    }
+                }
+            } else {
+                NodeTest::Name(name.to_owned())
+            }
+        };
+        let mut predicates = Vec::new();
+        while self.eat("[") {
+            self.skip_whitespace();
+            predicates.push(self.parse_predicate()?);
+            self.skip_whitespace();
+            if !self.eat("]") {
+                return Err(self.error("Error: Expected ] ."));
+            }
+        }
+        Ok(Step {
+            descendant: false,
+            test,
+            predicates,
+        })
+    }
+
+    /// after [
+    fn parse_predicate(&mut self) -> Result<Predicate, PathError> {
+        let start = self.pos;
+        if let Some(ch) = self.peek() {
+            if ch.is_ascii_digit() {
+                let digits_len = self.expression[start..]
+                    .find(|ch: char| !ch.is_ascii_digit())
+                    .unwrap_or(self.expression.len() - start);
+                self.pos += digits_len;
+                return match self.expression[start..self.pos].parse() {
+                    Ok(position) if position > 0 => Ok(Predicate::Position(position)),
+                    _ => Err(PathError {
+                        pos: start,
+                        message: "Error: Position must be greater than 0.",
+                    }),
+                };
+            }
+        }
+        if self.eat("last()") {
+            return Ok(Predicate::Last);
+        }
+        if self.eat("text()") {
+            return match self.parse_comparison()? {
+                Some((value, negated)) => Ok(Predicate::TextEquals(value, negated)),
+                None => Ok(Predicate::TextEquals(String::new(), true)),
+            };
+        }
+        let is_attribute = self.eat("@");
+        let name = self.parse_name()?.to_owned();
+        Ok(match (is_attribute, self.parse_comparison()?) {
+            (true, None) => Predicate::AttributeExists(name),
+            (true, Some((value, negated))) => Predicate::AttributeEquals(name, value, negated),
+            (false, None) => Predicate::ChildExists(name),
+            (false, Some((value, negated))) => Predicate::ChildEquals(name, value, negated),
+        })
+    }
+
+    /// Optional `= 'value'` or `!= 'value'`. Returns the value and if it is negated.
+    fn parse_comparison(&mut self) -> Result<Option<(String, bool)>, PathError> {
+        self.skip_whitespace();
+        let negated = if self.eat("!=") {
+            true
+        } else if self.eat("=") {
+            false
+        } else {
+            return Ok(None);
+        };
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some(quote) if quote == '\'' || quote == '"' => {
+                self.pos += 1;
+                let start = self.pos;
+                match self.expression[start..].find(quote) {
+                    Some(len) => {
+                        self.pos = start + len + 1;
+                        self.expression[start..start + len].to_owned()
+                    }
+                    None => return Err(self.error("Error: String has no end quote.")),
+                }
+            }
+            Some(ch) if ch.is_ascii_digit() => {
+                let start = self.pos;
+                let len = self.expression[start..]
+                    .find(|ch: char| !ch.is_ascii_digit() && ch != '.')
+                    .unwrap_or(self.expression.len() - start);
+                self.pos += len;
+                self.expression[start..self.pos].to_string()
+            }
+            _ => return Err(self.error("Error: Expected a string.")),
+        };
+        Ok(Some((value, negated)))
+    }
+}
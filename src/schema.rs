@@ -0,0 +1,839 @@
+//! schema.rs - compact schema language and streaming validator
+//!
+//! One definition per element, `#` starts a comment till the end of the line:
+//! ```text
+//! start = library
+//! library = { @version? book* }
+//! book = { @id @year:integer? title author+ (isbn | issn)? }
+//! title = text
+//! author = text
+//! isbn = token
+//! issn = token
+//! note = { @lang? text em* }
+//! em = text
+//! br = {}
+//! ```
+//! - `name = datatype` is an element with only text of the datatype: `text`, `token`, `integer`, `decimal`, `boolean`.
+//!   The datatype of the text and of the attributes ignores the leading and trailing whitespace.
+//! - `name = { ... }` lists the attributes `@name`, `@name:datatype`, `@name?` and then the content model
+//! - the content model has sequences `a b` or `a, b`, choices `a | b`, groups `( )` and repetitions `? * +`
+//! - `text` inside the content model allows text between the child elements (mixed content)
+//! - `start = name` is the root element, without it any defined element can be the root
+//!
+//! The schema prints with `to_string()` in the same syntax, without the comments.
+//!
+//! The validator runs streaming over the tokens with one state set for every open element.
+//! Whitespace-only text and comments are ignored.
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{entities, ReaderForMicroXml, Token};
+
+pub const ERR_EXPECTED_NAME: &str = "Error: Schema expected a name.";
+pub const ERR_EXPECTED_EQUALS: &str = "Error: Schema expected = after the name.";
+pub const ERR_EXPECTED_CLOSING: &str = "Error: Schema expected ) or }.";
+pub const ERR_UNKNOWN_DATATYPE: &str = "Error: Schema unknown datatype.";
+pub const ERR_DUPLICATE_DEFINITION: &str = "Error: Schema element is defined twice.";
+pub const ERR_UNDEFINED_ELEMENT: &str = "Error: Schema element is not defined.";
+
+pub const ERR_ROOT_NOT_START: &str = "Error: The root element is not the start element.";
+pub const ERR_NO_ROOT: &str = "Error: The document has no root element.";
+pub const ERR_UNDECLARED_ELEMENT: &str = "Error: Element is not declared in the schema.";
+pub const ERR_ELEMENT_NOT_ALLOWED: &str = "Error: Element is not allowed here.";
+pub const ERR_CONTENT_NOT_COMPLETE: &str = "Error: Element content is not complete.";
+pub const ERR_UNDECLARED_ATTRIBUTE: &str = "Error: Attribute is not declared in the schema.";
+pub const ERR_MISSING_ATTRIBUTE: &str = "Error: Required attribute is missing.";
+pub const ERR_ATTRIBUTE_DATATYPE: &str = "Error: Attribute value is not of the datatype.";
+pub const ERR_TEXT_NOT_ALLOWED: &str = "Error: Text is not allowed here.";
+pub const ERR_TEXT_DATATYPE: &str = "Error: Text is not of the datatype.";
+
+/// Error of the schema parser with the byte position.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SchemaError {
+    pub pos: usize,
+    pub message: &'static str,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} Position: {}", self.message, self.pos)
+    }
+}
+
+/// Violation of the schema with the byte position in the document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation {
+    pub pos: usize,
+    pub message: &'static str,
+    /// the element or attribute name
+    pub name: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} Name: {} Position: {}", self.message, self.name, self.pos)
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Datatype {
+    /// any text
+    Text,
+    /// not empty and without whitespace
+    Token,
+    /// `-12`
+    Integer,
+    /// `-12.5`
+    Decimal,
+    /// `true` or `false`
+    Boolean,
+}
+
+impl Datatype {
+    pub fn from_name(name: &str) -> Option<Datatype> {
+        match name {
+            "text" => Some(Datatype::Text),
+            "token" => Some(Datatype::Token),
+            "integer" => Some(Datatype::Integer),
+            "decimal" => Some(Datatype::Decimal),
+            "boolean" => Some(Datatype::Boolean),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Datatype::Text => "text",
+            Datatype::Token => "token",
+            Datatype::Integer => "integer",
+            Datatype::Decimal => "decimal",
+            Datatype::Boolean => "boolean",
+        }
+    }
+
+    /// Checks the decoded value. The leading and trailing whitespace is not significant,
+    /// so `<year> 2020 </year>` and `year=" 2020"` are integers.
+    pub fn is_valid(&self, value: &str) -> bool {
+        let value = value.trim();
+        let digits = |text: &str| !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit());
+        let unsigned = value.strip_prefix(['-', '+']).unwrap_or(value);
+        match self {
+            Datatype::Text => true,
+            Datatype::Token => !value.is_empty() && !value.contains(char::is_whitespace),
+            Datatype::Integer => digits(unsigned),
+            Datatype::Decimal => match unsigned.split_once('.') {
+                Some((int, fraction)) => (int.is_empty() || digits(int)) && digits(fraction),
+                None => digits(unsigned),
+            },
+            Datatype::Boolean => value == "true" || value == "false",
+        }
+    }
+}
+
+/// The content model of the child elements.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Particle {
+    Element(String),
+    /// text between the child elements, it matches no element
+    Text,
+    Sequence(Vec<Particle>),
+    Choice(Vec<Particle>),
+    Optional(Box<Particle>),
+    ZeroOrMore(Box<Particle>),
+    OneOrMore(Box<Particle>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Content {
+    /// only text of the datatype
+    Datatype(Datatype),
+    /// child elements, `mixed` allows text between them
+    Elements { model: Particle, mixed: bool },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AttributeDef {
+    pub name: String,
+    pub datatype: Datatype,
+    pub optional: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ElementDef {
+    pub name: String,
+    pub attributes: Vec<AttributeDef>,
+    pub content: Content,
+    nfa: Nfa,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Schema {
+    /// the root element
+    pub start: Option<String>,
+    pub elements: Vec<ElementDef>,
+}
+
+impl Schema {
+    pub fn parse(text: &str) -> Result<Schema, SchemaError> {
+        SchemaParser { text, pos: 0 }.parse()
+    }
+
+    pub fn element(&self, name: &str) -> Option<&ElementDef> {
+        self.elements.iter().find(|element| element.name == name)
+    }
+
+    /// Reads the document and returns all the violations. A reader error is the last violation.
+    pub fn validate(&self, input: &str) -> Vec<Violation> {
+        let mut validator = Validator::new(self);
+        let mut reader_iterator = ReaderForMicroXml::new(input);
+        while let Some(result_token) = reader_iterator.next() {
+            match result_token {
+                Ok(token) => {
+                    let pos = position_of(input, &token).unwrap_or_else(|| reader_iterator.pos());
+                    validator.token(&token, pos);
+                }
+                Err(err_msg) => {
+                    // the elements cut by the error are not checked
+                    let mut violations = validator.violations;
+                    violations.push(Violation {
+                        pos: reader_iterator.pos(),
+                        message: err_msg,
+                        name: String::new(),
+                    });
+                    return violations;
+                }
+            }
+        }
+        validator.finish(input.len())
+    }
+}
+
+// region: schema printer
+
+/// Prints the schema in the same syntax, one definition per line, without comments.
+impl fmt::Display for Schema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(start) = &self.start {
+            writeln!(f, "start = {}", start)?;
+        }
+        for element_def in self.elements.iter() {
+            write!(f, "{} = ", element_def.name)?;
+            match &element_def.content {
+                Content::Datatype(datatype) => f.write_str(datatype.name())?,
+                Content::Elements { model, .. } => {
+                    let has_model = *model != Particle::Sequence(Vec::new());
+                    f.write_str("{")?;
+                    for attribute in element_def.attributes.iter() {
+                        write!(f, " @{}", attribute.name)?;
+                        if attribute.datatype != Datatype::Text {
+                            write!(f, ":{}", attribute.datatype.name())?;
+                        }
+                        if attribute.optional {
+                            f.write_str("?")?;
+                        }
+                    }
+                    if has_model {
+                        f.write_str(" ")?;
+                        write_particle(f, model, false)?;
+                    }
+                    if !element_def.attributes.is_empty() || has_model {
+                        f.write_str(" ")?;
+                    }
+                    f.write_str("}")?;
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+/// The term is inside of a sequence or before `? * +`, there the sequence needs the parentheses.
+fn write_particle(f: &mut fmt::Formatter<'_>, particle: &Particle, is_term: bool) -> fmt::Result {
+    let (particle, suffix) = match particle {
+        Particle::Element(name) => return f.write_str(name),
+        Particle::Text => return f.write_str("text"),
+        Particle::Sequence(particles) => {
+            if is_term {
+                f.write_str("(")?;
+            }
+            for (i, particle) in particles.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(" ")?;
+                }
+                write_particle(f, particle, true)?;
+            }
+            if is_term {
+                f.write_str(")")?;
+            }
+            return Ok(());
+        }
+        Particle::Choice(particles) => {
+            f.write_str("(")?;
+            for (i, particle) in particles.iter().enumerate() {
+                if i > 0 {
+                    f.write_str(" | ")?;
+                }
+                write_particle(f, particle, false)?;
+            }
+            return f.write_str(")");
+        }
+        Particle::Optional(particle) => (particle, '?'),
+        Particle::ZeroOrMore(particle) => (particle, '*'),
+        Particle::OneOrMore(particle) => (particle, '+'),
+    };
+    // the repetition of a repetition needs the parentheses
+    if matches!(**particle, Particle::Optional(_) | Particle::ZeroOrMore(_) | Particle::OneOrMore(_)) {
+        f.write_str("(")?;
+        write_particle(f, particle, false)?;
+        f.write_str(")")?;
+    } else {
+        write_particle(f, particle, true)?;
+    }
+    write!(f, "{}", suffix)
+}
+
+// endregion
+
+/// the position of the token slice in the input
+pub(crate) fn position_of(input: &str, token: &Token) -> Option<usize> {
+    let slice = match token {
+        Token::StartElement(name) | Token::Attribute(name, _) | Token::EndElement(name) => name,
+        Token::TextNode(txt) | Token::Comment(txt) => txt,
+    };
+    let pos = (slice.as_ptr() as usize).checked_sub(input.as_ptr() as usize)?;
+    (pos + slice.len() <= input.len() && !slice.is_empty()).then_some(pos)
+}
+
+// region: streaming validator
+
+/// one open element
+struct Frame<'s> {
+    /// None for elements not allowed, their content is not validated
+    element_def: Option<&'s ElementDef>,
+    name_pos: usize,
+    states: Vec<usize>,
+    seen_attributes: Vec<bool>,
+    /// the text of the datatype elements
+    text: String,
+}
+
+/// Validates the tokens one by one.
+pub struct Validator<'s> {
+    schema: &'s Schema,
+    stack: Vec<Frame<'s>>,
+    start_tag_open: bool,
+    has_root: bool,
+    violations: Vec<Violation>,
+}
+
+impl<'s> Validator<'s> {
+    pub fn new(schema: &'s Schema) -> Validator<'s> {
+        Validator {
+            schema,
+            stack: Vec::new(),
+            start_tag_open: false,
+            has_root: false,
+            violations: Vec::new(),
+        }
+    }
+
+    /// The position is the byte position of the token in the input.
+    pub fn token(&mut self, token: &Token, pos: usize) {
+        if self.start_tag_open && !matches!(token, Token::Attribute(..)) {
+            self.start_tag_open = false;
+            self.check_required_attributes();
+        }
+        match token {
+            Token::StartElement(name) => self.start_element(name, pos),
+            Token::Attribute(name, value) => self.attribute(name, value, pos),
+            Token::TextNode(txt) => self.text(txt, pos),
+            Token::Comment(_txt) => {}
+            Token::EndElement(_name) => self.end_element(pos),
+        }
+    }
+
+    /// Closes the elements not closed and returns all the violations.
+    pub fn finish(mut self, pos: usize) -> Vec<Violation> {
+        if self.start_tag_open {
+            self.check_required_attributes();
+        }
+        while !self.stack.is_empty() {
+            self.end_element(pos);
+        }
+        if !self.has_root {
+            self.violation(pos, ERR_NO_ROOT, "");
+        }
+        self.violations
+    }
+
+    fn violation(&mut self, pos: usize, message: &'static str, name: &str) {
+        self.violations.push(Violation {
+            pos,
+            message,
+            name: name.to_string(),
+        });
+    }
+
+    fn start_element(&mut self, name: &str, pos: usize) {
+        self.start_tag_open = true;
+        let element_def = match self.stack.last_mut() {
+            None if self.has_root => {
+                self.violation(pos, ERR_ELEMENT_NOT_ALLOWED, name);
+                None
+            }
+            None => {
+                self.has_root = true;
+                let element_def = self.schema.element(name);
+                match &self.schema.start {
+                    Some(start) if start != name => self.violation(pos, ERR_ROOT_NOT_START, name),
+                    None if element_def.is_none() => self.violation(pos, ERR_UNDECLARED_ELEMENT, name),
+                    _ => {}
+                }
+                element_def
+            }
+            // the content of not allowed elements is not validated
+            Some(Frame { element_def: None, .. }) => None,
+            Some(Frame {
+                element_def: Some(parent_def),
+                states,
+                ..
+            }) => {
+                let next_states = parent_def.nfa.step(states, name);
+                if next_states.is_empty() {
+                    self.violation(pos, ERR_ELEMENT_NOT_ALLOWED, name);
+                    None
+                } else {
+                    *states = next_states;
+                    self.schema.element(name)
+                }
+            }
+        };
+        self.stack.push(Frame {
+            element_def,
+            name_pos: pos,
+            states: element_def.map(|element_def| element_def.nfa.start_states()).unwrap_or_default(),
+            seen_attributes: element_def
+                .map(|element_def| vec![false; element_def.attributes.len()])
+                .unwrap_or_default(),
+            text: String::new(),
+        });
+    }
+
+    fn attribute(&mut self, name: &str, value: &str, pos: usize) {
+        let frame = match self.stack.last_mut() {
+            Some(frame) => frame,
+            None => return,
+        };
+        let element_def = match frame.element_def {
+            Some(element_def) => element_def,
+            None => return,
+        };
+        match element_def.attributes.iter().position(|attribute| attribute.name == name) {
+            Some(index) => {
+                frame.seen_attributes[index] = true;
+                if !element_def.attributes[index].datatype.is_valid(&entities::decode(value)) {
+                    self.violation(pos, ERR_ATTRIBUTE_DATATYPE, name);
+                }
+            }
+            None => self.violation(pos, ERR_UNDECLARED_ATTRIBUTE, name),
+        }
+    }
+
+    fn check_required_attributes(&mut self) {
+        let (element_def, name_pos, seen_attributes) = match self.stack.last() {
+            Some(Frame {
+                element_def: Some(element_def),
+                name_pos,
+                seen_attributes,
+                ..
+            }) => (*element_def, *name_pos, seen_attributes.clone()),
+            _ => return,
+        };
+        for (attribute, is_seen) in element_def.attributes.iter().zip(seen_attributes) {
+            if !attribute.optional && !is_seen {
+                self.violation(name_pos, ERR_MISSING_ATTRIBUTE, &attribute.name);
+            }
+        }
+    }
+
+    fn text(&mut self, txt: &str, pos: usize) {
+        let frame = match self.stack.last_mut() {
+            Some(frame) => frame,
+            None => return,
+        };
+        match frame.element_def.map(|element_def| &element_def.content) {
+            Some(Content::Datatype(_datatype)) => frame.text.push_str(&entities::decode(txt)),
+            Some(Content::Elements { mixed: false, .. }) if !txt.trim().is_empty() => {
+                let name = frame.element_def.map(|element_def| element_def.name.as_str()).unwrap_or("");
+                self.violations.push(Violation {
+                    pos,
+                    message: ERR_TEXT_NOT_ALLOWED,
+                    name: name.to_string(),
+                });
+            }
+            _ => {}
+        }
+    }
+
+    fn end_element(&mut self, pos: usize) {
+        let frame = match self.stack.pop() {
+            Some(frame) => frame,
+            None => return,
+        };
+        let element_def = match frame.element_def {
+            Some(element_def) => element_def,
+            None => return,
+        };
+        match &element_def.content {
+            Content::Datatype(datatype) => {
+                if !datatype.is_valid(&frame.text) {
+                    self.violation(pos, ERR_TEXT_DATATYPE, &element_def.name);
+                }
+            }
+            Content::Elements { .. } => {
+                if !element_def.nfa.is_accepting(&frame.states) {
+                    self.violation(pos, ERR_CONTENT_NOT_COMPLETE, &element_def.name);
+                }
+            }
+        }
+    }
+}
+
+// endregion
+
+// region: content model automaton
+
+/// Thompson automaton of the content model, the state 0 is the start.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct Nfa {
+    epsilon: Vec<Vec<usize>>,
+    /// transitions on the child element name
+    transitions: Vec<Vec<(String, usize)>>,
+    accept: usize,
+}
+
+impl Nfa {
+    fn new(particle: &Particle) -> Nfa {
+        let mut nfa = Nfa::default();
+        let start = nfa.add_state();
+        nfa.accept = nfa.compile(particle, start);
+        nfa
+    }
+
+    fn add_state(&mut self) -> usize {
+        self.epsilon.push(Vec::new());
+        self.transitions.push(Vec::new());
+        self.epsilon.len() - 1
+    }
+
+    /// returns the end state
+    fn compile(&mut self, particle: &Particle, from: usize) -> usize {
+        match particle {
+            Particle::Element(name) => {
+                let to = self.add_state();
+                self.transitions[from].push((name.clone(), to));
+                to
+            }
+            Particle::Text => from,
+            Particle::Sequence(particles) => particles.iter().fold(from, |from, particle| self.compile(particle, from)),
+            Particle::Choice(particles) => {
+                let to = self.add_state();
+                for particle in particles.iter() {
+                    let end = self.compile(particle, from);
+                    self.epsilon[end].push(to);
+                }
+                to
+            }
+            Particle::Optional(particle) => {
+                // the fresh entry and exit keep the skip edge away from the inner loops
+                let entry = self.add_state();
+                self.epsilon[from].push(entry);
+                let end = self.compile(particle, entry);
+                let exit = self.add_state();
+                self.epsilon[end].push(exit);
+                self.epsilon[entry].push(exit);
+                exit
+            }
+            Particle::ZeroOrMore(particle) => {
+                let repeat = self.add_state();
+                self.epsilon[from].push(repeat);
+                let end = self.compile(particle, repeat);
+                self.epsilon[end].push(repeat);
+                let exit = self.add_state();
+                self.epsilon[repeat].push(exit);
+                exit
+            }
+            Particle::OneOrMore(particle) => {
+                let repeat = self.add_state();
+                self.epsilon[from].push(repeat);
+                let end = self.compile(particle, repeat);
+                let exit = self.add_state();
+                self.epsilon[end].push(repeat);
+                self.epsilon[end].push(exit);
+                exit
+            }
+        }
+    }
+
+    fn closure(&self, mut states: Vec<usize>) -> Vec<usize> {
+        let mut i = 0;
+        while i < states.len() {
+            for next in self.epsilon[states[i]].iter() {
+                if !states.contains(next) {
+                    states.push(*next);
+                }
+            }
+            i += 1;
+        }
+        states
+    }
+
+    fn start_states(&self) -> Vec<usize> {
+        self.closure(vec![0])
+    }
+
+    fn step(&self, states: &[usize], name: &str) -> Vec<usize> {
+        let mut next_states = Vec::new();
+        for state in states.iter() {
+            for (element_name, to) in self.transitions[*state].iter() {
+                if element_name == name && !next_states.contains(to) {
+                    next_states.push(*to);
+                }
+            }
+        }
+        self.closure(next_states)
+    }
+
+    fn is_accepting(&self, states: &[usize]) -> bool {
+        states.contains(&self.accept)
+    }
+}
+
+// endregion
+
+// region: schema parser
+
+struct SchemaParser<'a> {
+    text: &'a str,
+    pos: usize,
+}
+
+impl<'a> SchemaParser<'a> {
+    fn parse(&mut self) -> Result<Schema, SchemaError> {
+        let mut schema = Schema {
+            start: None,
+            elements: Vec::new(),
+        };
+        // the references to check after all the definitions
+        let mut references: Vec<(String, usize)> = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.pos == self.text.len() {
+                break;
+            }
+            let name_pos = self.pos;
+            let name = self.read_name()?;
+            self.skip_whitespace();
+            self.expect('=', ERR_EXPECTED_EQUALS)?;
+            self.skip_whitespace();
+            if name == "start" {
+                let start_pos = self.pos;
+                let start = self.read_name()?;
+                references.push((start.to_string(), start_pos));
+                schema.start = Some(start.to_string());
+                continue;
+            }
+            if schema.element(name).is_some() {
+                return Err(SchemaError {
+                    pos: name_pos,
+                    message: ERR_DUPLICATE_DEFINITION,
+                });
+            }
+            let (attributes, content) = if self.eat('{') {
+                self.parse_body(&mut references)?
+            } else {
+                (Vec::new(), Content::Datatype(self.read_datatype()?))
+            };
+            let nfa = match &content {
+                Content::Elements { model, .. } => Nfa::new(model),
+                // no child elements
+                Content::Datatype(_datatype) => Nfa::new(&Particle::Sequence(Vec::new())),
+            };
+            schema.elements.push(ElementDef {
+                name: name.to_string(),
+                attributes,
+                content,
+                nfa,
+            });
+        }
+        for (name, pos) in references.iter() {
+            if schema.element(name).is_none() {
+                return Err(SchemaError {
+                    pos: *pos,
+                    message: ERR_UNDEFINED_ELEMENT,
+                });
+            }
+        }
+        Ok(schema)
+    }
+
+    fn error(&self, message: &'static str) -> SchemaError {
+        SchemaError { pos: self.pos, message }
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    /// whitespace and comments
+    fn skip_whitespace(&mut self) {
+        loop {
+            let rest = self.rest();
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if !trimmed.starts_with('#') {
+                return;
+            }
+            self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+        }
+    }
+
+    fn eat(&mut self, ch: char) -> bool {
+        if self.rest().starts_with(ch) {
+            self.pos += ch.len_utf8();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, ch: char, message: &'static str) -> Result<(), SchemaError> {
+        if self.eat(ch) {
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn read_name(&mut self) -> Result<&'a str, SchemaError> {
+        let rest = self.rest();
+        let end = rest
+            .find(|ch: char| ch.is_whitespace() || "{}()|,?*+=@:#".contains(ch))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(self.error(ERR_EXPECTED_NAME));
+        }
+        self.pos += end;
+        Ok(&rest[..end])
+    }
+
+    fn read_datatype(&mut self) -> Result<Datatype, SchemaError> {
+        let pos = self.pos;
+        let name = self.read_name()?;
+        Datatype::from_name(name).ok_or(SchemaError {
+            pos,
+            message: ERR_UNKNOWN_DATATYPE,
+        })
+    }
+
+    /// `{` is already read
+    fn parse_body(&mut self, references: &mut Vec<(String, usize)>) -> Result<(Vec<AttributeDef>, Content), SchemaError> {
+        let mut attributes = Vec::new();
+        self.skip_whitespace();
+        while self.eat('@') {
+            let name = self.read_name()?.to_string();
+            let datatype = if self.eat(':') { self.read_datatype()? } else { Datatype::Text };
+            let optional = self.eat('?');
+            attributes.push(AttributeDef { name, datatype, optional });
+            self.skip_whitespace();
+        }
+        let model = if self.rest().starts_with('}') {
+            Particle::Sequence(Vec::new())
+        } else {
+            self.parse_choice(references)?
+        };
+        self.skip_whitespace();
+        self.expect('}', ERR_EXPECTED_CLOSING)?;
+        let mixed = contains_text(&model);
+        Ok((attributes, Content::Elements { model, mixed }))
+    }
+
+    fn parse_choice(&mut self, references: &mut Vec<(String, usize)>) -> Result<Particle, SchemaError> {
+        let mut particles = Vec::new();
+        loop {
+            particles.push(self.parse_sequence(references)?);
+            self.skip_whitespace();
+            if !self.eat('|') {
+                break;
+            }
+        }
+        Ok(single_or(particles, Particle::Choice))
+    }
+
+    fn parse_sequence(&mut self, references: &mut Vec<(String, usize)>) -> Result<Particle, SchemaError> {
+        let mut particles = Vec::new();
+        loop {
+            self.skip_whitespace();
+            if self.eat(',') {
+                continue;
+            }
+            let rest = self.rest();
+            if rest.is_empty() || rest.starts_with(['|', ')', '}']) {
+                break;
+            }
+            particles.push(self.parse_term(references)?);
+        }
+        Ok(single_or(particles, Particle::Sequence))
+    }
+
+    fn parse_term(&mut self, references: &mut Vec<(String, usize)>) -> Result<Particle, SchemaError> {
+        let particle = if self.eat('(') {
+            let particle = self.parse_choice(references)?;
+            self.skip_whitespace();
+            self.expect(')', ERR_EXPECTED_CLOSING)?;
+            particle
+        } else {
+            let pos = self.pos;
+            match self.read_name()? {
+                "text" => Particle::Text,
+                name => {
+                    references.push((name.to_string(), pos));
+                    Particle::Element(name.to_string())
+                }
+            }
+        };
+        Ok(if self.eat('?') {
+            Particle::Optional(Box::new(particle))
+        } else if self.eat('*') {
+            Particle::ZeroOrMore(Box::new(particle))
+        } else if self.eat('+') {
+            Particle::OneOrMore(Box::new(particle))
+        } else {
+            particle
+        })
+    }
+}
+
+fn single_or(mut particles: Vec<Particle>, group: fn(Vec<Particle>) -> Particle) -> Particle {
+    if particles.len() == 1 {
+        particles.remove(0)
+    } else {
+        group(particles)
+    }
+}
+
+fn contains_text(particle: &Particle) -> bool {
+    match particle {
+        Particle::Element(_name) => false,
+        Particle::Text => true,
+        Particle::Sequence(particles) | Particle::Choice(particles) => particles.iter().any(contains_text),
+        Particle::Optional(particle) | Particle::ZeroOrMore(particle) | Particle::OneOrMore(particle) => contains_text(particle),
+    }
+}
+
+// endregion
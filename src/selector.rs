@@ -0,0 +1,452 @@
+//! selector.rs - css selectors over the dom tree
+//!
+//! Supported: type `a` and `*`, `#id`, `.class`,
+//! attributes `[a]`, `[a=v]`, `[a~=v]`, `[a^=v]`, `[a$=v]`, `[a*=v]`,
+//! combinators descendant `a b`, child `a > b`, adjacent sibling `a + b`, general sibling `a ~ b`,
+//! pseudo-classes `:first-child` and `:nth-child(an+b)` (also `odd` and `even`),
+//! and a list of selectors separated by comma.
+
+use alloc::{borrow::ToOwned, string::String, vec::Vec};
+use core::fmt;
+
+use crate::dom::{Element, Node};
+
+/// Compiled selector. Parse once and use it many times.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Selector {
+    /// list of selectors separated by comma
+    complex_selectors: Vec<ComplexSelector>,
+}
+
+/// Error with the position in the selector expression.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SelectorError {
+    /// byte position in the selector expression
+    pub pos: usize,
+    pub message: &'static str,
+}
+
+/// compound selectors joined with combinators
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ComplexSelector {
+    compounds: Vec<CompoundSelector>,
+    /// combinators[i] is between compounds[i] and compounds[i+1]
+    combinators: Vec<Combinator>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Combinator {
+    Descendant,
+    Child,
+    AdjacentSibling,
+    GeneralSibling,
+}
+
+/// all the simple selectors for one element
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct CompoundSelector {
+    /// None is the universal selector *
+    name: Option<String>,
+    conditions: Vec<Condition>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Condition {
+    Attribute(String, AttributeOperator, String),
+    FirstChild,
+    /// a*n+b
+    NthChild(i64, i64),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum AttributeOperator {
+    Exists,
+    Equals,
+    Includes,
+    StartsWith,
+    EndsWith,
+    Contains,
+}
+
+/// Location of an element: the slice of its siblings and its index in the slice.
+type Location<'t> = (&'t [Node], usize);
+
+impl fmt::Display for SelectorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} at position {}", self.message, self.pos)
+    }
+}
+
+impl Selector {
+    /// Parses the selector expression.
+    pub fn parse(expression: &str) -> Result<Selector, SelectorError> {
+        let mut parser = Parser { expression, pos: 0 };
+        let mut complex_selectors = Vec::new();
+        loop {
+            complex_selectors.push(parser.parse_complex()?);
+            parser.skip_whitespace();
+            match parser.peek() {
+                None => break,
+                Some(',') => parser.pos += 1,
+                Some(_) => return Err(parser.error("Error: Unexpected character.")),
+            }
+        }
+        Ok(Selector { complex_selectors })
+    }
+
+    /// All elements in the nodes (and their descendants) that match, in document order.
+    pub fn select<'t>(&self, nodes: &'t [Node]) -> Vec<&'t Element> {
+        let mut result = Vec::new();
+        let mut ancestors = Vec::new();
+        self.select_recursive(nodes, &mut ancestors, &mut result, false);
+        result
+    }
+
+    /// The first element in document order that matches.
+    pub fn select_first<'t>(&self, nodes: &'t [Node]) -> Option<&'t Element> {
+        let mut result = Vec::new();
+        let mut ancestors = Vec::new();
+        self.select_recursive(nodes, &mut ancestors, &mut result, true);
+        result.pop()
+    }
+
+    /// Does the element at index in this siblings slice match (without ancestors).
+    /// False if the index is out of the slice or the node is not an element.
+    pub fn matches(&self, siblings: &[Node], index: usize) -> bool {
+        self.complex_selectors.iter().any(|complex| complex.matches(&[], (siblings, index)))
+    }
+
+    fn select_recursive<'t>(&self, nodes: &'t [Node], ancestors: &mut Vec<Location<'t>>, result: &mut Vec<&'t Element>, only_first: bool) {
+        for (index, node) in nodes.iter().enumerate() {
+            if only_first && !result.is_empty() {
+                return;
+            }
+            if let Node::Element(element) = node {
+                if self
+                    .complex_selectors
+                    .iter()
+                    .any(|complex| complex.matches(ancestors, (nodes, index)))
+                {
+                    result.push(element);
+                }
+                ancestors.push((nodes, index));
+                self.select_recursive(&element.nodes, ancestors, result, only_first);
+                ancestors.pop();
+            }
+        }
+    }
+}
+
+impl core::str::FromStr for Selector {
+    type Err = SelectorError;
+    fn from_str(expression: &str) -> Result<Selector, SelectorError> {
+        Selector::parse(expression)
+    }
+}
+
+impl ComplexSelector {
+    fn matches(&self, ancestors: &[Location], location: Location) -> bool {
+        self.matches_compound(self.compounds.len() - 1, ancestors, location)
+    }
+
+    /// Matching from right to left. Backtracking for descendant and general sibling.
+    fn matches_compound(&self, k: usize, ancestors: &[Location], location: Location) -> bool {
+        if !self.compounds[k].matches(location) {
+            return false;
+        }
+        if k == 0 {
+            return true;
+        }
+        let (siblings, index) = location;
+        match self.combinators[k - 1] {
+            Combinator::Child => match ancestors.split_last() {
+                Some((parent, grand_ancestors)) => self.matches_compound(k - 1, grand_ancestors, *parent),
+                None => false,
+            },
+            Combinator::Descendant => (0..ancestors.len())
+                .rev()
+                .any(|level| self.matches_compound(k - 1, &ancestors[..level], ancestors[level])),
+            Combinator::AdjacentSibling => match previous_element_indices(siblings, index).next() {
+                Some(previous) => self.matches_compound(k - 1, ancestors, (siblings, previous)),
+                None => false,
+            },
+            Combinator::GeneralSibling => {
+                previous_element_indices(siblings, index).any(|previous| self.matches_compound(k - 1, ancestors, (siblings, previous)))
+            }
+        }
+    }
+}
+
+impl CompoundSelector {
+    fn matches(&self, location: Location) -> bool {
+        let (siblings, index) = location;
+        let element = match siblings.get(index) {
+            Some(Node::Element(element)) => element,
+            _ => return false,
+        };
+        if let Some(name) = &self.name {
+            if *name != element.name {
+                return false;
+            }
+        }
+        self.conditions.iter().all(|condition| match condition {
+            Condition::Attribute(name, operator, value) => match element.attribute(name) {
+                Some(attr_value) => operator.matches(attr_value, value),
+                None => false,
+            },
+            Condition::FirstChild => previous_element_indices(siblings, index).next().is_none(),
+            Condition::NthChild(a, b) => {
+                // the position is 1-based and counts only elements
+                let position = previous_element_indices(siblings, index).count() as i64 + 1;
+                nth_matches(*a, *b, position)
+            }
+        })
+    }
+}
+
+impl AttributeOperator {
+    fn matches(self, attr_value: &str, value: &str) -> bool {
+        match self {
+            AttributeOperator::Exists => true,
+            AttributeOperator::Equals => attr_value == value,
+            AttributeOperator::Includes => attr_value.split_whitespace().any(|word| word == value),
+            AttributeOperator::StartsWith => !value.is_empty() && attr_value.starts_with(value),
+            AttributeOperator::EndsWith => !value.is_empty() && attr_value.ends_with(value),
+            AttributeOperator::Contains => !value.is_empty() && attr_value.contains(value),
+        }
+    }
+}
+
+/// Indices of the previous siblings that are elements, from the nearest.
+fn previous_element_indices(siblings: &[Node], index: usize) -> impl Iterator<Item = usize> + '_ {
+    (0..index).rev().filter(move |i| matches!(siblings[*i], Node::Element(_)))
+}
+
+/// Is there an n >= 0 so that a*n+b == position.
+/// The a and b come from the user, so the arithmetic is in i128 and cannot overflow.
+fn nth_matches(a: i64, b: i64, position: i64) -> bool {
+    let (a, b, position) = (i128::from(a), i128::from(b), i128::from(position));
+    if a == 0 {
+        position == b
+    } else {
+        let diff = position - b;
+        diff % a == 0 && diff / a >= 0
+    }
+}
+
+/// Is the char part of a css identifier.
+fn is_name_char(ch: char) -> bool {
+    ch.is_alphanumeric() || ch == '-' || ch == '_' || !ch.is_ascii()
+}
+
+/// simple recursive descent parser for the selector expression
+struct Parser<'e> {
+    expression: &'e str,
+    pos: usize,
+}
+
+impl<'e> Parser<'e> {
+    fn peek(&self) -> Option<char> {
+        self.expression[self.pos..].chars().next()
+    }
+
+    fn error(&self, message: &'static str) -> SelectorError {
+        SelectorError { pos: self.pos, message }
+    }
+
+    fn skip_whitespace(&mut self) -> bool {
+        let start = self.pos;
+        while let Some(ch) = self.peek() {
+            if !ch.is_whitespace() {
+                break;
+            }
+            self.pos += ch.len_utf8();
+        }
+        self.pos > start
+    }
+
+    fn expect(&mut self, expected: char, message: &'static str) -> Result<(), SelectorError> {
+        if self.peek() == Some(expected) {
+            self.pos += 1;
+            Ok(())
+        } else {
+            Err(self.error(message))
+        }
+    }
+
+    fn parse_name(&mut self) -> Result<&'e str, SelectorError> {
+        let start = self.pos;
+        while let Some(ch) = self.peek() {
+            if !is_name_char(ch) {
+                break;
+            }
+            self.pos += ch.len_utf8();
+        }
+        if self.pos == start {
+            return Err(self.error("Error: Expected a name."));
+        }
+        Ok(&self.expression[start..self.pos])
+    }
+
+    fn parse_complex(&mut self) -> Result<ComplexSelector, SelectorError> {
+        self.skip_whitespace();
+        let mut compounds = Vec::new();
+        let mut combinators = Vec::new();
+        compounds.push(self.parse_compound()?);
+        loop {
+            let had_whitespace = self.skip_whitespace();
+            let combinator = match self.peek() {
+                None | Some(',') => break,
+                Some('>') => Combinator::Child,
+                Some('+') => Combinator::AdjacentSibling,
+                Some('~') => Combinator::GeneralSibling,
+                Some(_) if had_whitespace => Combinator::Descendant,
+                Some(_) => return Err(self.error("Error: Unexpected character.")),
+            };
+            if combinator != Combinator::Descendant {
+                self.pos += 1;
+                self.skip_whitespace();
+            }
+            combinators.push(combinator);
+            compounds.push(self.parse_compound()?);
+        }
+        Ok(ComplexSelector { compounds, combinators })
+    }
+
+    fn parse_compound(&mut self) -> Result<CompoundSelector, SelectorError> {
+        let start = self.pos;
+        let mut compound = CompoundSelector::default();
+        match self.peek() {
+            Some('*') => self.pos += 1,
+            Some(ch) if is_name_char(ch) => compound.name = Some(self.parse_name()?.to_owned()),
+            _ => {}
+        }
+        loop {
+            match self.peek() {
+                Some('#') => {
+                    self.pos += 1;
+                    let id = self.parse_name()?;
+                    compound
+                        .conditions
+                        .push(Condition::Attribute("id".to_owned(), AttributeOperator::Equals, id.to_owned()));
+                }
+                Some('.') => {
+                    self.pos += 1;
+                    let class = self.parse_name()?;
+                    compound.conditions.push(Condition::Attribute(
+                        "class".to_owned(),
+                        AttributeOperator::Includes,
+                        class.to_owned(),
+                    ));
+                }
+                Some('[') => {
+                    self.pos += 1;
+                    let condition = self.parse_attribute()?;
+                    compound.conditions.push(condition);
+                }
+                Some(':') => {
+                    self.pos += 1;
+                    let condition = self.parse_pseudo_class()?;
+                    compound.conditions.push(condition);
+                }
+                _ => break,
+            }
+        }
+        if self.pos == start {
+            return Err(self.error("Error: Expected a selector."));
+        }
+        Ok(compound)
+    }
+
+    /// after [
+    fn parse_attribute(&mut self) -> Result<Condition, SelectorError> {
+        self.skip_whitespace();
+        let name = self.parse_name()?.to_owned();
+        self.skip_whitespace();
+        let operator = match self.peek() {
+            Some(']') => {
+                self.pos += 1;
+                return Ok(Condition::Attribute(name, AttributeOperator::Exists, String::new()));
+            }
+            Some('=') => AttributeOperator::Equals,
+            Some('~') => AttributeOperator::Includes,
+            Some('^') => AttributeOperator::StartsWith,
+            Some('$') => AttributeOperator::EndsWith,
+            Some('*') => AttributeOperator::Contains,
+            _ => return Err(self.error("Error: Expected an attribute operator.")),
+        };
+        self.pos += 1;
+        if operator != AttributeOperator::Equals {
+            self.expect('=', "Error: Expected = .")?;
+        }
+        self.skip_whitespace();
+        let value = match self.peek() {
+            Some(quote) if quote == '"' || quote == '\'' => {
+                self.pos += 1;
+                let start = self.pos;
+                match self.expression[start..].find(quote) {
+                    Some(len) => {
+                        self.pos = start + len + 1;
+                        &self.expression[start..start + len]
+                    }
+                    None => return Err(self.error("Error: Attribute value has no end quote.")),
+                }
+            }
+            _ => self.parse_name()?,
+        };
+        self.skip_whitespace();
+        self.expect(']', "Error: Expected ] .")?;
+        Ok(Condition::Attribute(name, operator, value.to_owned()))
+    }
+
+    /// after :
+    fn parse_pseudo_class(&mut self) -> Result<Condition, SelectorError> {
+        let start = self.pos;
+        match self.parse_name()? {
+            "first-child" => Ok(Condition::FirstChild),
+            "nth-child" => {
+                self.expect('(', "Error: Expected ( .")?;
+                let argument_start = self.pos;
+                let len = match self.expression[argument_start..].find(')') {
+                    Some(len) => len,
+                    None => return Err(self.error("Error: Expected ) .")),
+                };
+                let (a, b) = parse_nth(&self.expression[argument_start..argument_start + len]).ok_or(SelectorError {
+                    pos: argument_start,
+                    message: "Error: Wrong nth-child argument.",
+                })?;
+                self.pos = argument_start + len + 1;
+                Ok(Condition::NthChild(a, b))
+            }
+            _ => Err(SelectorError {
+                pos: start,
+                message: "Error: Unsupported pseudo-class.",
+            }),
+        }
+    }
+}
+
+/// Parses `an+b`, `odd`, `even` or a number.
+fn parse_nth(argument: &str) -> Option<(i64, i64)> {
+    let argument: String = argument.chars().filter(|ch| !ch.is_whitespace()).collect();
+    match argument.as_str() {
+        "odd" => return Some((2, 1)),
+        "even" => return Some((2, 0)),
+        _ => {}
+    }
+    match argument.find('n') {
+        None => Some((0, argument.parse().ok()?)),
+        Some(n_pos) => {
+            let a = match &argument[..n_pos] {
+                "" | "+" => 1,
+                "-" => -1,
+                a => a.parse().ok()?,
+            };
+            let b = match &argument[n_pos + 1..] {
+                "" => 0,
+                b => b.strip_prefix('+').unwrap_or(b).parse().ok()?,
+            };
+            Some((a, b))
+        }
+    }
+}
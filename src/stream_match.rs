@@ -0,0 +1,229 @@
+//! stream_match.rs - extract data from big input without building a tree
+//!
+//! Simple absolute paths like `/catalog/book/title` or `/catalog/book/@id`.
+//! The segment `*` matches any element name.
+//! Only a stack of element names is kept in memory.
+
+use alloc::{borrow::Cow, collections::VecDeque, string::String, vec::Vec};
+
+use crate::path::PathError;
+use crate::{ReaderForMicroXml, Token};
+
+/// Matches a set of simple paths while the tokens stream past.
+/// It is an iterator of matches.
+pub struct StreamMatcher<'a> {
+    reader_iterator: ReaderForMicroXml<'a>,
+    paths: Vec<SimplePath<'a>>,
+    /// open elements: name and sequential number
+    stack: Vec<(&'a str, usize)>,
+    /// count of start elements until now
+    elements_count: usize,
+    /// element paths that are collecting the text until their end element
+    captures: Vec<Capture<'a>>,
+    /// one token can make more matches
+    pending: VecDeque<StreamMatch<'a>>,
+}
+
+/// One match of a path.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct StreamMatch<'a> {
+    /// index of the path in the slice given to the constructor
+    pub path_index: usize,
+    /// names of the enclosing elements from the root to the matched element
+    pub context: Vec<&'a str>,
+    /// sequential number of the matched element (for attributes the element that has it)
+    pub element_index: usize,
+    /// sequential number of the parent element, to group the fields of the same record
+    pub parent_index: Option<usize>,
+    /// attribute value or all the text inside the element
+    pub value: Cow<'a, str>,
+}
+
+/// compiled simple path
+struct SimplePath<'a> {
+    elements: Vec<&'a str>,
+    attribute: Option<&'a str>,
+}
+
+/// element match that is waiting for its end element
+struct Capture<'a> {
+    path_index: usize,
+    depth: usize,
+    context: Vec<&'a str>,
+    element_index: usize,
+    parent_index: Option<usize>,
+    text: Option<Cow<'a, str>>,
+}
+
+impl<'a> StreamMatcher<'a> {
+    /// The paths are parsed here, the error has the position in the path.
+    pub fn new(reader_iterator: ReaderForMicroXml<'a>, paths: &[&'a str]) -> Result<StreamMatcher<'a>, PathError> {
+        let paths = paths.iter().map(|path| SimplePath::parse(path)).collect::<Result<Vec<_>, _>>()?;
+        Ok(StreamMatcher {
+            reader_iterator,
+            paths,
+            stack: Vec::new(),
+            elements_count: 0,
+            captures: Vec::new(),
+            pending: VecDeque::new(),
+        })
+    }
+
+    fn context(&self) -> Vec<&'a str> {
+        self.stack.iter().map(|(name, _index)| *name).collect()
+    }
+
+    fn parent_index(&self) -> Option<usize> {
+        self.stack.len().checked_sub(2).map(|parent| self.stack[parent].1)
+    }
+
+    fn process_token(&mut self, token: Token<'a>) {
+        match token {
+            Token::StartElement(name) => {
+                self.elements_count += 1;
+                self.stack.push((name, self.elements_count));
+                for path_index in 0..self.paths.len() {
+                    if self.paths[path_index].attribute.is_none() && self.paths[path_index].matches(&self.stack) {
+                        self.captures.push(Capture {
+                            path_index,
+                            depth: self.stack.len(),
+                            context: self.context(),
+                            element_index: self.elements_count,
+                            parent_index: self.parent_index(),
+                            text: None,
+                        });
+                    }
+                }
+            }
+            Token::Attribute(name, value) => {
+                for path_index in 0..self.paths.len() {
+                    if self.paths[path_index].attribute == Some(name) && self.paths[path_index].matches(&self.stack) {
+                        self.pending.push_back(StreamMatch {
+                            path_index,
+                            context: self.context(),
+                            element_index: self.elements_count,
+                            parent_index: self.parent_index(),
+                            value: Cow::Borrowed(value),
+                        });
+                    }
+                }
+            }
+            Token::TextNode(txt) => {
+                for capture in self.captures.iter_mut() {
+                    capture.text = Some(match capture.text.take() {
+                        None => Cow::Borrowed(txt),
+                        Some(text) => {
+                            let mut text = String::from(text);
+                            text.push_str(txt);
+                            Cow::Owned(text)
+                        }
+                    });
+                }
+            }
+            Token::Comment(_txt) => {}
+            Token::EndElement(_name) => {
+                let depth = self.stack.len();
+                self.finish_captures(depth);
+                self.stack.pop();
+            }
+        }
+    }
+
+    /// The captures at this depth or deeper are finished.
+    fn finish_captures(&mut self, depth: usize) {
+        let mut i = 0;
+        while i < self.captures.len() {
+            if self.captures[i].depth >= depth {
+                let capture = self.captures.remove(i);
+                self.pending.push_back(StreamMatch {
+                    path_index: capture.path_index,
+                    context: capture.context,
+                    element_index: capture.element_index,
+                    parent_index: capture.parent_index,
+                    value: capture.text.unwrap_or(Cow::Borrowed("")),
+                });
+            } else {
+                i += 1;
+            }
+        }
+    }
+}
+
+impl<'a> Iterator for StreamMatcher<'a> {
+    type Item = Result<StreamMatch<'a>, &'static str>;
+    /// Reads tokens until the next match.
+    fn next(&mut self) -> Option<Result<StreamMatch<'a>, &'static str>> {
+        loop {
+            if let Some(stream_match) = self.pending.pop_front() {
+                return Some(Ok(stream_match));
+            }
+            match self.reader_iterator.next() {
+                Some(Ok(token)) => self.process_token(token),
+                Some(Err(err_msg)) => return Some(Err(err_msg)),
+                None => {
+                    // not closed elements are finished at the end of input
+                    if self.captures.is_empty() {
+                        return None;
+                    }
+                    self.finish_captures(0);
+                }
+            }
+        }
+    }
+}
+
+impl<'a> SimplePath<'a> {
+    fn parse(path: &'a str) -> Result<SimplePath<'a>, PathError> {
+        if !path.starts_with('/') || path.starts_with("//") {
+            return Err(PathError {
+                pos: 0,
+                message: "Error: Simple path must start with one / .",
+            });
+        }
+        let mut elements = Vec::new();
+        let mut attribute = None;
+        let mut pos = 1;
+        for segment in path[1..].split('/') {
+            if attribute.is_some() {
+                return Err(PathError {
+                    pos,
+                    message: "Error: Attribute must be the last segment.",
+                });
+            }
+            if segment.is_empty() {
+                return Err(PathError {
+                    pos,
+                    message: "Error: Expected a name.",
+                });
+            }
+            if segment.contains(|ch: char| ch.is_whitespace() || "[]()=\"'".contains(ch)) {
+                return Err(PathError {
+                    pos,
+                    message: "Error: Simple path has only names.",
+                });
+            }
+            match segment.strip_prefix('@') {
+                Some(name) => attribute = Some(name),
+                None => elements.push(segment),
+            }
+            pos += segment.len() + 1;
+        }
+        if elements.is_empty() {
+            return Err(PathError {
+                pos: 1,
+                message: "Error: Expected a name.",
+            });
+        }
+        Ok(SimplePath { elements, attribute })
+    }
+
+    /// Does the stack of open elements match the element segments.
+    fn matches(&self, stack: &[(&str, usize)]) -> bool {
+        self.elements.len() == stack.len()
+            && self
+                .elements
+                .iter()
+                .zip(stack.iter())
+                .all(|(segment, (name, _index))| *segment == "*" || segment == name)
+    }
+}
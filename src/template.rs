@@ -0,0 +1,444 @@
+//! template.rs - render html templates with placeholders
+//!
+//! The template is a valid MicroXml, so the graphic designer can open it in the browser.
+//! The placeholders:
+//! - `<!--t=name-->` the value replaces the next text node
+//! - `data-t-href="name"` the value replaces the attribute `href`
+//! - `data-t-if="name"` or `data-t-if="!name"` renders the element only if the condition is true (false)
+//! - `data-t-repeat="name"` renders the element once for every item
+//!
+//! The placeholder comments and the `data-t-` attributes are not rendered.
+//! The whitespace and the unknown references like `&nbsp;` of the template are rendered as they are.
+//!
+//! The template is compiled once into a list of instructions: static chunks of already escaped MicroXml
+//! and slots for the placeholders. The renderer never parses the template again.
+//! The compiled template can be serialized to bytes, for example in build.rs, and embedded with `include_bytes!`.
+
+use alloc::{borrow::Cow, string::String, vec::Vec};
+use core::{convert::TryFrom, fmt};
+
+use crate::dom::{self, Element, Node};
+use crate::entities;
+use crate::{ReaderForMicroXml, WhitespacePolicy};
+
+pub const ERR_WRITE: &str = "Error: Template write failed.";
+pub const ERR_BYTES_HEADER: &str = "Error: Compiled template bytes have a wrong header.";
+pub const ERR_BYTES_INVALID: &str = "Error: Compiled template bytes are invalid.";
+pub const ERR_BYTES_TOO_BIG: &str = "Error: Compiled template is too big for the u32 numbers of the bytes.";
+
+/// prefix of the placeholder attributes
+const ATTR_PREFIX: &str = "data-t-";
+const ATTR_IF: &str = "data-t-if";
+const ATTR_REPEAT: &str = "data-t-repeat";
+/// header and version of the serialized bytes
+const BYTES_HEADER: &[u8] = b"MXT\x01";
+
+/// The data for the placeholders.
+/// Only `value()` is required.
+pub trait TemplateData {
+    /// Value for the text and attribute placeholders.
+    /// None leaves the content of the template.
+    fn value(&self, name: &str) -> Option<Cow<'_, str>>;
+    /// Condition for `data-t-if`. The default is true for a non-empty value.
+    fn condition(&self, name: &str) -> bool {
+        self.value(name).is_some_and(|value| !value.is_empty())
+    }
+    /// Items for `data-t-repeat`. Calls `render_item` once for every item.
+    /// The default has no items.
+    fn repeat(&self, _name: &str, _render_item: &mut dyn FnMut(&dyn TemplateData) -> fmt::Result) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// Simple data as a slice of (name, value).
+impl TemplateData for [(&str, &str)] {
+    fn value(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.iter()
+            .find(|(key, _value)| *key == name)
+            .map(|(_key, value)| Cow::Borrowed(*value))
+    }
+}
+
+/// One instruction of the compiled template.
+/// The static strings and defaults are already escaped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Instruction<'a> {
+    /// static MicroXml
+    Static(Cow<'a, str>),
+    /// text placeholder: name, default
+    Text(Cow<'a, str>, Cow<'a, str>),
+    /// attribute value placeholder inside the quotes: name, default
+    AttributeValue(Cow<'a, str>, Cow<'a, str>),
+    /// attribute that is written only if the value exists: attribute name, placeholder name
+    Attribute(Cow<'a, str>, Cow<'a, str>),
+    /// condition: name, negate, index of the instruction after the conditional part
+    If(Cow<'a, str>, bool, usize),
+    /// loop: name, index of the instruction after the repeated part
+    Repeat(Cow<'a, str>, usize),
+}
+
+/// Template compiled into a list of instructions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CompiledTemplate<'a> {
+    instructions: Vec<Instruction<'a>>,
+}
+
+/// Reads the template and writes the rendered MicroXml with escaping.
+/// The template error is returned before anything is written.
+pub fn render<D, W>(template: &str, data: &D, w: &mut W) -> Result<(), &'static str>
+where
+    D: TemplateData + ?Sized,
+    W: fmt::Write,
+{
+    CompiledTemplate::compile(template)?.render(data, w)
+}
+
+/// Renders the template into a new string.
+pub fn render_to_string<D: TemplateData + ?Sized>(template: &str, data: &D) -> Result<String, &'static str> {
+    let mut text = String::new();
+    render(template, data, &mut text)?;
+    Ok(text)
+}
+
+/// the name of the placeholder comment `t=name`
+fn placeholder_name(comment: &str) -> Option<&str> {
+    comment.trim().strip_prefix("t=").map(str::trim)
+}
+
+/// wrapper to get `&dyn TemplateData` also from unsized data
+struct AsDyn<'d, D: ?Sized>(&'d D);
+
+impl<D: TemplateData + ?Sized> TemplateData for AsDyn<'_, D> {
+    fn value(&self, name: &str) -> Option<Cow<'_, str>> {
+        self.0.value(name)
+    }
+    fn condition(&self, name: &str) -> bool {
+        self.0.condition(name)
+    }
+    fn repeat(&self, name: &str, render_item: &mut dyn FnMut(&dyn TemplateData) -> fmt::Result) -> fmt::Result {
+        self.0.repeat(name, render_item)
+    }
+}
+
+impl CompiledTemplate<'static> {
+    /// Reads the template once and compiles it.
+    pub fn compile(template: &str) -> Result<CompiledTemplate<'static>, &'static str> {
+        // the whitespace between inline elements is significant in html
+        let reader_iterator = ReaderForMicroXml::new_fragment(template).with_whitespace(WhitespacePolicy::Preserve);
+        let nodes = dom::read_nodes(reader_iterator)?;
+        let mut compiler = Compiler::default();
+        // writing to a String cannot fail
+        let _ = compiler.compile_nodes(&nodes);
+        compiler.flush_static();
+        Ok(CompiledTemplate {
+            instructions: compiler.instructions,
+        })
+    }
+}
+
+impl<'a> CompiledTemplate<'a> {
+    /// The compiled instructions.
+    pub fn instructions(&self) -> &[Instruction<'a>] {
+        &self.instructions
+    }
+
+    /// Renders the compiled template with escaping.
+    pub fn render<D, W>(&self, data: &D, w: &mut W) -> Result<(), &'static str>
+    where
+        D: TemplateData + ?Sized,
+        W: fmt::Write,
+    {
+        self.render_range(0, self.instructions.len(), &AsDyn(data), w)
+            .map_err(|_| ERR_WRITE)
+    }
+
+    /// Renders the compiled template into a new string.
+    pub fn render_to_string<D: TemplateData + ?Sized>(&self, data: &D) -> Result<String, &'static str> {
+        let mut text = String::new();
+        self.render(data, &mut text)?;
+        Ok(text)
+    }
+
+    fn render_range(&self, start: usize, end: usize, data: &dyn TemplateData, w: &mut dyn fmt::Write) -> fmt::Result {
+        let mut i = start;
+        while i < end {
+            match &self.instructions[i] {
+                Instruction::Static(txt) => w.write_str(txt)?,
+                Instruction::Text(name, default) => match data.value(name) {
+                    Some(value) => entities::write_escaped_text(w, &value)?,
+                    None => w.write_str(default)?,
+                },
+                Instruction::AttributeValue(name, default) => match data.value(name) {
+                    Some(value) => entities::write_escaped_attribute(w, &value)?,
+                    None => w.write_str(default)?,
+                },
+                Instruction::Attribute(attribute_name, name) => {
+                    if let Some(value) = data.value(name) {
+                        write!(w, " {}=\"", attribute_name)?;
+                        entities::write_escaped_attribute(w, &value)?;
+                        w.write_char('"')?;
+                    }
+                }
+                Instruction::If(name, negate, if_end) => {
+                    if data.condition(name) == *negate {
+                        i = *if_end;
+                        continue;
+                    }
+                }
+                Instruction::Repeat(name, repeat_end) => {
+                    // the condition is checked for every item
+                    data.repeat(name, &mut |item| self.render_range(i + 1, *repeat_end, item, w))?;
+                    i = *repeat_end;
+                    continue;
+                }
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+
+    // region: bytes
+
+    /// Serializes the instructions. The numbers are u32 little-endian, the strings have the length before.
+    /// A string or a count that does not fit into u32 is an error.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, &'static str> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(BYTES_HEADER);
+        push_u32(&mut bytes, self.instructions.len())?;
+        for instruction in self.instructions.iter() {
+            match instruction {
+                Instruction::Static(txt) => {
+                    bytes.push(0);
+                    push_str(&mut bytes, txt)?;
+                }
+                Instruction::Text(name, default) => {
+                    bytes.push(1);
+                    push_str(&mut bytes, name)?;
+                    push_str(&mut bytes, default)?;
+                }
+                Instruction::AttributeValue(name, default) => {
+                    bytes.push(2);
+                    push_str(&mut bytes, name)?;
+                    push_str(&mut bytes, default)?;
+                }
+                Instruction::Attribute(attribute_name, name) => {
+                    bytes.push(3);
+                    push_str(&mut bytes, attribute_name)?;
+                    push_str(&mut bytes, name)?;
+                }
+                Instruction::If(name, negate, if_end) => {
+                    bytes.push(4);
+                    push_str(&mut bytes, name)?;
+                    bytes.push(u8::from(*negate));
+                    push_u32(&mut bytes, *if_end)?;
+                }
+                Instruction::Repeat(name, repeat_end) => {
+                    bytes.push(5);
+                    push_str(&mut bytes, name)?;
+                    push_u32(&mut bytes, *repeat_end)?;
+                }
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Deserializes the instructions. The strings are borrowed from the bytes, nothing is parsed.
+    pub fn from_bytes(bytes: &'a [u8]) -> Result<CompiledTemplate<'a>, &'static str> {
+        let mut rest = bytes.strip_prefix(BYTES_HEADER).ok_or(ERR_BYTES_HEADER)?;
+        let count = take_u32(&mut rest)?;
+        let mut instructions = Vec::new();
+        for i in 0..count {
+            let (tag, tail) = rest.split_first().ok_or(ERR_BYTES_INVALID)?;
+            rest = tail;
+            let instruction = match tag {
+                0 => Instruction::Static(take_str(&mut rest)?),
+                1 => Instruction::Text(take_str(&mut rest)?, take_str(&mut rest)?),
+                2 => Instruction::AttributeValue(take_str(&mut rest)?, take_str(&mut rest)?),
+                3 => Instruction::Attribute(take_str(&mut rest)?, take_str(&mut rest)?),
+                4 => {
+                    let name = take_str(&mut rest)?;
+                    let (negate, tail) = rest.split_first().ok_or(ERR_BYTES_INVALID)?;
+                    rest = tail;
+                    Instruction::If(name, *negate != 0, take_end(&mut rest, i, count)?)
+                }
+                5 => {
+                    let name = take_str(&mut rest)?;
+                    Instruction::Repeat(name, take_end(&mut rest, i, count)?)
+                }
+                _ => return Err(ERR_BYTES_INVALID),
+            };
+            instructions.push(instruction);
+        }
+        if !rest.is_empty() {
+            return Err(ERR_BYTES_INVALID);
+        }
+        Ok(CompiledTemplate { instructions })
+    }
+
+    // endregion: bytes
+}
+
+/// collects the instructions and merges the static chunks
+#[derive(Default)]
+struct Compiler {
+    instructions: Vec<Instruction<'static>>,
+    static_chunk: String,
+}
+
+impl Compiler {
+    fn flush_static(&mut self) {
+        if !self.static_chunk.is_empty() {
+            let chunk = core::mem::take(&mut self.static_chunk);
+            self.instructions.push(Instruction::Static(Cow::Owned(chunk)));
+        }
+    }
+
+    fn push(&mut self, instruction: Instruction<'static>) {
+        self.flush_static();
+        self.instructions.push(instruction);
+    }
+
+    /// the index of the instruction after the conditional or repeated part is set later
+    fn set_end(&mut self, index: usize) {
+        self.flush_static();
+        let len = self.instructions.len();
+        if let Instruction::If(_, _, end) | Instruction::Repeat(_, end) = &mut self.instructions[index] {
+            *end = len;
+        }
+    }
+
+    fn compile_nodes(&mut self, nodes: &[Node]) -> fmt::Result {
+        let mut i = 0;
+        while i < nodes.len() {
+            match &nodes[i] {
+                Node::Element(element) => self.compile_element(element)?,
+                Node::Text(txt) => entities::write_escaped_text(&mut self.static_chunk, txt)?,
+                Node::Comment(txt) => match placeholder_name(txt) {
+                    Some(name) => {
+                        // without value the text of the template stays
+                        let mut default = String::new();
+                        if let Some(Node::Text(txt)) = nodes.get(i + 1) {
+                            i += 1;
+                            entities::write_escaped_text(&mut default, txt)?;
+                        }
+                        self.push(Instruction::Text(Cow::Owned(name.into()), Cow::Owned(default)));
+                    }
+                    None => {
+                        self.static_chunk.push_str("<!--");
+                        self.static_chunk.push_str(txt);
+                        self.static_chunk.push_str("-->");
+                    }
+                },
+            }
+            i += 1;
+        }
+        Ok(())
+    }
+
+    fn compile_element(&mut self, element: &Element) -> fmt::Result {
+        let repeat_index = element.attribute(ATTR_REPEAT).map(|name| {
+            self.push(Instruction::Repeat(Cow::Owned(name.into()), 0));
+            self.instructions.len() - 1
+        });
+        let if_index = element.attribute(ATTR_IF).map(|condition| {
+            let instruction = match condition.strip_prefix('!') {
+                Some(name) => Instruction::If(Cow::Owned(name.into()), true, 0),
+                None => Instruction::If(Cow::Owned(condition.into()), false, 0),
+            };
+            self.push(instruction);
+            self.instructions.len() - 1
+        });
+        self.static_chunk.push('<');
+        self.static_chunk.push_str(&element.name);
+        for attribute in element.attributes.iter() {
+            if attribute.name.starts_with(ATTR_PREFIX) {
+                continue;
+            }
+            self.static_chunk.push(' ');
+            self.static_chunk.push_str(&attribute.name);
+            self.static_chunk.push_str("=\"");
+            // the placeholder attribute data-t-href replaces the value of href
+            let placeholder = element
+                .attributes
+                .iter()
+                .find(|placeholder| placeholder.name.strip_prefix(ATTR_PREFIX) == Some(attribute.name.as_str()));
+            match placeholder {
+                Some(placeholder) => {
+                    let mut default = String::new();
+                    entities::write_escaped_attribute(&mut default, &attribute.value)?;
+                    self.push(Instruction::AttributeValue(
+                        Cow::Owned(placeholder.value.clone()),
+                        Cow::Owned(default),
+                    ));
+                }
+                None => entities::write_escaped_attribute(&mut self.static_chunk, &attribute.value)?,
+            }
+            self.static_chunk.push('"');
+        }
+        // the placeholder attribute without the attribute in the template adds it
+        for placeholder in element.attributes.iter() {
+            if let Some(name) = placeholder.name.strip_prefix(ATTR_PREFIX) {
+                if placeholder.name == ATTR_IF || placeholder.name == ATTR_REPEAT || element.attribute(name).is_some() {
+                    continue;
+                }
+                self.push(Instruction::Attribute(
+                    Cow::Owned(name.into()),
+                    Cow::Owned(placeholder.value.clone()),
+                ));
+            }
+        }
+        if element.nodes.is_empty() {
+            self.static_chunk.push_str("/>");
+        } else {
+            self.static_chunk.push('>');
+            self.compile_nodes(&element.nodes)?;
+            self.static_chunk.push_str("</");
+            self.static_chunk.push_str(&element.name);
+            self.static_chunk.push('>');
+        }
+        if let Some(index) = if_index {
+            self.set_end(index);
+        }
+        if let Some(index) = repeat_index {
+            self.set_end(index);
+        }
+        Ok(())
+    }
+}
+
+fn push_u32(bytes: &mut Vec<u8>, number: usize) -> Result<(), &'static str> {
+    let number = u32::try_from(number).map_err(|_| ERR_BYTES_TOO_BIG)?;
+    bytes.extend_from_slice(&number.to_le_bytes());
+    Ok(())
+}
+
+fn push_str(bytes: &mut Vec<u8>, txt: &str) -> Result<(), &'static str> {
+    push_u32(bytes, txt.len())?;
+    bytes.extend_from_slice(txt.as_bytes());
+    Ok(())
+}
+
+fn take_u32(rest: &mut &[u8]) -> Result<usize, &'static str> {
+    let (number, tail) = rest.split_first_chunk::<4>().ok_or(ERR_BYTES_INVALID)?;
+    *rest = tail;
+    Ok(u32::from_le_bytes(*number) as usize)
+}
+
+fn take_str<'a>(rest: &mut &'a [u8]) -> Result<Cow<'a, str>, &'static str> {
+    let len = take_u32(rest)?;
+    if len > rest.len() {
+        return Err(ERR_BYTES_INVALID);
+    }
+    let (txt, tail) = rest.split_at(len);
+    *rest = tail;
+    core::str::from_utf8(txt).map(Cow::Borrowed).map_err(|_| ERR_BYTES_INVALID)
+}
+
+/// the end index must be after the instruction, so the renderer always moves forward
+fn take_end(rest: &mut &[u8], index: usize, count: usize) -> Result<usize, &'static str> {
+    let end = take_u32(rest)?;
+    if end <= index || end > count {
+        return Err(ERR_BYTES_INVALID);
+    }
+    Ok(end)
+}
@@ -0,0 +1,127 @@
+//! tree_sink.rs - build any tree type from the reader
+//!
+//! The `build()` function drives the sink: wasm virtual-dom nodes, arena trees,
+//! or the crate's own `dom` with `DomSink`.
+
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+use crate::dom::{Attribute, Element, Node};
+use crate::whitespace::collapse_whitespace;
+use crate::{entities, ReaderForMicroXml, Token, WhitespacePolicy};
+
+/// Callbacks to build a tree.
+/// The child element is appended to its parent when it is complete (at its end tag),
+/// so also bottom-up builders like virtual-dom can be used.
+/// The text and comments are appended at once to the open parent.
+/// The parent None means the top-level of the fragment.
+pub trait TreeSink<'a> {
+    /// Reference to an element under construction.
+    type Handle;
+    /// The result of `finish()`.
+    type Output;
+    fn create_element(&mut self, name: &'a str) -> Self::Handle;
+    /// The value is "as is" from the reader, not decoded.
+    fn set_attribute(&mut self, element: &Self::Handle, name: &'a str, value: &'a str);
+    fn append_child(&mut self, parent: Option<&Self::Handle>, child: Self::Handle);
+    /// The text is "as is" from the reader, not decoded.
+    /// It is borrowed from the input, only `WhitespacePolicy::Collapse` can make a new string.
+    fn append_text(&mut self, parent: Option<&Self::Handle>, text: Cow<'a, str>);
+    fn append_comment(&mut self, parent: Option<&Self::Handle>, text: &'a str);
+    fn finish(self) -> Self::Output;
+}
+
+/// Reads all the tokens and drives the sink.
+/// Not closed elements are closed at the end of input.
+/// End elements without start element are ignored.
+/// The reader returns only sub-slices of the input, so the text is collapsed here
+/// if the reader has `WhitespacePolicy::Collapse`.
+pub fn build<'a, S: TreeSink<'a>>(reader_iterator: ReaderForMicroXml<'a>, mut sink: S) -> Result<S::Output, &'static str> {
+    let mut open_elements: Vec<S::Handle> = Vec::new();
+    let whitespace = reader_iterator.whitespace();
+    for result_token in reader_iterator {
+        match result_token? {
+            Token::StartElement(name) => {
+                let handle = sink.create_element(name);
+                open_elements.push(handle);
+            }
+            Token::Attribute(name, value) => {
+                if let Some(handle) = open_elements.last() {
+                    sink.set_attribute(handle, name, value);
+                }
+            }
+            Token::TextNode(txt) => {
+                let text = match whitespace {
+                    WhitespacePolicy::Collapse => collapse_whitespace(txt),
+                    _ => Cow::Borrowed(txt),
+                };
+                sink.append_text(open_elements.last(), text)
+            }
+            Token::Comment(txt) => sink.append_comment(open_elements.last(), txt),
+            Token::EndElement(_name) => {
+                if let Some(handle) = open_elements.pop() {
+                    sink.append_child(open_elements.last(), handle);
+                }
+            }
+        }
+    }
+    while let Some(handle) = open_elements.pop() {
+        sink.append_child(open_elements.last(), handle);
+    }
+    Ok(sink.finish())
+}
+
+/// Sink for the crate's own `dom`. The text and attribute values are decoded.
+#[derive(Debug, Default)]
+pub struct DomSink {
+    /// elements under construction, the handle is the index
+    open_elements: Vec<Element>,
+    /// top-level nodes
+    nodes: Vec<Node>,
+}
+
+impl DomSink {
+    fn push_node(&mut self, parent: Option<&usize>, node: Node) {
+        match parent {
+            Some(index) => self.open_elements[*index].nodes.push(node),
+            None => self.nodes.push(node),
+        }
+    }
+}
+
+impl<'a> TreeSink<'a> for DomSink {
+    type Handle = usize;
+    type Output = Vec<Node>;
+
+    fn create_element(&mut self, name: &'a str) -> usize {
+        self.open_elements.push(Element::new(name));
+        self.open_elements.len() - 1
+    }
+
+    fn set_attribute(&mut self, element: &usize, name: &'a str, value: &'a str) {
+        self.open_elements[*element].attributes.push(Attribute {
+            name: name.into(),
+            value: entities::decode(value).into_owned(),
+        });
+    }
+
+    fn append_child(&mut self, parent: Option<&usize>, child: usize) {
+        // build() appends the children in the reverse order of creation, so it is always the last one
+        debug_assert_eq!(child + 1, self.open_elements.len());
+        if let Some(element) = self.open_elements.pop() {
+            self.push_node(parent, Node::Element(element));
+        }
+    }
+
+    fn append_text(&mut self, parent: Option<&usize>, text: Cow<'a, str>) {
+        self.push_node(parent, Node::Text(entities::decode(&text).into_owned()));
+    }
+
+    fn append_comment(&mut self, parent: Option<&usize>, text: &'a str) {
+        self.push_node(parent, Node::Comment(text.into()));
+    }
+
+    fn finish(self) -> Vec<Node> {
+        self.nodes
+    }
+}
@@ -0,0 +1,80 @@
+//! whitespace.rs - policy for whitespace in text nodes
+
+#[cfg(feature = "alloc")]
+use alloc::{borrow::Cow, string::String};
+
+/// What the reader does with whitespace in `Token::TextNode`.
+/// For html templates the whitespace is significant, for data files it is mostly noise.
+/// The reader never allocates, so it can only return sub-slices of the input.
+/// The collapse of internal whitespace runs needs a new string,
+/// this is done with `apply()` or `collapse_whitespace()` with the feature `alloc`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WhitespacePolicy {
+    /// Text nodes exactly as in the source.
+    /// Also the whitespace-only text between tags is returned as a text node.
+    Preserve,
+    /// Text nodes exactly as in the source, but whitespace-only text is not returned.
+    /// This is the default and the behavior of the reader before this policy existed.
+    #[default]
+    DropWhitespaceOnly,
+    /// Leading and trailing whitespace is removed, whitespace-only text is not returned.
+    Trim,
+    /// Like `Trim` and then every run of internal whitespace is replaced by one space.
+    /// The reader returns the trimmed slice, `apply()` returns the collapsed value.
+    /// `tree_sink::build()` and `dom::read_nodes()` collapse the text of a reader with this policy.
+    Collapse,
+}
+
+impl WhitespacePolicy {
+    /// The part of the policy that is possible without allocation.
+    /// Returns None if the text node must not be returned.
+    pub fn apply_borrowed(self, text: &str) -> Option<&str> {
+        let is_whitespace_only = text.chars().all(char::is_whitespace);
+        match self {
+            WhitespacePolicy::Preserve => Some(text),
+            WhitespacePolicy::DropWhitespaceOnly if is_whitespace_only => None,
+            WhitespacePolicy::DropWhitespaceOnly => Some(text),
+            WhitespacePolicy::Trim | WhitespacePolicy::Collapse if is_whitespace_only => None,
+            WhitespacePolicy::Trim | WhitespacePolicy::Collapse => Some(text.trim()),
+        }
+    }
+
+    /// The complete policy. Returns None if the text node must be dropped.
+    /// The value is borrowed when there was nothing to change.
+    #[cfg(feature = "alloc")]
+    pub fn apply(self, text: &str) -> Option<Cow<'_, str>> {
+        let text = self.apply_borrowed(text)?;
+        match self {
+            WhitespacePolicy::Collapse => Some(collapse_whitespace(text)),
+            _ => Some(Cow::Borrowed(text)),
+        }
+    }
+}
+
+/// Trims the text and replaces every run of whitespace with one space.
+/// Allocates a new string only if there is something to collapse.
+#[cfg(feature = "alloc")]
+pub fn collapse_whitespace(text: &str) -> Cow<'_, str> {
+    let text = text.trim();
+    let mut previous_is_whitespace = false;
+    let is_collapsed = text.chars().all(|ch| {
+        let ok = if ch.is_whitespace() {
+            ch == ' ' && !previous_is_whitespace
+        } else {
+            true
+        };
+        previous_is_whitespace = ch.is_whitespace();
+        ok
+    });
+    if is_collapsed {
+        return Cow::Borrowed(text);
+    }
+    let mut collapsed = String::with_capacity(text.len());
+    for word in text.split_whitespace() {
+        if !collapsed.is_empty() {
+            collapsed.push(' ');
+        }
+        collapsed.push_str(word);
+    }
+    Cow::Owned(collapsed)
+}
@@ -0,0 +1,352 @@
+//! xml.rs - lenient front-end for full XML
+//!
+//! `XmlReader` reads regular XML and returns the normal `Token` stream of this crate:
+//! - the XML declaration, processing instructions and DOCTYPE (also with internal subset) are skipped
+//! - CDATA sections are returned as `TextNode` with the content "as is", see `is_cdata()`
+//! - single-quoted attribute values and whitespace around `=` are accepted
+//! - namespace declarations `xmlns` and `xmlns:p` are dropped, the prefixes are handled by the `NamespacePolicy`
+//!
+//! Entities declared in the DOCTYPE are not expanded. Whitespace-only text is dropped, like the default reader does.
+//! `convert()` writes the result as MicroXml.
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+use core::fmt;
+
+use crate::{entities, Token};
+
+pub const ERR_NOT_CLOSED_COMMENT: &str = "Error: Comment is not closed with -->.";
+pub const ERR_NOT_CLOSED_CDATA: &str = "Error: CDATA is not closed with ]]>.";
+pub const ERR_NOT_CLOSED_PI: &str = "Error: Processing instruction is not closed with ?>.";
+pub const ERR_NOT_CLOSED_DOCTYPE: &str = "Error: DOCTYPE is not closed with >.";
+pub const ERR_NOT_CLOSED_TAG: &str = "Error: Tag is not closed with >.";
+pub const ERR_NOT_CLOSED_ATTRIBUTE_VALUE: &str = "Error: Attribute value is not closed with the quote.";
+pub const ERR_ATTRIBUTE_WITHOUT_VALUE: &str = "Error: Attribute does not have = and a quoted value.";
+pub const ERR_EMPTY_NAME: &str = "Error: Name is empty.";
+pub const ERR_WRITE: &str = "Error: XML conversion write failed.";
+
+/// What to do with the namespace prefixes like `svg:rect`.
+/// The prefix `xml:` of attributes like `xml:lang` is always stripped.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum NamespacePolicy {
+    /// `svg:rect` becomes `rect`
+    #[default]
+    StripPrefix,
+    /// elements with a prefix are dropped with all their content, attributes with a prefix are dropped
+    DropPrefixed,
+    /// the tokens have the qualified name `svg:rect`, `convert()` writes it as `svg-rect`
+    Flatten,
+}
+
+/// Lenient XML reader that returns the tokens of this crate.
+pub struct XmlReader<'a> {
+    input: &'a str,
+    pos: usize,
+    namespace_policy: NamespacePolicy,
+    /// after the start element name, reading the attributes
+    inside_start_tag: bool,
+    /// count of open elements that are dropped with their content
+    dropped_depth: usize,
+    /// the last TextNode was a CDATA section
+    is_cdata: bool,
+    /// after the end of input or after an error
+    is_finished: bool,
+}
+
+impl<'a> XmlReader<'a> {
+    /// Constructor. No allocation.
+    pub fn new(input: &'a str) -> XmlReader<'a> {
+        XmlReader {
+            input,
+            pos: 0,
+            namespace_policy: NamespacePolicy::default(),
+            inside_start_tag: false,
+            dropped_depth: 0,
+            is_cdata: false,
+            is_finished: false,
+        }
+    }
+
+    /// Sets the policy for the namespace prefixes. The default is `StripPrefix`.
+    pub fn with_namespace_policy(mut self, namespace_policy: NamespacePolicy) -> Self {
+        self.namespace_policy = namespace_policy;
+        self
+    }
+
+    /// True if the last `TextNode` was a CDATA section. Its content is not escaped:
+    /// `&amp;` inside CDATA is literally `&amp;`, not `&`.
+    pub fn is_cdata(&self) -> bool {
+        self.is_cdata
+    }
+
+    /// Current byte position in the input.
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
+    fn rest(&self) -> &'a str {
+        &self.input[self.pos..]
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start().len();
+    }
+
+    /// moves after the delimiter and returns the content before it
+    fn read_until(&mut self, delimiter: &str, err_msg: &'static str) -> Result<&'a str, &'static str> {
+        let rest = self.rest();
+        let end = rest.find(delimiter).ok_or(err_msg)?;
+        self.pos += end + delimiter.len();
+        Ok(&rest[..end])
+    }
+
+    fn read_name(&mut self) -> Result<&'a str, &'static str> {
+        let rest = self.rest();
+        let end = rest
+            .find(|ch: char| ch.is_whitespace() || "=/>\"'<".contains(ch))
+            .unwrap_or(rest.len());
+        if end == 0 {
+            return Err(ERR_EMPTY_NAME);
+        }
+        self.pos += end;
+        Ok(&rest[..end])
+    }
+
+    /// the DOCTYPE can have an internal subset in `[ ]` with `>` inside
+    fn skip_doctype(&mut self) -> Result<(), &'static str> {
+        let mut is_inside_subset = false;
+        for (i, ch) in self.rest().char_indices() {
+            match ch {
+                '[' => is_inside_subset = true,
+                ']' => is_inside_subset = false,
+                '>' if !is_inside_subset => {
+                    self.pos += i + 1;
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        Err(ERR_NOT_CLOSED_DOCTYPE)
+    }
+
+    /// the name after the namespace policy, None if it is dropped
+    fn element_name(&self, name: &'a str) -> Option<&'a str> {
+        match (name.split_once(':'), self.namespace_policy) {
+            (None, _) | (Some(_), NamespacePolicy::Flatten) => Some(name),
+            (Some((_prefix, local_name)), NamespacePolicy::StripPrefix) => Some(local_name),
+            (Some(_), NamespacePolicy::DropPrefixed) => None,
+        }
+    }
+
+    /// the attribute name after the namespace policy, None if it is dropped
+    fn attribute_name(&self, name: &'a str) -> Option<&'a str> {
+        if name == "xmlns" || name.starts_with("xmlns:") {
+            return None;
+        }
+        if let Some(local_name) = name.strip_prefix("xml:") {
+            return Some(local_name);
+        }
+        self.element_name(name)
+    }
+
+    /// next token inside the start tag: attribute or the end of the tag
+    fn next_inside_start_tag(&mut self) -> Result<Option<Token<'a>>, &'static str> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        if rest.starts_with("/>") {
+            self.pos += 2;
+            self.inside_start_tag = false;
+            if self.dropped_depth > 0 {
+                self.dropped_depth -= 1;
+                return Ok(None);
+            }
+            return Ok(Some(Token::EndElement("")));
+        }
+        if rest.starts_with('>') {
+            self.pos += 1;
+            self.inside_start_tag = false;
+            return Ok(None);
+        }
+        if rest.is_empty() {
+            return Err(ERR_NOT_CLOSED_TAG);
+        }
+        let name = self.read_name()?;
+        self.skip_whitespace();
+        if !self.rest().starts_with('=') {
+            return Err(ERR_ATTRIBUTE_WITHOUT_VALUE);
+        }
+        self.pos += 1;
+        self.skip_whitespace();
+        let quote = match self.rest().chars().next() {
+            Some(quote @ ('"' | '\'')) => quote,
+            _ => return Err(ERR_ATTRIBUTE_WITHOUT_VALUE),
+        };
+        self.pos += 1;
+        let value = self.read_until(if quote == '"' { "\"" } else { "'" }, ERR_NOT_CLOSED_ATTRIBUTE_VALUE)?;
+        if self.dropped_depth > 0 {
+            return Ok(None);
+        }
+        Ok(self.attribute_name(name).map(|name| Token::Attribute(name, value)))
+    }
+
+    /// next token outside of tags, None if it is skipped
+    fn next_outside_of_tag(&mut self) -> Result<Option<Token<'a>>, &'static str> {
+        let rest = self.rest();
+        self.is_cdata = false;
+        if let Some(after) = rest.strip_prefix("<!--") {
+            self.pos += rest.len() - after.len();
+            let comment = self.read_until("-->", ERR_NOT_CLOSED_COMMENT)?;
+            return Ok(self.is_visible().then_some(Token::Comment(comment)));
+        }
+        if let Some(after) = rest.strip_prefix("<![CDATA[") {
+            self.pos += rest.len() - after.len();
+            let text = self.read_until("]]>", ERR_NOT_CLOSED_CDATA)?;
+            if !self.is_visible() || text.trim().is_empty() {
+                return Ok(None);
+            }
+            self.is_cdata = true;
+            return Ok(Some(Token::TextNode(text)));
+        }
+        if rest.starts_with("<?") {
+            self.read_until("?>", ERR_NOT_CLOSED_PI)?;
+            return Ok(None);
+        }
+        if rest.starts_with("<!") {
+            self.skip_doctype()?;
+            return Ok(None);
+        }
+        if let Some(after) = rest.strip_prefix("</") {
+            self.pos += rest.len() - after.len();
+            let name = self.read_until(">", ERR_NOT_CLOSED_TAG)?.trim();
+            if self.dropped_depth > 0 {
+                self.dropped_depth -= 1;
+                return Ok(None);
+            }
+            // a prefixed end tag here is not balanced, it keeps its name
+            return Ok(Some(Token::EndElement(self.element_name(name).unwrap_or(name))));
+        }
+        if rest.starts_with('<') {
+            self.pos += 1;
+            let name = self.read_name()?;
+            self.inside_start_tag = true;
+            match self.element_name(name) {
+                Some(name) if self.is_visible() => return Ok(Some(Token::StartElement(name))),
+                _ => {
+                    self.dropped_depth += 1;
+                    return Ok(None);
+                }
+            }
+        }
+        let end = rest.find('<').unwrap_or(rest.len());
+        self.pos += end;
+        let text = &rest[..end];
+        if !self.is_visible() || text.trim().is_empty() {
+            return Ok(None);
+        }
+        Ok(Some(Token::TextNode(text)))
+    }
+
+    fn is_visible(&self) -> bool {
+        self.dropped_depth == 0
+    }
+}
+
+impl<'a> Iterator for XmlReader<'a> {
+    type Item = Result<Token<'a>, &'static str>;
+    /// Reads the next token. After an error the iteration ends.
+    fn next(&mut self) -> Option<Result<Token<'a>, &'static str>> {
+        while !self.is_finished {
+            if !self.inside_start_tag && self.pos >= self.input.len() {
+                self.is_finished = true;
+                return None;
+            }
+            let result = if self.inside_start_tag {
+                self.next_inside_start_tag()
+            } else {
+                self.next_outside_of_tag()
+            };
+            match result {
+                Ok(Some(token)) => return Some(Ok(token)),
+                Ok(None) => {}
+                Err(err_msg) => {
+                    self.is_finished = true;
+                    return Some(Err(err_msg));
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Reads the XML and writes MicroXml.
+/// CDATA content is escaped, the qualified names of the policy `Flatten` are written with `-` instead of `:`.
+pub fn convert<W: fmt::Write>(input: &str, namespace_policy: NamespacePolicy, w: &mut W) -> Result<(), &'static str> {
+    let mut xml_reader = XmlReader::new(input).with_namespace_policy(namespace_policy);
+    // the start tag is closed when the next token comes: `/>` if it is the end element, else `>`
+    let mut start_tag_open = false;
+    while let Some(result_token) = xml_reader.next() {
+        let token = result_token?;
+        if start_tag_open && !matches!(token, Token::Attribute(..)) {
+            start_tag_open = false;
+            if let Token::EndElement("") = token {
+                w.write_str("/>").map_err(|_| ERR_WRITE)?;
+                continue;
+            }
+            w.write_char('>').map_err(|_| ERR_WRITE)?;
+        }
+        match token {
+            Token::StartElement(name) => {
+                start_tag_open = true;
+                w.write_char('<').and_then(|()| write_name(w, name))
+            }
+            Token::Attribute(name, value) => w
+                .write_char(' ')
+                .and_then(|()| write_name(w, name))
+                .and_then(|()| w.write_str("=\""))
+                .and_then(|()| write_attribute_value(w, value))
+                .and_then(|()| w.write_char('"')),
+            Token::TextNode(txt) if xml_reader.is_cdata() => entities::write_escaped_text(w, txt),
+            Token::TextNode(txt) => w.write_str(txt),
+            Token::Comment(txt) => write!(w, "<!--{}-->", txt),
+            Token::EndElement(name) => w
+                .write_str("</")
+                .and_then(|()| write_name(w, name))
+                .and_then(|()| w.write_char('>')),
+        }
+        .map_err(|_| ERR_WRITE)?;
+    }
+    if start_tag_open {
+        w.write_str("/>").map_err(|_| ERR_WRITE)?;
+    }
+    Ok(())
+}
+
+/// Reads the XML and returns MicroXml.
+#[cfg(feature = "alloc")]
+pub fn to_microxml(input: &str, namespace_policy: NamespacePolicy) -> Result<String, &'static str> {
+    let mut text = String::new();
+    convert(input, namespace_policy, &mut text)?;
+    Ok(text)
+}
+
+fn write_name<W: fmt::Write>(w: &mut W, name: &str) -> fmt::Result {
+    for part in name.split(':').enumerate() {
+        if part.0 > 0 {
+            w.write_char('-')?;
+        }
+        w.write_str(part.1)?;
+    }
+    Ok(())
+}
+
+/// single-quoted values can contain `"`
+fn write_attribute_value<W: fmt::Write>(w: &mut W, value: &str) -> fmt::Result {
+    for (i, part) in value.split('"').enumerate() {
+        if i > 0 {
+            w.write_str("&quot;")?;
+        }
+        w.write_str(part)?;
+    }
+    Ok(())
+}
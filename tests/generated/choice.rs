@@ -0,0 +1,119 @@
+// generated by reader_for_microxml::codegen from the schema, do not edit
+
+#[allow(unused_imports)]
+use reader_for_microxml::codegen::{Child, Cursor};
+#[allow(unused_imports)]
+use reader_for_microxml::entities;
+#[allow(unused_imports)]
+use reader_for_microxml::schema::{self, Datatype, Violation};
+
+/// element `r`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct R {
+    /// element `a`
+    pub a: Option<A>,
+    /// element `b`
+    pub b: Option<B>,
+    /// element `c`
+    pub c: C,
+}
+
+fn read_r(cursor: &mut Cursor, pos: usize) -> Result<R, Violation> {
+    if let Some((name, _value, value_pos)) = cursor.attribute()? {
+        return Err(Cursor::violation(value_pos, schema::ERR_UNDECLARED_ATTRIBUTE, name));
+    }
+    let mut a = None;
+    let mut b = None;
+    let mut c = None;
+    loop {
+        match cursor.child()? {
+            Child::Element("a", child_pos) => {
+                let value = read_a(cursor, child_pos)?;
+                Cursor::set_once(&mut a, value, child_pos, "a")?
+            }
+            Child::Element("b", child_pos) => {
+                let value = read_b(cursor, child_pos)?;
+                Cursor::set_once(&mut b, value, child_pos, "b")?
+            }
+            Child::Element("c", child_pos) => {
+                let value = read_c(cursor, child_pos)?;
+                Cursor::set_once(&mut c, value, child_pos, "c")?
+            }
+            Child::Element(name, child_pos) => return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, name)),
+            Child::Text(_txt, txt_pos) => return Err(Cursor::violation(txt_pos, schema::ERR_TEXT_NOT_ALLOWED, "r")),
+            Child::End => break,
+        }
+    }
+    Ok(R {
+        a,
+        b,
+        c: c.ok_or_else(|| Cursor::violation(pos, schema::ERR_CONTENT_NOT_COMPLETE, "c"))?,
+    })
+}
+
+/// element `a`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct A {}
+
+fn read_a(cursor: &mut Cursor, _pos: usize) -> Result<A, Violation> {
+    if let Some((name, _value, value_pos)) = cursor.attribute()? {
+        return Err(Cursor::violation(value_pos, schema::ERR_UNDECLARED_ATTRIBUTE, name));
+    }
+    match cursor.child()? {
+        Child::Element(name, child_pos) => return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, name)),
+        Child::Text(_txt, txt_pos) => return Err(Cursor::violation(txt_pos, schema::ERR_TEXT_NOT_ALLOWED, "a")),
+        Child::End => {}
+    }
+    Ok(A {})
+}
+
+/// element `b`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct B {}
+
+fn read_b(cursor: &mut Cursor, _pos: usize) -> Result<B, Violation> {
+    if let Some((name, _value, value_pos)) = cursor.attribute()? {
+        return Err(Cursor::violation(value_pos, schema::ERR_UNDECLARED_ATTRIBUTE, name));
+    }
+    match cursor.child()? {
+        Child::Element(name, child_pos) => return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, name)),
+        Child::Text(_txt, txt_pos) => return Err(Cursor::violation(txt_pos, schema::ERR_TEXT_NOT_ALLOWED, "b")),
+        Child::End => {}
+    }
+    Ok(B {})
+}
+
+/// element `c`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct C {}
+
+fn read_c(cursor: &mut Cursor, _pos: usize) -> Result<C, Violation> {
+    if let Some((name, _value, value_pos)) = cursor.attribute()? {
+        return Err(Cursor::violation(value_pos, schema::ERR_UNDECLARED_ATTRIBUTE, name));
+    }
+    match cursor.child()? {
+        Child::Element(name, child_pos) => return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, name)),
+        Child::Text(_txt, txt_pos) => return Err(Cursor::violation(txt_pos, schema::ERR_TEXT_NOT_ALLOWED, "c")),
+        Child::End => {}
+    }
+    Ok(C {})
+}
+
+/// the schema for the validation before the typed reading
+const SCHEMA: &str = "\
+start = r
+r = { (a | b) c }
+a = {}
+b = {}
+c = {}
+";
+
+/// Parses the document with the root element `r`.
+pub fn parse_r(input: &str) -> Result<R, Violation> {
+    Cursor::validate(SCHEMA, input)?;
+    let cursor = &mut Cursor::new(input);
+    let pos = cursor.root("r")?;
+    let value = read_r(cursor, pos)?;
+    cursor.finish()?;
+    Ok(value)
+}
@@ -0,0 +1,195 @@
+// generated by reader_for_microxml::codegen from the schema, do not edit
+
+#[allow(unused_imports)]
+use reader_for_microxml::codegen::{Child, Cursor};
+#[allow(unused_imports)]
+use reader_for_microxml::entities;
+#[allow(unused_imports)]
+use reader_for_microxml::schema::{self, Datatype, Violation};
+
+/// element `library`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Library {
+    /// attribute `version`
+    pub version: Option<f64>,
+    /// attribute `public`
+    pub public: Option<bool>,
+    /// element `book`
+    pub book: Vec<Book>,
+    /// element `note`
+    pub note: Option<Note>,
+}
+
+fn read_library(cursor: &mut Cursor, _pos: usize) -> Result<Library, Violation> {
+    let mut version = None;
+    let mut public = None;
+    while let Some((name, value, value_pos)) = cursor.attribute()? {
+        match name {
+            "version" => version = Some(Cursor::attribute_value(value, Datatype::Decimal, value_pos, name)?),
+            "public" => public = Some(Cursor::attribute_value(value, Datatype::Boolean, value_pos, name)?),
+            _ => return Err(Cursor::violation(value_pos, schema::ERR_UNDECLARED_ATTRIBUTE, name)),
+        }
+    }
+    let mut book = Vec::new();
+    let mut note = None;
+    loop {
+        match cursor.child()? {
+            Child::Element("book", child_pos) => book.push(read_book(cursor, child_pos)?),
+            Child::Element("note", child_pos) => {
+                let value = read_note(cursor, child_pos)?;
+                Cursor::set_once(&mut note, value, child_pos, "note")?
+            }
+            Child::Element(name, child_pos) => return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, name)),
+            Child::Text(_txt, txt_pos) => return Err(Cursor::violation(txt_pos, schema::ERR_TEXT_NOT_ALLOWED, "library")),
+            Child::End => break,
+        }
+    }
+    Ok(Library {
+        version,
+        public,
+        book,
+        note,
+    })
+}
+
+/// element `book`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Book {
+    /// attribute `id`
+    pub id: String,
+    /// attribute `year`
+    pub year: Option<i64>,
+    /// element `title`
+    pub title: String,
+    /// element `author`
+    pub author: Vec<String>,
+    /// element `isbn`
+    pub isbn: Option<String>,
+    /// element `issn`
+    pub issn: Option<String>,
+}
+
+fn read_book(cursor: &mut Cursor, pos: usize) -> Result<Book, Violation> {
+    let mut id = None;
+    let mut year = None;
+    while let Some((name, value, value_pos)) = cursor.attribute()? {
+        match name {
+            "id" => id = Some(Cursor::attribute_value(value, Datatype::Text, value_pos, name)?),
+            "year" => year = Some(Cursor::attribute_value(value, Datatype::Integer, value_pos, name)?),
+            _ => return Err(Cursor::violation(value_pos, schema::ERR_UNDECLARED_ATTRIBUTE, name)),
+        }
+    }
+    let mut title = None;
+    let mut author = Vec::new();
+    let mut isbn = None;
+    let mut issn = None;
+    loop {
+        match cursor.child()? {
+            Child::Element("title", child_pos) => {
+                let value = cursor.text_value(Datatype::Text, child_pos, "title")?;
+                Cursor::set_once(&mut title, value, child_pos, "title")?
+            }
+            Child::Element("author", child_pos) => author.push(cursor.text_value(Datatype::Text, child_pos, "author")?),
+            Child::Element("isbn", child_pos) => {
+                let value = cursor.text_value(Datatype::Token, child_pos, "isbn")?;
+                Cursor::set_once(&mut isbn, value, child_pos, "isbn")?
+            }
+            Child::Element("issn", child_pos) => {
+                let value = cursor.text_value(Datatype::Token, child_pos, "issn")?;
+                Cursor::set_once(&mut issn, value, child_pos, "issn")?
+            }
+            Child::Element(name, child_pos) => return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, name)),
+            Child::Text(_txt, txt_pos) => return Err(Cursor::violation(txt_pos, schema::ERR_TEXT_NOT_ALLOWED, "book")),
+            Child::End => break,
+        }
+    }
+    Ok(Book {
+        id: id.ok_or_else(|| Cursor::violation(pos, schema::ERR_MISSING_ATTRIBUTE, "id"))?,
+        year,
+        title: title.ok_or_else(|| Cursor::violation(pos, schema::ERR_CONTENT_NOT_COMPLETE, "title"))?,
+        author: Cursor::at_least(author, 1, pos, "author")?,
+        isbn,
+        issn,
+    })
+}
+
+/// element `note`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Note {
+    /// attribute `type`
+    pub type_: Option<String>,
+    /// element `em`
+    pub em: Vec<String>,
+    /// element `br`
+    pub br: Vec<Br>,
+    /// mixed text
+    pub text: String,
+}
+
+fn read_note(cursor: &mut Cursor, _pos: usize) -> Result<Note, Violation> {
+    let mut type_ = None;
+    while let Some((name, value, value_pos)) = cursor.attribute()? {
+        match name {
+            "type" => type_ = Some(Cursor::attribute_value(value, Datatype::Text, value_pos, name)?),
+            _ => return Err(Cursor::violation(value_pos, schema::ERR_UNDECLARED_ATTRIBUTE, name)),
+        }
+    }
+    let mut em = Vec::new();
+    let mut br = Vec::new();
+    let mut text = String::new();
+    loop {
+        match cursor.child()? {
+            Child::Element("em", child_pos) => em.push(cursor.text_value(Datatype::Text, child_pos, "em")?),
+            Child::Element("br", child_pos) => br.push(read_br(cursor, child_pos)?),
+            Child::Element(name, child_pos) => return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, name)),
+            Child::Text(txt, _txt_pos) => text.push_str(&entities::decode(txt)),
+            Child::End => break,
+        }
+    }
+    Ok(Note {
+        type_,
+        em,
+        br,
+        text,
+    })
+}
+
+/// element `br`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Br {}
+
+fn read_br(cursor: &mut Cursor, _pos: usize) -> Result<Br, Violation> {
+    if let Some((name, _value, value_pos)) = cursor.attribute()? {
+        return Err(Cursor::violation(value_pos, schema::ERR_UNDECLARED_ATTRIBUTE, name));
+    }
+    match cursor.child()? {
+        Child::Element(name, child_pos) => return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, name)),
+        Child::Text(_txt, txt_pos) => return Err(Cursor::violation(txt_pos, schema::ERR_TEXT_NOT_ALLOWED, "br")),
+        Child::End => {}
+    }
+    Ok(Br {})
+}
+
+/// the schema for the validation before the typed reading
+const SCHEMA: &str = "\
+start = library
+library = { @version:decimal? @public:boolean? book* note? }
+book = { @id @year:integer? title author+ (isbn | issn)? }
+title = text
+author = text
+isbn = token
+issn = token
+note = { @type? text em* br* }
+em = text
+br = {}
+";
+
+/// Parses the document with the root element `library`.
+pub fn parse_library(input: &str) -> Result<Library, Violation> {
+    Cursor::validate(SCHEMA, input)?;
+    let cursor = &mut Cursor::new(input);
+    let pos = cursor.root("library")?;
+    let value = read_library(cursor, pos)?;
+    cursor.finish()?;
+    Ok(value)
+}
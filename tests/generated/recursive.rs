@@ -0,0 +1,131 @@
+// generated by reader_for_microxml::codegen from the schema, do not edit
+
+#[allow(unused_imports)]
+use reader_for_microxml::codegen::{Child, Cursor};
+#[allow(unused_imports)]
+use reader_for_microxml::entities;
+#[allow(unused_imports)]
+use reader_for_microxml::schema::{self, Datatype, Violation};
+
+/// element `doc`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Doc {
+    /// element `section`
+    pub section: Vec<Section>,
+}
+
+fn read_doc(cursor: &mut Cursor, _pos: usize) -> Result<Doc, Violation> {
+    if let Some((name, _value, value_pos)) = cursor.attribute()? {
+        return Err(Cursor::violation(value_pos, schema::ERR_UNDECLARED_ATTRIBUTE, name));
+    }
+    let mut section = Vec::new();
+    loop {
+        match cursor.child()? {
+            Child::Element("section", child_pos) => section.push(read_section(cursor, child_pos)?),
+            Child::Element(name, child_pos) => return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, name)),
+            Child::Text(_txt, txt_pos) => return Err(Cursor::violation(txt_pos, schema::ERR_TEXT_NOT_ALLOWED, "doc")),
+            Child::End => break,
+        }
+    }
+    Ok(Doc {
+        section,
+    })
+}
+
+/// element `section`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Section {
+    /// attribute `id`
+    pub id: Option<String>,
+    /// element `title`
+    pub title: String,
+    /// element `section`
+    pub section: Option<Box<Section>>,
+    /// element `part`
+    pub part: Option<Box<Part>>,
+}
+
+fn read_section(cursor: &mut Cursor, pos: usize) -> Result<Section, Violation> {
+    let mut id = None;
+    while let Some((name, value, value_pos)) = cursor.attribute()? {
+        match name {
+            "id" => id = Some(Cursor::attribute_value(value, Datatype::Text, value_pos, name)?),
+            _ => return Err(Cursor::violation(value_pos, schema::ERR_UNDECLARED_ATTRIBUTE, name)),
+        }
+    }
+    let mut title = None;
+    let mut section = None;
+    let mut part = None;
+    loop {
+        match cursor.child()? {
+            Child::Element("title", child_pos) => {
+                let value = cursor.text_value(Datatype::Text, child_pos, "title")?;
+                Cursor::set_once(&mut title, value, child_pos, "title")?
+            }
+            Child::Element("section", child_pos) => {
+                let value = Box::new(read_section(cursor, child_pos)?);
+                Cursor::set_once(&mut section, value, child_pos, "section")?
+            }
+            Child::Element("part", child_pos) => {
+                let value = Box::new(read_part(cursor, child_pos)?);
+                Cursor::set_once(&mut part, value, child_pos, "part")?
+            }
+            Child::Element(name, child_pos) => return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, name)),
+            Child::Text(_txt, txt_pos) => return Err(Cursor::violation(txt_pos, schema::ERR_TEXT_NOT_ALLOWED, "section")),
+            Child::End => break,
+        }
+    }
+    Ok(Section {
+        id,
+        title: title.ok_or_else(|| Cursor::violation(pos, schema::ERR_CONTENT_NOT_COMPLETE, "title"))?,
+        section,
+        part,
+    })
+}
+
+/// element `part`
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Part {
+    /// element `section`
+    pub section: Box<Section>,
+}
+
+fn read_part(cursor: &mut Cursor, pos: usize) -> Result<Part, Violation> {
+    if let Some((name, _value, value_pos)) = cursor.attribute()? {
+        return Err(Cursor::violation(value_pos, schema::ERR_UNDECLARED_ATTRIBUTE, name));
+    }
+    let mut section = None;
+    loop {
+        match cursor.child()? {
+            Child::Element("section", child_pos) => {
+                let value = Box::new(read_section(cursor, child_pos)?);
+                Cursor::set_once(&mut section, value, child_pos, "section")?
+            }
+            Child::Element(name, child_pos) => return Err(Cursor::violation(child_pos, schema::ERR_ELEMENT_NOT_ALLOWED, name)),
+            Child::Text(_txt, txt_pos) => return Err(Cursor::violation(txt_pos, schema::ERR_TEXT_NOT_ALLOWED, "part")),
+            Child::End => break,
+        }
+    }
+    Ok(Part {
+        section: section.ok_or_else(|| Cursor::violation(pos, schema::ERR_CONTENT_NOT_COMPLETE, "section"))?,
+    })
+}
+
+/// the schema for the validation before the typed reading
+const SCHEMA: &str = "\
+start = doc
+doc = { section* }
+section = { @id? title section? part? }
+part = { section }
+title = text
+";
+
+/// Parses the document with the root element `doc`.
+pub fn parse_doc(input: &str) -> Result<Doc, Violation> {
+    Cursor::validate(SCHEMA, input)?;
+    let cursor = &mut Cursor::new(input);
+    let pos = cursor.root("doc")?;
+    let value = read_doc(cursor, pos)?;
+    cursor.finish()?;
+    Ok(value)
+}
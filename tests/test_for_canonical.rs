@@ -0,0 +1,39 @@
+//! test_for_canonical
+#![cfg(feature = "alloc")]
+use reader_for_microxml::canonical::{canonical_eq, canonical_hash, to_canonical_string};
+
+#[test]
+/// sorted attributes, decoded and minimally escaped, fixed empty element form
+fn test_01() {
+    let result = to_canonical_string("<a z=\"&#x31;\" b=\"x&apos;&gt;\">\n  <c/><!--comment-->t&#65;\r\nu&gt;</a>").unwrap();
+    assert_eq!(result, "<a b=\"x'&gt;\" z=\"1\"><c></c>tA\nu&gt;</a>");
+}
+
+#[test]
+/// semantically identical inputs have the same canonical form and hash
+fn test_02() {
+    let input_1 = "<p class=\"c\" id=\"1\">Tom &amp; Jerry<br/></p>";
+    let input_2 = "<p  id=\"&#49;\" class=\"c\" >Tom &#38; Jerry<!-- x --><br></br></p>";
+    assert!(canonical_eq(input_1, input_2).unwrap());
+    assert_eq!(canonical_hash(input_1).unwrap(), canonical_hash(input_2).unwrap());
+    assert!(!canonical_eq(input_1, "<p class=\"c\" id=\"2\">Tom &amp; Jerry<br/></p>").unwrap());
+    assert_ne!(canonical_hash(input_1).unwrap(), canonical_hash("<p/>").unwrap());
+}
+
+#[test]
+/// the hash is stable: FNV-1a of the canonical bytes
+fn test_03() {
+    // the FNV-1a hash of the empty string is the offset basis
+    assert_eq!(canonical_hash("").unwrap(), 0xcbf2_9ce4_8422_2325);
+    assert_eq!(canonical_hash("<a/>").unwrap(), canonical_hash("<a></a>").unwrap());
+}
+
+#[test]
+/// the decoded carriage return is escaped, not closed elements are closed
+fn test_04() {
+    assert_eq!(
+        to_canonical_string("<a x=\"&#13;\">&#13;<b>").unwrap(),
+        "<a x=\"&#xD;\">&#xD;<b></b></a>"
+    );
+    assert!(to_canonical_string("<a x=1/>").is_err());
+}
@@ -0,0 +1,65 @@
+//! test_for_cli
+//! run with `cargo test --features cli`
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// runs the binary with the input on stdin, returns exit code and stdout
+fn run(args: &[&str], input: &str) -> (i32, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_microxml"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .unwrap();
+    child.stdin.take().unwrap().write_all(input.as_bytes()).unwrap();
+    let output = child.wait_with_output().unwrap();
+    (output.status.code().unwrap(), String::from_utf8(output.stdout).unwrap())
+}
+
+#[test]
+/// check prints positioned errors with snippet and exit code 1
+fn test_01() {
+    assert_eq!(run(&["check"], "<a><b/></a>"), (0, String::new()));
+    let (code, output) = run(&["check"], "<a>\n<b x=1></b>\n</a>");
+    assert_eq!(code, 1);
+    assert!(output.starts_with("-:2:"));
+    assert!(output.contains("    <b x=1></b>\n"));
+}
+
+#[test]
+/// fmt, minify, query and to-json
+fn test_02() {
+    let input = "<a x=\"1\"> <b>t</b><!--c--></a>";
+    assert_eq!(run(&["fmt"], input), (0, "<a x=\"1\">\n  <b>t</b>\n  <!--c-->\n</a>\n".to_string()));
+    assert_eq!(run(&["fmt", "--check"], input).0, 1);
+    assert_eq!(run(&["minify"], input), (0, "<a x=\"1\"> <b>t</b></a>\n".to_string()));
+    assert_eq!(run(&["query", "a > b"], input), (0, "<b>t</b>\n".to_string()));
+    assert_eq!(run(&["query", "i"], input).0, 1);
+    assert_eq!(
+        run(&["to-json"], input),
+        (0, "[\"a\", {\"x\":\"1\"}, [[\"b\", {}, [\"t\"]]]]\n".to_string())
+    );
+}
+
+#[test]
+/// wrong usage has exit code 2
+fn test_03() {
+    assert_eq!(run(&[], "").0, 2);
+    assert_eq!(run(&["unknown"], "").0, 2);
+    assert_eq!(run(&["query", "["], "").0, 2);
+    assert_eq!(run(&["tokens", "missing_file.xml"], "").0, 2);
+}
+
+#[test]
+/// the input that ends inside of a comment or attribute value fails the check
+fn test_04() {
+    let (code, output) = run(&["check"], "<!-- x");
+    assert_eq!(code, 1);
+    assert!(output.starts_with("-:1:1: Error: The input ends inside of the comment."));
+    let (code, output) = run(&["check"], "<a x=\"1");
+    assert_eq!(code, 1);
+    assert!(output.starts_with("-:1:6: Error: The input ends inside of the attribute value."));
+}
@@ -0,0 +1,196 @@
+//! test_for_codegen
+//! The generated code is in `tests/generated/library.rs`, `recursive.rs` and `choice.rs`. After a change of the generator,
+//! write them again with the output of `codegen::generate()` for the `.schema` files in `tests/generated/`.
+#![cfg(feature = "alloc")]
+use reader_for_microxml::codegen;
+use reader_for_microxml::schema::{self, Schema};
+
+mod library {
+    include!("generated/library.rs");
+}
+use library::{parse_library, Book, Br, Library, Note};
+
+mod recursive {
+    include!("generated/recursive.rs");
+}
+
+mod choice {
+    include!("generated/choice.rs");
+}
+
+#[test]
+/// the generated code is the same as the included file
+fn test_01() {
+    let schema = Schema::parse(include_str!("generated/library.schema")).unwrap();
+    assert_eq!(codegen::generate(&schema), include_str!("generated/library.rs"));
+}
+
+#[test]
+/// typed values, optional and repeated fields, mixed text
+fn test_02() {
+    let input = r#"<library version="1.5" public="true">
+  <book id="a &amp; b" year="2020"><title>T</title><author>A</author><author>B</author><isbn>123</isbn></book>
+  <!-- comment -->
+  <book id="c"><title/><author>C</author></book>
+  <note type="x">Hello <em>you</em><br/>!</note>
+</library>"#;
+    assert_eq!(
+        parse_library(input).unwrap(),
+        Library {
+            version: Some(1.5),
+            public: Some(true),
+            book: vec![
+                Book {
+                    id: "a & b".to_string(),
+                    year: Some(2020),
+                    title: "T".to_string(),
+                    author: vec!["A".to_string(), "B".to_string()],
+                    isbn: Some("123".to_string()),
+                    issn: None,
+                },
+                Book {
+                    id: "c".to_string(),
+                    year: None,
+                    title: String::new(),
+                    author: vec!["C".to_string()],
+                    isbn: None,
+                    issn: None,
+                },
+            ],
+            note: Some(Note {
+                type_: Some("x".to_string()),
+                em: vec!["you".to_string()],
+                br: vec![Br {}],
+                text: "Hello !".to_string(),
+            }),
+        }
+    );
+    assert_eq!(parse_library("<library/>").unwrap(), Library::default());
+}
+
+#[test]
+/// errors with the position
+fn test_03() {
+    let cases = [
+        ("<book/>", schema::ERR_ROOT_NOT_START, "book", 1),
+        ("<library x=\"1\"/>", schema::ERR_UNDECLARED_ATTRIBUTE, "x", 9),
+        ("<library version=\"v\"/>", schema::ERR_ATTRIBUTE_DATATYPE, "version", 9),
+        (
+            "<library><book><title/><author/></book></library>",
+            schema::ERR_MISSING_ATTRIBUTE,
+            "id",
+            10,
+        ),
+        (
+            "<library><book id=\"1\"><title/></book></library>",
+            schema::ERR_CONTENT_NOT_COMPLETE,
+            "book",
+            32,
+        ),
+        (
+            "<library><book id=\"1\"><title/><title/></book></library>",
+            schema::ERR_ELEMENT_NOT_ALLOWED,
+            "title",
+            31,
+        ),
+        (
+            "<library><book id=\"1\" year=\"x\"/></library>",
+            schema::ERR_ATTRIBUTE_DATATYPE,
+            "year",
+            22,
+        ),
+        (
+            "<library><book id=\"1\"><title/><author/><isbn>1 2</isbn></book></library>",
+            schema::ERR_TEXT_DATATYPE,
+            "isbn",
+            50,
+        ),
+        ("<library>text</library>", schema::ERR_TEXT_NOT_ALLOWED, "library", 9),
+        ("<library><title/></library>", schema::ERR_ELEMENT_NOT_ALLOWED, "title", 10),
+        ("<library/><library/>", schema::ERR_ELEMENT_NOT_ALLOWED, "library", 11),
+        ("", schema::ERR_NO_ROOT, "", 0),
+    ];
+    for (input, message, name, pos) in cases.iter() {
+        let violation = parse_library(input).unwrap_err();
+        assert_eq!(
+            (violation.message, violation.name.as_str(), violation.pos),
+            (*message, *name, *pos),
+            "{}",
+            input
+        );
+    }
+}
+
+#[test]
+/// names to Rust identifiers, cardinality from the content model, schema without start
+fn test_04() {
+    let schema = Schema::parse("my-list = { @type (a | b)+ a? c } a = {} b = integer c = {}").unwrap();
+    let code = codegen::generate(&schema);
+    assert!(code.contains("pub struct MyList {"));
+    assert!(code.contains("    pub type_: String,"));
+    assert!(code.contains("    pub a: Vec<A>,"));
+    assert!(code.contains("    pub b: Vec<i64>,"));
+    assert!(code.contains("    pub c: C,"));
+    assert!(code.contains("pub fn parse_my_list(input: &str) -> Result<MyList, Violation> {"));
+    assert!(code.contains("pub fn parse_b(input: &str) -> Result<i64, Violation> {"));
+}
+
+#[test]
+/// the recursive struct fields are boxed
+fn test_05() {
+    use recursive::{parse_doc, Part, Section};
+    let schema = Schema::parse(include_str!("generated/recursive.schema")).unwrap();
+    assert_eq!(codegen::generate(&schema), include_str!("generated/recursive.rs"));
+    let input = "<doc><section id=\"1\"><title>A</title><section><title>B</title></section></section>\
+<section><title>C</title><part><section><title>D</title></section></part></section></doc>";
+    let doc = parse_doc(input).unwrap();
+    assert_eq!(doc.section.len(), 2);
+    assert_eq!(doc.section[0].section.as_ref().unwrap().title, "B");
+    assert_eq!(
+        doc.section[1].part,
+        Some(Box::new(Part {
+            section: Box::new(Section {
+                title: "D".to_string(),
+                ..Section::default()
+            }),
+        }))
+    );
+    let violation = parse_doc("<doc><section><title/><part/></section></doc>").unwrap_err();
+    assert_eq!((violation.message, violation.pos), (schema::ERR_CONTENT_NOT_COMPLETE, 29));
+}
+
+#[test]
+/// the leading and trailing whitespace of the typed values is not significant, like in the validator
+fn test_06() {
+    let input = "<library version=\" 1.5 \"><book id=\" a \" year=\"2020\n\"><title> T </title><author/><isbn> 1 </isbn></book></library>";
+    let library = parse_library(input).unwrap();
+    assert_eq!(library.version, Some(1.5));
+    assert_eq!(library.book[0].year, Some(2020));
+    assert_eq!(library.book[0].isbn.as_deref(), Some("1"));
+    // text is kept as it is
+    assert_eq!((library.book[0].id.as_str(), library.book[0].title.as_str()), (" a ", " T "));
+    let schema = Schema::parse(include_str!("generated/library.schema")).unwrap();
+    assert!(schema.validate(input).is_empty());
+}
+
+#[test]
+/// the generated parse function and the validator agree on the choice and the order of the child elements
+fn test_07() {
+    let schema = Schema::parse(include_str!("generated/choice.schema")).unwrap();
+    assert_eq!(codegen::generate(&schema), include_str!("generated/choice.rs"));
+    let inputs = [
+        "<r><a/><c/></r>",
+        "<r><b/><c/></r>",
+        "<r/>",
+        "<r><c/></r>",
+        "<r><a/><b/><c/></r>",
+        "<r><c/><a/></r>",
+    ];
+    for input in inputs.iter() {
+        let violation = choice::parse_r(input).err();
+        assert_eq!(violation, schema.validate(input).into_iter().next(), "{}", input);
+    }
+    assert!(choice::parse_r("<r><a/><c/></r>").unwrap().a.is_some());
+    assert!(choice::parse_r("<r/>").is_err());
+    assert!(choice::parse_r("<r><c/><a/></r>").is_err());
+}
@@ -0,0 +1,212 @@
+//! test_for_cst
+#![cfg(feature = "alloc")]
+use reader_for_microxml::cst::{self, Cst, CstError, Node};
+use reader_for_microxml::ReaderForMicroXml;
+
+#[test]
+/// printing the CST reproduces the source byte-for-byte
+fn test_01() {
+    let sources = [
+        "",
+        "  \n ",
+        "<a/>",
+        "\n<a  x = \"say 'hi'\"\ty=\"&amp;\" >\n  <br />  text &lt; \r\n<!-- c --> <b\n/></ a >\n",
+        "text<a><b>x</b>  <c/></a>tail",
+        "<html><body><p>not closed\n",
+        "<a>x</b> </c>\n",
+        "< a x \"1\"y=\"2\"/>< /a/ >< !xx c -->",
+        include_str!("../examples/t1.html"),
+        include_str!("../examples/t3.xml"),
+        include_str!("../examples/text_nodes.html"),
+    ];
+    for source in sources.iter() {
+        let cst = Cst::parse(source).unwrap();
+        assert_eq!(cst.to_string(), *source);
+    }
+}
+
+#[test]
+/// trivia and ranges
+fn test_02() {
+    let source = "<a>\n  <b x = \"v\"/>\n  <!--c-->\n</a>\n";
+    let cst = Cst::parse(source).unwrap();
+    assert_eq!(cst.trailing_trivia, "\n");
+    let a = match &cst.nodes[..] {
+        [Node::Element(a)] => a,
+        _ => panic!("one element"),
+    };
+    assert_eq!(a.range, 0..source.len() - 1);
+    assert_eq!(a.trailing_trivia, "\n");
+    assert_eq!(&source[a.end_tag.as_ref().unwrap().range.clone()], "</a>");
+    let b = match &a.nodes[..] {
+        [Node::Element(b), Node::Comment(comment)] => {
+            assert_eq!(comment.leading_trivia, "\n  ");
+            assert_eq!(&source[comment.range.clone()], "<!--c-->");
+            b
+        }
+        _ => panic!("element and comment"),
+    };
+    assert_eq!(b.leading_trivia, "\n  ");
+    assert_eq!(b.start_tag.close, "/>");
+    assert!(b.end_tag.is_none());
+    let x = b.attribute("x").unwrap();
+    assert_eq!((x.before_equals, x.after_equals, x.raw_value), (" ", " ", "v"));
+    assert_eq!(&source[x.range.clone()], "x = \"v\"");
+    assert_eq!(&source[x.value_range.clone()], "v");
+    assert_eq!(&source[b.start_tag.name_range.clone()], "b");
+}
+
+#[test]
+/// text keeps its whitespace, only whitespace-only text is trivia
+fn test_03() {
+    let source = "<p> a <i>b</i> </p>";
+    let cst = Cst::parse(source).unwrap();
+    let p = match &cst.nodes[0] {
+        Node::Element(p) => p,
+        _ => panic!("element"),
+    };
+    assert_eq!(p.nodes.len(), 2);
+    assert!(matches!(&p.nodes[0], Node::Text(text) if text.text == " a " && text.range == (3..6)));
+    assert_eq!(p.nodes[1].range(), 6..14);
+    assert_eq!(p.trailing_trivia, " ");
+}
+
+#[test]
+/// errors with position
+fn test_04() {
+    let cases = [
+        (
+            "<a x='1'/>",
+            CstError {
+                pos: 5,
+                message: cst::ERR_ATTRIBUTE_NOT_QUOTED,
+            },
+        ),
+        (
+            "<a x>",
+            CstError {
+                pos: 3,
+                message: cst::ERR_NOT_CLOSED_TAG,
+            },
+        ),
+        (
+            "<a x=1>",
+            CstError {
+                pos: 5,
+                message: cst::ERR_ATTRIBUTE_NOT_QUOTED,
+            },
+        ),
+        (
+            "<a x=\"1>",
+            CstError {
+                pos: 6,
+                message: cst::ERR_NOT_CLOSED_ATTRIBUTE_VALUE,
+            },
+        ),
+        (
+            "<a/ x>",
+            CstError {
+                pos: 2,
+                message: cst::ERR_NOT_CLOSED_TAG,
+            },
+        ),
+        (
+            "<!-- c",
+            CstError {
+                pos: 4,
+                message: cst::ERR_NOT_CLOSED_COMMENT,
+            },
+        ),
+        (
+            "<a<b>",
+            CstError {
+                pos: 2,
+                message: cst::ERR_NAME_HAS_LESS_THAN,
+            },
+        ),
+        (
+            "< >",
+            CstError {
+                pos: 2,
+                message: cst::ERR_EMPTY_NAME,
+            },
+        ),
+    ];
+    for (source, err) in cases.iter() {
+        assert_eq!(Cst::parse(source).unwrap_err(), *err, "{}", source);
+    }
+}
+
+#[test]
+/// the end tag closes the open element like in the reader, the end tag without start tag is kept
+fn test_05() {
+    let source = "<a>x</b> </c>";
+    let cst = Cst::parse(source).unwrap();
+    match &cst.nodes[..] {
+        [Node::Element(a), Node::StrayEndTag(stray)] => {
+            assert_eq!(a.end_tag.as_ref().unwrap().name, "b");
+            assert_eq!((stray.leading_trivia, stray.end_tag.name), (" ", "c"));
+        }
+        _ => panic!("element and stray end tag"),
+    }
+    assert_eq!(cst.nodes[1].range(), 9..13);
+}
+
+#[test]
+/// the CST and the reader accept the same sources
+fn test_06() {
+    let sources = [
+        "<a></b>",
+        "</a>",
+        "<a x='1'/>",
+        "<a x=\"1\"y=\"2\"/>",
+        "<a\"/>",
+        "<a b=\"<\"/>",
+        "<a>1 > 2 &amp; & ]]></a>",
+        "<a><b/ ></a>",
+        "<a/ >",
+        "<a/ x>",
+        "<a/b>",
+        "<a x>",
+        "<a x=1>",
+        "<a x=\"1>",
+        "<a x = >",
+        "<a></a b>",
+        "<a></>",
+        "< >",
+        "<<<>>>",
+        "<!-- c",
+        "<!---->",
+        "text<",
+        "<a",
+        "< /a>",
+        "<a>x< /a>",
+        "<a x \"1\"/>",
+        "<a x=\"1\"=\"2\"/>",
+        "<a=b/>",
+        "<a\"b\"/>",
+        "</a/>",
+        "<a x/=\"1\"></a>",
+        "<a x>=\"1\"></a>",
+        "<a x/>",
+        "<a x />",
+        "<a<b>",
+        "<a x<=\"1\"/>",
+        "</a<>",
+        "</ >",
+        "< ",
+        "<!x-->",
+        "<!xx-->",
+        "< !-- c -->",
+        "<!-->",
+        "<!--",
+        "<!-- a -- b -->",
+        "<!\u{e9}\u{e9} c -->",
+        "<a> </a > x",
+        "<a x=\"\u{e9}\"/>",
+    ];
+    for source in sources.iter() {
+        let reader_is_ok = ReaderForMicroXml::new(source).all(|result_token| result_token.is_ok());
+        assert_eq!(Cst::parse(source).is_ok(), reader_is_ok, "{}", source);
+    }
+}
@@ -0,0 +1,220 @@
+//! test_for_derive
+//! run with `cargo test --features derive`
+#![cfg(feature = "derive")]
+use reader_for_microxml::extract::{self, FromMicroXml};
+use reader_for_microxml::schema;
+use std::borrow::Cow;
+
+#[derive(FromMicroXml, Debug, PartialEq)]
+struct Library<'a> {
+    #[mx(attr = "version")]
+    version: Option<f32>,
+    #[mx(children = "book")]
+    books: Vec<Book<'a>>,
+    #[mx(child = "note")]
+    note: Option<Note<'a>>,
+}
+
+#[derive(FromMicroXml, Debug, PartialEq)]
+struct Book<'a> {
+    #[mx(attr = "id")]
+    id: &'a str,
+    #[mx(attr = "year")]
+    year: Option<u32>,
+    #[mx(child = "title")]
+    title: Cow<'a, str>,
+    #[mx(children = "author")]
+    authors: Vec<&'a str>,
+    /// fields without `mx` get the default
+    is_read: bool,
+}
+
+#[derive(FromMicroXml, Debug, PartialEq)]
+struct Note<'a> {
+    #[mx(attr = "type")]
+    r#type: &'a str,
+    #[mx(text)]
+    text: Cow<'a, str>,
+}
+
+/// without lifetime, with owned values
+#[derive(FromMicroXml, Debug, PartialEq)]
+pub struct Item {
+    #[mx(attr = "count")]
+    pub count: i64,
+    #[mx(child = "name")]
+    pub name: String,
+    #[mx(child = "available")]
+    pub available: Option<bool>,
+    #[mx(children = "tag")]
+    pub tags: Vec<Tag>,
+}
+
+#[derive(FromMicroXml, Debug, PartialEq)]
+pub struct Tag {
+    #[mx(text)]
+    pub text: String,
+}
+
+#[test]
+/// attributes, children and text as slices of the input
+fn test_01() {
+    let input = r#"<library version="1.5">
+  <book id="a1" year="2020" lang="en"><title>T</title><author>A</author><!-- c --><author>B</author><isbn>1</isbn></book>
+  <book id="b2"><title/></book>
+  <note type="x">Hello <em>you</em>!</note>
+</library>"#;
+    let library: Library = extract::from_str(input, "library").unwrap();
+    assert_eq!(
+        library,
+        Library {
+            version: Some(1.5),
+            books: vec![
+                Book {
+                    id: "a1",
+                    year: Some(2020),
+                    title: Cow::Borrowed("T"),
+                    authors: vec!["A", "B"],
+                    is_read: false,
+                },
+                Book {
+                    id: "b2",
+                    year: None,
+                    title: Cow::Borrowed(""),
+                    authors: vec![],
+                    is_read: false,
+                },
+            ],
+            note: Some(Note {
+                r#type: "x",
+                text: Cow::Owned("Hello !".to_string()),
+            }),
+        }
+    );
+    // zero-copy
+    let input_range = input.as_ptr() as usize..input.as_ptr() as usize + input.len();
+    assert!(input_range.contains(&(library.books[0].id.as_ptr() as usize)));
+    assert!(input_range.contains(&(library.books[0].authors[1].as_ptr() as usize)));
+    assert!(matches!(library.books[0].title, Cow::Borrowed(_)));
+}
+
+#[test]
+/// owned values, references are decoded
+fn test_02() {
+    let input = "<item count=\"-3\"><tag>a &amp; b</tag><name>N&#x41;</name><available>true</available><tag>c</tag></item>";
+    assert_eq!(
+        extract::from_str::<Item>(input, "item").unwrap(),
+        Item {
+            count: -3,
+            name: "NA".to_string(),
+            available: Some(true),
+            tags: vec![Tag { text: "a & b".to_string() }, Tag { text: "c".to_string() },],
+        }
+    );
+    let note: Note = extract::from_str("<note type=\"t\">x &lt; y</note>", "note").unwrap();
+    assert!(matches!(note.text, Cow::Owned(text) if text == "x < y"));
+}
+
+/// the error of `from_str` as `message name pos`
+fn error<'a, T: FromMicroXml<'a> + std::fmt::Debug>(input: &'a str, root: &'a str) -> (&'static str, String, usize) {
+    let violation = extract::from_str::<T>(input, root).unwrap_err();
+    (violation.message, violation.name, violation.pos)
+}
+
+#[test]
+/// errors with the position
+fn test_03() {
+    assert_eq!(
+        error::<Item>("<item><name>N</name></item>", "item"),
+        (schema::ERR_MISSING_ATTRIBUTE, "count".to_string(), 1)
+    );
+    assert_eq!(
+        error::<Item>("<item count=\"1\"/>", "item"),
+        (schema::ERR_CONTENT_NOT_COMPLETE, "name".to_string(), 1)
+    );
+    assert_eq!(
+        error::<Item>("<item count=\"x\"><name/></item>", "item"),
+        (extract::ERR_VALUE, "count".to_string(), 6)
+    );
+    assert_eq!(
+        error::<Item>("<item count=\"1\"><name/><name/></item>", "item"),
+        (schema::ERR_ELEMENT_NOT_ALLOWED, "name".to_string(), 24)
+    );
+    assert_eq!(
+        error::<Item>("<item count=\"1\"><name>a<b/></name></item>", "item"),
+        (schema::ERR_ELEMENT_NOT_ALLOWED, "b".to_string(), 24)
+    );
+    assert_eq!(
+        error::<Book>("<book id=\"a&amp;b\"><title/></book>", "book"),
+        (extract::ERR_NOT_BORROWED, "id".to_string(), 6)
+    );
+    assert_eq!(
+        error::<Item>("<book/>", "item"),
+        (schema::ERR_ROOT_NOT_START, "book".to_string(), 1)
+    );
+    assert_eq!(error::<Item>("", "item"), (schema::ERR_NO_ROOT, String::new(), 0));
+}
+
+#[test]
+/// the text of a child element as a value, unknown elements are skipped
+fn test_04() {
+    assert_eq!(extract::from_str::<u8>("<a>42</a>", "a"), Ok(42));
+    assert_eq!(extract::from_str::<&str>("<a x=\"1\">text</a>", "a"), Ok("text"));
+    assert_eq!(
+        extract::from_str::<Tag>("<tag><x><y>skipped</y></x>one<z/> two</tag>", "tag").unwrap(),
+        Tag {
+            text: "one two".to_string()
+        }
+    );
+    assert_eq!(
+        extract::from_str::<&str>("<a>one<!-- c -->two</a>", "a").unwrap_err().message,
+        extract::ERR_NOT_BORROWED
+    );
+}
+
+/// optional text
+#[derive(FromMicroXml, Debug, PartialEq)]
+struct Entry<'a> {
+    #[mx(attr = "n")]
+    n: u8,
+    #[mx(child = "title")]
+    title: Option<Title<'a>>,
+    #[mx(children = "count")]
+    counts: Vec<u32>,
+}
+
+#[derive(FromMicroXml, Debug, PartialEq)]
+struct Title<'a> {
+    #[mx(text)]
+    text: Option<&'a str>,
+}
+
+#[derive(FromMicroXml, Debug, PartialEq)]
+struct Owned {
+    #[mx(text)]
+    text: Option<String>,
+}
+
+#[test]
+/// Option text is None without text, the numbers are trimmed like the schema datatypes
+fn test_05() {
+    let entry: Entry = extract::from_str("<entry n=\" 1\"><title/><count>\n 2\n</count></entry>", "entry").unwrap();
+    assert_eq!(
+        entry,
+        Entry {
+            n: 1,
+            title: Some(Title { text: None }),
+            counts: vec![2],
+        }
+    );
+    let title: Title = extract::from_str("<title> T </title>", "title").unwrap();
+    assert_eq!(title.text, Some(" T "));
+    let owned: Owned = extract::from_str("<owned>a &amp; <!--c-->b</owned>", "owned").unwrap();
+    assert_eq!(owned.text.as_deref(), Some("a & b"));
+    let owned: Owned = extract::from_str("<owned><!--c--></owned>", "owned").unwrap();
+    assert_eq!(owned.text, None);
+    assert_eq!(
+        error::<Title>("<title>a &amp; b</title>", "title"),
+        (extract::ERR_NOT_BORROWED, "title".to_string(), 1)
+    );
+}
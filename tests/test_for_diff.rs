@@ -0,0 +1,124 @@
+//! test_for_diff
+#![cfg(feature = "alloc")]
+use reader_for_microxml::diff::{describe_path, diff, Edit};
+use reader_for_microxml::dom::{self, nodes_to_string};
+
+/// the patch applied to the old nodes gives the new nodes
+fn assert_patch(old: &str, new: &str) -> String {
+    let mut old_nodes = dom::parse_fragment(old).unwrap();
+    let new_nodes = dom::parse_fragment(new).unwrap();
+    let patch = diff(&old_nodes, &new_nodes);
+    patch.apply(&mut old_nodes).unwrap();
+    assert_eq!(nodes_to_string(&old_nodes), nodes_to_string(&new_nodes));
+    patch.to_string()
+}
+
+#[test]
+/// equal trees have an empty patch, the order of attributes is not significant
+fn test_01() {
+    let old = dom::parse_fragment(r#"<a x="1" y="2"><b>t</b></a>"#).unwrap();
+    let new = dom::parse_fragment(r#"<a y="2" x="1"><b>t</b></a>"#).unwrap();
+    assert!(diff(&old, &new).is_empty());
+}
+
+#[test]
+/// text edits, attribute changes, insertions and deletions
+fn test_02() {
+    let report = assert_patch(
+        r#"<ul class="a"><li>one</li><li>two</li><!--c--><p/></ul>"#,
+        r#"<ul class="b" id="x"><li>one</li><li>2</li><li>three</li></ul>"#,
+    );
+    assert_eq!(
+        report,
+        r#"attribute /0 class: "a" -> "b"
+attribute /0 id: "x"
+delete /0/3 <p/>
+delete /0/2 <!--c-->
+insert /0/2 <li>three</li>
+text /0/1/0: "two" -> "2"
+"#
+    );
+}
+
+#[test]
+/// moves of elements with id
+fn test_03() {
+    let report = assert_patch(
+        r#"<r><i id="1"/><i id="2"/><i id="3"/><i id="4"/></r>"#,
+        r#"<r><i id="4"/><i id="1"/><i id="3"/><i id="2" x="y"/></r>"#,
+    );
+    assert_eq!(
+        report,
+        r#"move /0/3 /0/0
+move /0/2 /0/3
+attribute /0/3 x: "y"
+"#
+    );
+}
+
+#[test]
+/// readable paths, remove attribute, bigger restructuring
+fn test_04() {
+    let old = dom::parse_fragment(r#"<html><body><p>a</p><p x="1">b</p></body></html>"#).unwrap();
+    assert_eq!(describe_path(&old, &[0, 0, 1]).unwrap(), "/html[1]/body[1]/p[2]");
+    assert_eq!(describe_path(&old, &[0, 0, 1, 0]).unwrap(), "/html[1]/body[1]/p[2]/text()[1]");
+    assert_eq!(describe_path(&old, &[0, 5]), None);
+    let old = dom::parse_fragment(r#"<a x="1" y="2"/>"#).unwrap();
+    let new = dom::parse_fragment(r#"<a y="2"/>"#).unwrap();
+    let patch = diff(&old, &new);
+    assert!(matches!(&patch.edits[..], [Edit::RemoveAttribute { name, .. }] if name == "x"));
+    assert_patch(
+        r#"<html><body><p>a</p><p x="1">b</p></body></html>"#,
+        r#"<html><head/><body><p>b</p><div><p>a</p></div></body></html>"#,
+    );
+    assert_patch("text<a/>", "<b/>other text<!--c-->");
+}
+
+#[test]
+/// moves, insertions and deletions mixed in the same list
+fn test_05() {
+    let cases = [
+        (r#"<i id="1"/><i id="2"/><i id="3"/>"#, r#"<i id="3"/><i id="2"/><i id="1"/>"#),
+        (
+            r#"<i id="1"/><i id="2"/><i id="3"/><i id="4"/>"#,
+            r#"<i id="2"/><x/><i id="4"/><i id="1"/>"#,
+        ),
+        (r#"<a/><b/><c/><d/><e/>"#, r#"<e/><d/>t<c/><b/><a/>"#),
+        (r#"<a><b><c/></b></a><d/>"#, r#"<d/><a><c/><b>x</b></a>"#),
+        (r#"<i id="5"/>x<i id="6"/>"#, r#"y<i id="6"/><i id="7"/><i id="5"/>"#),
+    ];
+    for (old, new) in cases.iter() {
+        assert_patch(old, new);
+    }
+}
+
+#[test]
+/// a patch with wrong paths is an error and does not panic
+fn test_06() {
+    use reader_for_microxml::diff::{Patch, ERR_PATCH_PATH};
+    let mut nodes = dom::parse_fragment("<a><b/></a>").unwrap();
+    let paths: [(Vec<usize>, Vec<usize>); 5] = [
+        (vec![], vec![]),
+        (vec![0], vec![]),
+        (vec![], vec![0]),
+        (vec![0, 0], vec![0]),
+        (vec![0, 1], vec![0, 0]),
+    ];
+    for (from, to) in paths.iter() {
+        let patch = Patch {
+            edits: vec![Edit::Move {
+                from: from.clone(),
+                to: to.clone(),
+            }],
+        };
+        assert_eq!(patch.apply(&mut nodes), Err(ERR_PATCH_PATH));
+    }
+    let patch = Patch {
+        edits: vec![Edit::Delete {
+            path: vec![],
+            node: dom::Node::Text("x".into()),
+        }],
+    };
+    assert_eq!(patch.apply(&mut nodes), Err(ERR_PATCH_PATH));
+    assert_eq!(nodes_to_string(&nodes), "<a><b/></a>");
+}
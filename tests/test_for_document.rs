@@ -0,0 +1,74 @@
+//! test_for_document
+use reader_for_microxml::*;
+
+/// read all tokens and return the first error or None
+fn first_error(reader_iterator: ReaderForMicroXml) -> Option<&'static str> {
+    for result_token in reader_iterator {
+        if let Err(err_msg) = result_token {
+            return Some(err_msg);
+        }
+    }
+    None
+}
+
+#[test]
+/// one root element with whitespace and comments around it
+fn test_01() {
+    let str_xml = "\n<!-- before -->\n<memo lang=\"en\">I <em>love</em> it</memo>\n<!-- after -->\n";
+    let tokens: Vec<_> = ReaderForMicroXml::new_document(str_xml).map(Result::unwrap).collect();
+    assert_eq!(tokens.len(), 10);
+    assert!(matches!(tokens[0], Token::Comment(" before ")));
+    assert!(matches!(tokens[9], Token::Comment(" after ")));
+}
+
+#[test]
+/// the whitespace outside of the root is not returned, even with Preserve
+fn test_02() {
+    let str_xml = "  <a> x </a>  ";
+    let tokens: Vec<_> = ReaderForMicroXml::new_document(str_xml)
+        .with_whitespace(WhitespacePolicy::Preserve)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(tokens.len(), 3);
+    assert!(matches!(tokens[1], Token::TextNode(" x ")));
+}
+
+#[test]
+fn test_03() {
+    let cases = [
+        ("test", "Error: Document has text outside of the root element."),
+        ("this<html>test</html>", "Error: Document has text outside of the root element."),
+        ("<html>test</html>trailing", "Error: Document has text outside of the root element."),
+        ("<html>test</html><xml>two</xml>", "Error: Document has more than one root element."),
+        ("", "Error: Document has no root element."),
+        ("<!-- only comment -->", "Error: Document has no root element."),
+        ("<html><div>test</div>", "Error: Document ends before the root element is closed."),
+        ("</html>", "Error: End element without start element."),
+    ];
+    for (str_xml, expected) in cases.iter() {
+        assert_eq!(
+            first_error(ReaderForMicroXml::new_document(str_xml)),
+            Some(*expected),
+            "{}",
+            str_xml
+        );
+    }
+}
+
+#[test]
+/// the fragment accepts all of that, like new()
+fn test_04() {
+    for str_xml in ["test", "<html>test</html><xml>two</xml>", "", "<html><div>test</div>"].iter() {
+        assert_eq!(first_error(ReaderForMicroXml::new_fragment(str_xml)), None);
+    }
+}
+
+#[test]
+/// after a document error the reader stops
+fn test_05() {
+    let mut reader_iterator = ReaderForMicroXml::new_document("<a/><b/><c/>");
+    assert!(reader_iterator.next().unwrap().is_ok());
+    assert!(reader_iterator.next().unwrap().is_ok());
+    assert!(reader_iterator.next().unwrap().is_err());
+    assert!(reader_iterator.next().is_none());
+}
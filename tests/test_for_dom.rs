@@ -0,0 +1,78 @@
+//! test_for_dom
+#![cfg(feature = "alloc")]
+use reader_for_microxml::dom::{self, Element, Node};
+use reader_for_microxml::entities;
+
+#[test]
+/// parse and write again, the text is decoded and escaped again
+fn test_01() {
+    let str_xml = r#"<memo lang="en" title="&quot;x&quot;">I <em>love</em> it &amp; more<br/><!-- c --></memo>"#;
+    let root = dom::parse_document(str_xml).unwrap();
+    assert_eq!(root.attribute("title"), Some(r#""x""#));
+    assert_eq!(root.text(), "I love it & more");
+    assert_eq!(root.to_string(), str_xml);
+}
+
+#[test]
+/// attributes editing
+fn test_02() {
+    let mut root = dom::parse_document(r#"<a href="/x" class="c"/>"#).unwrap();
+    root.set_attribute("href", "/y?a=1&b=2");
+    root.set_attribute("target", "_blank");
+    assert_eq!(root.remove_attribute("class"), Some("c".to_string()));
+    assert_eq!(root.remove_attribute("class"), None);
+    assert_eq!(root.to_string(), r#"<a href="/y?a=1&amp;b=2" target="_blank"/>"#);
+}
+
+#[test]
+/// children editing: insert, remove, replace, wrap, move a subtree
+fn test_03() {
+    let mut root = dom::parse_document("<ul><li>1</li><li>2</li><li>3</li></ul>").unwrap();
+    root.insert_child(0, Node::Comment("list".to_string()));
+    assert!(root.remove_child(1));
+    let old = root.replace_child(2, Node::Element(Element::new("hr"))).unwrap();
+    assert_eq!(old.to_string(), "<li>3</li>");
+    assert!(root.wrap_child(0, Element::new("b")));
+    assert!(!root.wrap_child(9, Element::new("b")));
+    assert_eq!(root.to_string(), "<ul><b><!--list--></b><li>2</li><hr/></ul>");
+    // move the subtree to the end
+    let li = root.detach_child(1).unwrap();
+    root.append_child(li);
+    assert_eq!(root.to_string(), "<ul><b><!--list--></b><hr/><li>2</li></ul>");
+    assert!(root.detach_child(9).is_none());
+}
+
+#[test]
+/// remove comments in all descendants and edit text
+fn test_04() {
+    let mut nodes = dom::parse_fragment("<!--a--><p>x<!--b--><i>y<!--c--></i></p>").unwrap();
+    for node in nodes.iter_mut() {
+        if let Some(element) = node.as_element_mut() {
+            element.remove_comments();
+            element.elements_mut().next().unwrap().set_text("1 < 2");
+        }
+    }
+    assert_eq!(dom::nodes_to_string(&nodes), "<!--a--><p>x<i>1 &lt; 2</i></p>");
+}
+
+#[test]
+/// entities
+fn test_05() {
+    assert_eq!(entities::decode("a &lt;b&gt; &#65;&#x42; &unknown; & c"), "a <b> AB &unknown; & c");
+    let mut text = String::new();
+    entities::write_escaped_attribute(&mut text, r#"<"&">"#).unwrap();
+    assert_eq!(text, "&lt;&quot;&amp;&quot;&gt;");
+}
+
+#[test]
+/// unknown references stay as they are after parse and write
+fn test_06() {
+    let input = r#"<p title="&copy; 2020">a&nbsp;b &amp;nbsp; &#xZZ; &amp; &x y;</p>"#;
+    let nodes = dom::parse_fragment(input).unwrap();
+    let output = dom::nodes_to_string(&nodes);
+    assert_eq!(output, r#"<p title="&copy; 2020">a&nbsp;b &nbsp; &#xZZ; &amp; &amp;x y;</p>"#);
+    assert_eq!(dom::parse_fragment(&output).unwrap(), nodes);
+    let mut text = String::new();
+    entities::write_escaped_text(&mut text, "&lt; &copy;").unwrap();
+    assert_eq!(text, "&amp;lt; &copy;");
+}
@@ -0,0 +1,109 @@
+//! test_for_edit
+#![cfg(feature = "alloc")]
+use reader_for_microxml::edit::{self, span_of, Editor, TextEdit};
+use reader_for_microxml::{ReaderForMicroXml, Token};
+
+const SOURCE: &str = "<list  class = \"a\">\n    <item id=\"1\">one</item>\n    <item\n      id=\"2\"/>\n</list>\n";
+
+/// the slice of the first token that matches
+fn token_slice<'a>(source: &'a str, matches: impl Fn(&Token<'a>) -> Option<&'a str>) -> &'a str {
+    ReaderForMicroXml::new(source)
+        .find_map(|result_token| matches(&result_token.unwrap()))
+        .unwrap()
+}
+
+#[test]
+/// replace attribute value and insert attribute keep the layout
+fn test_01() {
+    let mut editor = Editor::new(SOURCE).unwrap();
+    let value = token_slice(SOURCE, |token| match token {
+        Token::Attribute("id", value @ "2") => Some(value),
+        _ => None,
+    });
+    editor.replace_attribute_value(value, "x<\"y\"").unwrap();
+    let list = token_slice(SOURCE, |token| match token {
+        Token::StartElement(name @ "list") => Some(name),
+        _ => None,
+    });
+    editor.insert_attribute(list, "lang", "en").unwrap();
+    assert_eq!(
+        editor.apply().unwrap(),
+        "<list  class = \"a\" lang=\"en\">\n    <item id=\"1\">one</item>\n    <item\n      id=\"x&lt;&quot;y&quot;\"/>\n</list>\n"
+    );
+    assert_eq!(editor.edits().len(), 2);
+}
+
+#[test]
+/// rename at both tags and remove element with its leading whitespace
+fn test_02() {
+    let mut editor = Editor::new(SOURCE).unwrap();
+    let mut items = ReaderForMicroXml::new(SOURCE).filter_map(|result_token| match result_token.unwrap() {
+        Token::StartElement(name @ "item") => Some(name),
+        _ => None,
+    });
+    editor.rename_element(items.next().unwrap(), "entry").unwrap();
+    editor.remove_element(items.next().unwrap()).unwrap();
+    assert_eq!(
+        editor.apply().unwrap(),
+        "<list  class = \"a\">\n    <entry id=\"1\">one</entry>\n</list>\n"
+    );
+}
+
+#[test]
+/// the span of the token slices, escaping of the value
+fn test_03() {
+    let source = "<a x=\"1\"/>";
+    let name = token_slice(SOURCE, |token| match token {
+        Token::StartElement(name) => Some(name),
+        _ => None,
+    });
+    assert_eq!(span_of(SOURCE, name), Some(1..5));
+    assert_eq!(span_of(SOURCE, "list"), None);
+    let mut editor = Editor::new(source).unwrap();
+    editor.replace_attribute_value(&source[6..7], "say \"it's\"").unwrap();
+    assert_eq!(editor.apply().unwrap(), "<a x=\"say &quot;it's&quot;\"/>");
+    assert_eq!(editor.rename_element(&String::from("a"), "b"), Err(edit::ERR_NOT_IN_SOURCE));
+    assert_eq!(editor.rename_element(&source[3..4], "b"), Err(edit::ERR_NO_ELEMENT));
+    assert_eq!(editor.replace_attribute_value(&source[1..2], "b"), Err(edit::ERR_NO_ATTRIBUTE));
+}
+
+#[test]
+/// apply in any order, overlapping edits are an error
+fn test_04() {
+    let edits = [
+        TextEdit {
+            range: 4..5,
+            text: "X".to_string(),
+        },
+        TextEdit {
+            range: 0..0,
+            text: "1".to_string(),
+        },
+        TextEdit {
+            range: 0..0,
+            text: "2".to_string(),
+        },
+        TextEdit {
+            range: 0..1,
+            text: "".to_string(),
+        },
+    ];
+    assert_eq!(edit::apply("abcdef", &edits).unwrap(), "12bcdXf");
+    let edits = [
+        TextEdit {
+            range: 0..3,
+            text: "".to_string(),
+        },
+        TextEdit {
+            range: 2..4,
+            text: "".to_string(),
+        },
+    ];
+    assert_eq!(edit::apply("abcdef", &edits), Err(edit::ERR_OVERLAPPING_EDITS));
+    let source = "<a><b/></a>";
+    let mut editor = Editor::new(source).unwrap();
+    editor.rename_element(&source[4..5], "c").unwrap();
+    editor.remove_element(&source[4..5]).unwrap();
+    assert_eq!(editor.apply(), Err(edit::ERR_OVERLAPPING_EDITS));
+    assert!(Editor::new("<a x='1'/>").is_err());
+}
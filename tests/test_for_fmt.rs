@@ -0,0 +1,73 @@
+//! test_for_fmt
+#![cfg(feature = "alloc")]
+use reader_for_microxml::fmt::{check, format, FormatOptions};
+
+#[test]
+/// indentation, whitespace-only text is dropped, normalized self-closing
+fn test_01() {
+    let result = format("<a>\n<b x=\"1\" ></b>   <c><d /></c><!--c--></a>", FormatOptions::default()).unwrap();
+    assert_eq!(
+        result,
+        r#"<a>
+  <b x="1"/>
+  <c>
+    <d/>
+  </c>
+  <!--c-->
+</a>
+"#
+    );
+}
+
+#[test]
+/// mixed content is preserved, text is not decoded
+fn test_02() {
+    let result = format("<div><p>Hello  <b>big</b> world &amp; <i/></p></div>", FormatOptions::default()).unwrap();
+    assert_eq!(result, "<div>\n  <p>Hello  <b>big</b> world &amp; <i/></p>\n</div>\n");
+}
+
+#[test]
+/// one attribute per line past the width, custom indentation
+fn test_03() {
+    let options = FormatOptions { indent: 4, max_width: 20 };
+    let result = format(r#"<a><b first="111" second="222">t</b><c x="1"/></a>"#, options).unwrap();
+    assert_eq!(
+        result,
+        r#"<a>
+    <b
+        first="111"
+        second="222">t</b>
+    <c x="1"/>
+</a>
+"#
+    );
+}
+
+#[test]
+/// check returns the first different line, formatted output is stable
+fn test_04() {
+    let options = FormatOptions::default();
+    let formatted = format("<a><b/></a>", options).unwrap();
+    assert_eq!(check(&formatted, options).unwrap(), None);
+    assert_eq!(check("<a>\n<b/>\n</a>\n", options).unwrap(), Some(2));
+    assert!(check("<a x=1/>", options).is_err());
+}
+
+#[test]
+/// formatting the formatted output again does not change it, also with top-level mixed content
+fn test_05() {
+    let options = FormatOptions::default();
+    for input in [
+        "text",
+        "text\n",
+        "hello <b>x</b> world \n\n",
+        "<a/>tail\n",
+        "x<!--c-->  \n",
+        "<a>\n<b/>\n</a>\n",
+    ] {
+        let formatted = format(input, options).unwrap();
+        assert_eq!(format(&formatted, options).unwrap(), formatted, "input: {:?}", input);
+        assert_eq!(check(&formatted, options).unwrap(), None);
+    }
+    assert_eq!(format("hello <b>x</b> world \n\n", options).unwrap(), "hello <b>x</b> world\n");
+}
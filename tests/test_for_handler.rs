@@ -0,0 +1,78 @@
+//! test_for_handler
+#![cfg(feature = "alloc")]
+use reader_for_microxml::handler::{drive, Attributes, MicroXmlHandler};
+use reader_for_microxml::ReaderForMicroXml;
+
+/// writes every event on a new line, indented by depth
+#[derive(Default)]
+struct DebugHandler {
+    result: String,
+    error: Option<&'static str>,
+}
+
+impl<'a> MicroXmlHandler<'a> for DebugHandler {
+    fn start_element(&mut self, name: &'a str, attributes: Attributes<'_, 'a>, depth: usize) {
+        let attributes: Vec<String> = attributes.map(|(name, value)| format!("{}={}", name, value)).collect();
+        self.result
+            .push_str(&format!("{}start {} [{}]\n", " ".repeat(depth), name, attributes.join(",")));
+    }
+    fn end_element(&mut self, name: &'a str, depth: usize) {
+        self.result.push_str(&format!("{}end {}\n", " ".repeat(depth), name));
+    }
+    fn text(&mut self, txt: &'a str) {
+        self.result.push_str(&format!("text {}\n", txt));
+    }
+    fn error(&mut self, err_msg: &'static str, _pos: usize) {
+        self.error = Some(err_msg);
+    }
+}
+
+#[test]
+/// attributes come with the start tag, self-closing elements get their name
+fn test_01() {
+    let mut handler = DebugHandler::default();
+    let reader_iterator = ReaderForMicroXml::new(r#"<a x="1" y="2">t<br class="c"/><!--c--></a>"#);
+    drive(reader_iterator, &mut handler).unwrap();
+    assert_eq!(
+        handler.result,
+        " start a [x=1,y=2]
+text t
+  start br [class=c]
+  end br
+ end a
+"
+    );
+}
+
+#[test]
+/// not closed elements are closed at the end, errors stop the drive
+fn test_02() {
+    let mut handler = DebugHandler::default();
+    drive(ReaderForMicroXml::new("<a><b>"), &mut handler).unwrap();
+    assert_eq!(handler.result, " start a []\n  start b []\n  end b\n end a\n");
+
+    let mut handler = DebugHandler::default();
+    let result = drive(ReaderForMicroXml::new("<a no_good >x</a>"), &mut handler);
+    assert_eq!(result, Err("Error: Attribute does not have the char = ."));
+    assert_eq!(handler.error, result.err());
+}
+
+#[test]
+/// attributes by name
+fn test_03() {
+    struct HrefHandler(Vec<String>);
+    impl<'a> MicroXmlHandler<'a> for HrefHandler {
+        fn start_element(&mut self, _name: &'a str, attributes: Attributes<'_, 'a>, _depth: usize) {
+            if let Some(href) = attributes.get("href") {
+                self.0.push(href.to_string());
+            }
+        }
+    }
+    let mut handler = HrefHandler(Vec::new());
+    drive(
+        ReaderForMicroXml::new(r#"<p><a href="/1">1</a><a>2</a><a id="x" href="/3"/></p>"#),
+        &mut handler,
+    )
+    .unwrap();
+    assert_eq!(handler.0, ["/1", "/3"]);
+}
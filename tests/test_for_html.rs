@@ -0,0 +1,70 @@
+//! test_for_html
+use reader_for_microxml::*;
+
+fn read_html_to_debug_string(str_xml: &str) -> String {
+    let mut result = String::new();
+    for result_token in ReaderForMicroXml::new(str_xml).with_html_compat(true) {
+        match result_token.unwrap() {
+            Token::StartElement(name) => result.push_str(&format!("Start: \"{}\"\n", name)),
+            Token::Attribute(name, value) => result.push_str(&format!("Attribute: \"{}\" = \"{}\"\n", name, value)),
+            Token::TextNode(txt) => result.push_str(&format!("Text: \"{}\"\n", txt)),
+            Token::Comment(txt) => result.push_str(&format!("Comment: \"{}\"\n", txt)),
+            Token::EndElement(name) => result.push_str(&format!("End: \"{}\"\n", name)),
+        }
+    }
+    result
+}
+
+#[test]
+/// void elements get a synthesized EndElement, their end tags are ignored
+fn test_01() {
+    let result = read_html_to_debug_string(r#"<p>one<br>two<BR/>three<img src="a.png"></img></p>"#);
+    assert_eq!(
+        result,
+        r#"Start: "p"
+Text: "one"
+Start: "br"
+End: ""
+Text: "two"
+Start: "BR"
+End: ""
+Text: "three"
+Start: "img"
+Attribute: "src" = "a.png"
+End: ""
+End: "p"
+"#
+    );
+}
+
+#[test]
+/// boolean, unquoted and single-quoted attributes
+fn test_02() {
+    let result = read_html_to_debug_string(r#"<td colspan=2 title='x "y"'><input disabled><input checked/></td>"#);
+    assert_eq!(
+        result,
+        r#"Start: "td"
+Attribute: "colspan" = "2"
+Attribute: "title" = "x "y""
+Start: "input"
+Attribute: "disabled" = ""
+End: ""
+Start: "input"
+Attribute: "checked" = ""
+End: ""
+End: "td"
+"#
+    );
+}
+
+#[test]
+/// the default stays strict MicroXml
+fn test_03() {
+    let mut reader_iterator = ReaderForMicroXml::new(r#"<td colspan=2>"#);
+    assert!(reader_iterator.next().unwrap().is_ok());
+    assert!(reader_iterator.next().unwrap().is_err());
+    let tokens: Vec<_> = ReaderForMicroXml::new("<p><br></p>").map(Result::unwrap).collect();
+    assert_eq!(tokens.len(), 3);
+    assert!(html::is_void_element("Input"));
+    assert!(!html::is_void_element("div"));
+}
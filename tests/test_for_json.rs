@@ -0,0 +1,74 @@
+//! test_for_json
+#![cfg(feature = "alloc")]
+use reader_for_microxml::dom::{self, nodes_to_string, Node};
+use reader_for_microxml::json::{parse_element, parse_nodes, write_document, write_fragment};
+
+#[test]
+/// the document is the root element triple, the text is decoded, comments are skipped
+fn test_01() {
+    let mut json = String::new();
+    write_document(r#"<memo lang="en">I <em>love</em><!--c--> &amp; "MicroXml"<br/></memo>"#, &mut json).unwrap();
+    assert_eq!(
+        json,
+        r#"["memo", {"lang":"en"}, ["I ", ["em", {}, ["love"]], " & \"MicroXml\"", ["br", {}, []]]]"#
+    );
+}
+
+#[test]
+/// the fragment is a list, not closed elements are closed
+fn test_02() {
+    let mut json = String::new();
+    write_fragment("x\t<a b=\"1\" c=\"&#x41;\"><i>", &mut json).unwrap();
+    assert_eq!(json, r#"["x\t", ["a", {"b":"1", "c":"A"}, [["i", {}, []]]]]"#);
+    assert!(write_document("<a x=1/>", &mut String::new()).is_err());
+}
+
+#[test]
+/// the parser reads what the writer writes
+fn test_03() {
+    let input = r#"<memo lang="en" x="&lt;">I <em>love</em> &amp; "MicroXml"<br/></memo>"#;
+    let mut json = String::new();
+    write_document(input, &mut json).unwrap();
+    let element = parse_element(&json).unwrap();
+    assert_eq!(vec![Node::Element(element)], dom::parse_fragment(input).unwrap());
+
+    for input in ["text<a/><b>c</b>", "text", "<a/>", ""].iter() {
+        let mut json = String::new();
+        write_fragment(input, &mut json).unwrap();
+        let nodes = parse_nodes(&json).unwrap();
+        assert_eq!(nodes_to_string(&nodes), *input);
+    }
+}
+
+#[test]
+/// optional attributes and children, escapes, errors with position
+fn test_04() {
+    let nodes = parse_nodes(r#" ["a", ["b"], "é😀\n", ["c", {"x": "1"}]] "#).unwrap();
+    assert_eq!(nodes_to_string(&nodes), "a<b/>é😀\n<c x=\"1\"/>");
+    let element = parse_element(r#"["p"]"#).unwrap();
+    assert_eq!(element.to_string(), "<p/>");
+    let err = parse_element(r#"["p", {"x": 1}]"#).unwrap_err();
+    assert_eq!(err.pos, 12);
+    assert!(parse_element(r#"["p", {}, []] x"#).is_err());
+    assert!(parse_nodes(r#"[true]"#).is_err());
+    assert!(parse_element(r#"["", {}, []]"#).is_err());
+}
+
+#[test]
+/// the surrogate pairs must be complete
+fn test_05() {
+    let nodes = parse_nodes(r#"["\uD83D\uDE00 \u00e9"]"#).unwrap();
+    assert_eq!(nodes_to_string(&nodes), "😀 é");
+    for json in [
+        r#"["\uD800\u0041"]"#,
+        r#"["\uD800x"]"#,
+        r#"["\uD800"]"#,
+        r#"["\uD800\uD800"]"#,
+        r#"["\uDC00"]"#,
+        r#"["\uDE00\uD83D"]"#,
+    ]
+    .iter()
+    {
+        assert!(parse_nodes(json).is_err(), "{}", json);
+    }
+}
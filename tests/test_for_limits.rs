@@ -0,0 +1,129 @@
+//! test_for_limits
+use reader_for_microxml::*;
+
+/// read all tokens and return the first error or None
+fn first_error(reader_iterator: ReaderForMicroXml) -> Option<&'static str> {
+    for result_token in reader_iterator {
+        if let Err(err_msg) = result_token {
+            return Some(err_msg);
+        }
+    }
+    None
+}
+
+#[test]
+/// the default has no limits
+fn test_01() {
+    let str_xml = r#"<a><b><c x="1" y="2">text<!--comment--></c></b></a>"#;
+    let reader_iterator = ReaderForMicroXml::new(str_xml).with_limits(Limits::default());
+    assert_eq!(first_error(reader_iterator), None);
+}
+
+#[test]
+/// every limit has its own error
+fn test_02() {
+    let cases = [
+        (
+            r#"<a><b><c/></b></a>"#,
+            Limits {
+                max_depth: 2,
+                ..Limits::default()
+            },
+            limits::ERR_MAX_DEPTH,
+        ),
+        (
+            r#"<a x="1" y="2" z="3"/>"#,
+            Limits {
+                max_attributes: 2,
+                ..Limits::default()
+            },
+            limits::ERR_MAX_ATTRIBUTES,
+        ),
+        (
+            r#"<abcdef/>"#,
+            Limits {
+                max_name_len: 5,
+                ..Limits::default()
+            },
+            limits::ERR_MAX_NAME_LEN,
+        ),
+        (
+            r#"<a abcdef="1"/>"#,
+            Limits {
+                max_name_len: 5,
+                ..Limits::default()
+            },
+            limits::ERR_MAX_NAME_LEN,
+        ),
+        (
+            r#"<a x="123456"/>"#,
+            Limits {
+                max_attribute_value_len: 5,
+                ..Limits::default()
+            },
+            limits::ERR_MAX_ATTRIBUTE_VALUE_LEN,
+        ),
+        (
+            r#"<a>123456</a>"#,
+            Limits {
+                max_text_len: 5,
+                ..Limits::default()
+            },
+            limits::ERR_MAX_TEXT_LEN,
+        ),
+        (
+            r#"<a><!--123456--></a>"#,
+            Limits {
+                max_comment_len: 5,
+                ..Limits::default()
+            },
+            limits::ERR_MAX_COMMENT_LEN,
+        ),
+        (
+            r#"<a>1</a><b>2</b>"#,
+            Limits {
+                max_tokens: 5,
+                ..Limits::default()
+            },
+            limits::ERR_MAX_TOKENS,
+        ),
+    ];
+    for (str_xml, limits, expected) in cases.iter() {
+        let reader_iterator = ReaderForMicroXml::new(str_xml).with_limits(*limits);
+        assert_eq!(first_error(reader_iterator), Some(*expected), "{}", str_xml);
+    }
+}
+
+#[test]
+/// exactly at the limit is ok, the attribute count restarts for every element
+fn test_03() {
+    let str_xml = r#"<a x="1" y="2"><b x="1" y="2"/></a>"#;
+    let limits = Limits {
+        max_depth: 2,
+        max_attributes: 2,
+        max_tokens: 8,
+        ..Limits::default()
+    };
+    let reader_iterator = ReaderForMicroXml::new(str_xml).with_limits(limits);
+    assert_eq!(first_error(reader_iterator), None);
+}
+
+#[test]
+/// after an exceeded limit the reader stops
+fn test_04() {
+    let str_xml = r#"<a><b><c/></b></a>"#;
+    let mut reader_iterator = ReaderForMicroXml::new(str_xml).with_limits(Limits {
+        max_depth: 1,
+        ..Limits::default()
+    });
+    assert!(reader_iterator.next().unwrap().is_ok());
+    assert_eq!(reader_iterator.next().unwrap().unwrap_err(), limits::ERR_MAX_DEPTH);
+    assert!(reader_iterator.next().is_none());
+}
+
+#[test]
+/// untrusted input must not panic: multi-byte text at the end of file
+fn test_05() {
+    let tokens: Vec<_> = ReaderForMicroXml::new("<a>é</a>é").map(Result::unwrap).collect();
+    assert!(matches!(tokens.last(), Some(Token::TextNode("é"))));
+}
@@ -26,7 +26,7 @@ fn read_xml_to_debug_string(reader_iterator: &mut ReaderForMicroXml) -> String {
                 }
             },
             Err(err_msg) => {
-                panic!(err_msg);
+                panic!("{}", err_msg);
             }
         }
     }
@@ -285,3 +285,30 @@ End: "html"
 "#
     );
 }
+
+#[test]
+/// empty input and only whitespace have no tokens
+fn test_09() {
+    assert!(ReaderForMicroXml::new("").next().is_none());
+    assert!(ReaderForMicroXml::new(" \n").next().is_none());
+}
+
+#[test]
+/// self-closing element and comment at the end of file
+fn test_10() {
+    let mut reader_iterator = ReaderForMicroXml::new("<br/>");
+    assert_eq!(
+        read_xml_to_debug_string(&mut reader_iterator),
+        r#"Start: "br"
+End: ""
+"#
+    );
+    let mut reader_iterator = ReaderForMicroXml::new("<a/><!--c-->");
+    assert_eq!(
+        read_xml_to_debug_string(&mut reader_iterator),
+        r#"Start: "a"
+End: ""
+Comment: "c"
+"#
+    );
+}
@@ -0,0 +1,64 @@
+//! test_for_minify
+#[cfg(feature = "alloc")]
+use reader_for_microxml::dom;
+#[cfg(feature = "alloc")]
+use reader_for_microxml::minify::minify_to_string;
+use reader_for_microxml::minify::minify_with;
+
+#[test]
+#[cfg(feature = "alloc")]
+/// comments are removed, whitespace is collapsed to one space, self-closing is shortened
+fn test_01() {
+    let input = "<a  x = \"1\"\n  y=\"2\" >\n  <!-- comment -->\n  <b></b>\n  <c>text &amp; more </c>\n</a>\n";
+    let result = minify_to_string(input).unwrap();
+    assert_eq!(result, r#"<a x="1" y="2"> <b/> <c>text &amp; more </c> </a>"#);
+}
+
+#[test]
+/// the predicate keeps the placeholder comments
+fn test_02() {
+    let mut result = String::new();
+    minify_with(
+        "<p> <!--t=name-->John <!--note--> </p>",
+        |comment| comment.starts_with("t="),
+        &mut result,
+    )
+    .unwrap();
+    assert_eq!(result, "<p> <!--t=name-->John </p>");
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+/// the minified output parses to the same dom without comments
+fn test_03() {
+    let input =
+        "<html>\n <head><title>T</title></head>\n <body class=\"c\">\n  <p>Hello <b>world</b>!</p><br/>\n  <!-- end -->\n </body>\n</html>";
+    let mut expected = dom::parse_fragment(input).unwrap();
+    for node in expected.iter_mut() {
+        if let Some(element) = node.as_element_mut() {
+            element.remove_comments();
+        }
+    }
+    let minified = minify_to_string(input).unwrap();
+    assert_eq!(dom::parse_fragment(&minified).unwrap(), expected);
+    // minify is idempotent
+    assert_eq!(minify_to_string(&minified).unwrap(), minified);
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+/// the error of the reader is returned
+fn test_04() {
+    assert!(minify_to_string("<a x=1/>").is_err());
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+/// the whitespace between inline elements is significant in html, it is kept as one space
+fn test_05() {
+    assert_eq!(minify_to_string("<b>a</b> <i>b</i>").unwrap(), "<b>a</b> <i>b</i>");
+    assert_eq!(
+        minify_to_string("\n <p>\n  Hello\t\t<b>big</b>\n  <!-- c -->\n  world </p>\n").unwrap(),
+        "<p> Hello <b>big</b> world </p>"
+    );
+}
@@ -0,0 +1,83 @@
+//! test_for_path
+#![cfg(feature = "alloc")]
+use reader_for_microxml::dom;
+use reader_for_microxml::path::{Path, PathItem};
+
+const STR_XML: &str = r#"<feed>
+<entry lang="en"><title>First</title><item id="a1"/><item id="a2"/></entry>
+<entry lang="de"><title>Zweite</title><item id="b1"/></entry>
+<entry lang="en"><title>Third <b>bold</b></title><item id="c1"/><item id="c2"/><!--note--></entry>
+</feed>"#;
+
+fn strings(expression: &str) -> Vec<String> {
+    let nodes = dom::parse_fragment(STR_XML).unwrap();
+    Path::parse(expression).unwrap().evaluate_strings(&nodes)
+}
+
+#[test]
+/// child steps, text() and attribute predicates
+fn test_01() {
+    assert_eq!(strings("/feed/entry[@lang='en']/title/text()"), ["First", "Third "]);
+    assert_eq!(strings("/feed/entry[@lang!='en']/title"), ["Zweite"]);
+    assert_eq!(strings("/feed/entry/title"), ["First", "Zweite", "Third bold"]);
+    assert_eq!(strings("feed/entry[title='Zweite']/@lang"), ["de"]);
+}
+
+#[test]
+/// descendant steps and positions
+fn test_02() {
+    assert_eq!(strings("//item[2]/@id"), ["a2", "c2"]);
+    assert_eq!(strings("//item[last()]/@id"), ["a2", "b1", "c2"]);
+    assert_eq!(strings("/feed/entry[2]//@id"), ["b1"]);
+    assert_eq!(strings("//entry[item][@lang='en'][2]/title/text()"), ["Third "]);
+    assert_eq!(strings("//comment()"), ["note"]);
+    assert_eq!(strings("//b/."), ["bold"]);
+    assert_eq!(strings("/feed/*[1]/@*"), ["en"]);
+}
+
+#[test]
+/// the items are references into the tree
+fn test_03() {
+    let nodes = dom::parse_fragment(STR_XML).unwrap();
+    let path: Path = "//entry".parse().unwrap();
+    let items = path.evaluate(&nodes);
+    assert_eq!(items.len(), 3);
+    assert_eq!(items[1].as_element().unwrap().attribute("lang"), Some("de"));
+    let path = Path::parse("//item/@id").unwrap();
+    assert_eq!(path.evaluate_first(&nodes), Some(PathItem::Attribute("id", "a1")));
+}
+
+#[test]
+/// errors point into the expression
+fn test_04() {
+    let err = Path::parse("/feed/entry[@lang='en'").unwrap_err();
+    assert_eq!((err.pos, err.message), (22, "Error: Expected ] ."));
+    let err = Path::parse("/feed/../x").unwrap_err();
+    assert_eq!(err.pos, 6);
+    let err = Path::parse("/feed/count()").unwrap_err();
+    assert_eq!((err.pos, err.message), (6, "Error: Unsupported function."));
+    assert_eq!(Path::parse("//item[0]").unwrap_err().pos, 7);
+    assert!(Path::parse("/feed/").is_err());
+}
+
+#[test]
+/// the duplicates are removed by the position in the tree, equal empty texts are different nodes
+fn test_05() {
+    let mut element = dom::Element::new("p");
+    element.nodes.push(dom::Node::Text(String::new()));
+    element.nodes.push(dom::Node::Text(String::new()));
+    let nodes = [dom::Node::Element(element)];
+    assert_eq!(Path::parse("p/text()").unwrap().evaluate(&nodes).len(), 2);
+    assert_eq!(Path::parse("//p//text()").unwrap().evaluate(&nodes).len(), 2);
+}
+
+#[test]
+/// the results are in document order, also when a nested match is found after a later sibling match
+fn test_06() {
+    let nodes = dom::parse_fragment("<x id=\"1\"><x id=\"2\"><a>1</a></x><a>2</a></x><a>3</a>").unwrap();
+    let path = Path::parse("//x//a").unwrap();
+    assert_eq!(path.evaluate_strings(&nodes), ["1", "2"]);
+    assert_eq!(path.evaluate_first(&nodes).unwrap().string_value(), "1");
+    let path = Path::parse("//x/@id").unwrap();
+    assert_eq!(path.evaluate_strings(&nodes), ["1", "2"]);
+}
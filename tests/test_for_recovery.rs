@@ -0,0 +1,113 @@
+//! test_for_recovery
+#![cfg(feature = "alloc")]
+use reader_for_microxml::*;
+
+#[test]
+/// the errors are collected and the reader keeps going
+fn test_01() {
+    let str_xml = "<html no_good >one<br / x>two</html>";
+    let mut reader_iterator = ReaderForMicroXml::new(str_xml).with_recovery(true);
+    let tokens: Vec<_> = reader_iterator.by_ref().map(Result::unwrap).collect();
+    let texts: Vec<_> = tokens
+        .iter()
+        .filter_map(|token| match token {
+            Token::TextNode(txt) => Some(*txt),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(texts, ["one", "two"]);
+    let diagnostics = reader_iterator.diagnostics();
+    assert_eq!(diagnostics.len(), 4);
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].message, "Error: Attribute does not have the char = .");
+    assert_eq!(diagnostics[0].pos, 14);
+    assert_eq!(diagnostics[1].message, "Error: Tag has / but not />");
+    assert_eq!(diagnostics[1].pos, 24);
+    // the broken <br> stays open, so the end tag does not match
+    assert_eq!(diagnostics[2].severity, Severity::Warning);
+    assert_eq!(diagnostics[3].message, diagnostics::WARN_ELEMENT_NOT_CLOSED);
+}
+
+#[test]
+/// not matching and not closed elements are warnings
+fn test_02() {
+    let str_xml = "<html>\n<div>test</span>\n<p>";
+    let mut reader_iterator = ReaderForMicroXml::new(str_xml).with_recovery(true);
+    assert_eq!(reader_iterator.by_ref().count(), 5);
+    let diagnostics = reader_iterator.diagnostics();
+    assert_eq!(diagnostics.len(), 3);
+    assert_eq!(diagnostics[0].severity, Severity::Warning);
+    assert_eq!(diagnostics[0].message, diagnostics::WARN_END_ELEMENT_NOT_MATCHING);
+    assert_eq!(diagnostics[0].line_column(str_xml), (2, 12));
+    assert_eq!(diagnostics[1].message, diagnostics::WARN_ELEMENT_NOT_CLOSED);
+    assert_eq!(diagnostics[1].line_column(str_xml), (3, 2));
+    assert_eq!(diagnostics[2].line_column(str_xml), (1, 2));
+}
+
+#[test]
+/// document errors are collected and the tokens are returned anyway
+fn test_03() {
+    let mut reader_iterator = ReaderForMicroXml::new_document("<a/><b/>").with_recovery(true);
+    assert_eq!(reader_iterator.by_ref().count(), 4);
+    let messages: Vec<_> = reader_iterator.diagnostics().iter().map(|d| d.message).collect();
+    assert_eq!(messages, ["Error: Document has more than one root element."]);
+}
+
+#[test]
+/// without the recovery mode nothing is collected
+fn test_04() {
+    let mut reader_iterator = ReaderForMicroXml::new("<html no_good >one</html>");
+    assert!(reader_iterator.by_ref().any(|result_token| result_token.is_err()));
+    assert_eq!(reader_iterator.pos(), 14);
+    assert!(reader_iterator.diagnostics().is_empty());
+}
+
+/// the diagnostics as `message pos`
+fn diagnostics_of(str_xml: &str) -> Vec<(&'static str, usize)> {
+    let mut reader_iterator = ReaderForMicroXml::new(str_xml).with_recovery(true);
+    for _token in reader_iterator.by_ref() {}
+    reader_iterator
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| (diagnostic.message, diagnostic.pos))
+        .collect()
+}
+
+#[test]
+/// the input ends inside of a tag, attribute value or comment
+fn test_05() {
+    assert_eq!(
+        diagnostics_of("<a x=\"1>t</a>"),
+        [(ERR_ATTRIBUTE_VALUE_NOT_CLOSED, 5), (diagnostics::WARN_ELEMENT_NOT_CLOSED, 1)]
+    );
+    assert_eq!(diagnostics_of("<!-- x"), [(ERR_COMMENT_NOT_CLOSED, 0)]);
+    assert_eq!(
+        diagnostics_of("t<a b"),
+        [(ERR_TAG_NOT_CLOSED, 1), (diagnostics::WARN_ELEMENT_NOT_CLOSED, 2)]
+    );
+    assert_eq!(diagnostics_of("</a"), [(ERR_TAG_NOT_CLOSED, 0)]);
+    // without the recovery mode it is the last error
+    let results: Vec<_> = ReaderForMicroXml::new("<!-- x").collect();
+    assert!(matches!(results.as_slice(), [Err(ERR_COMMENT_NOT_CLOSED)]));
+}
+
+#[test]
+/// empty names and names with < are errors
+fn test_06() {
+    assert_eq!(diagnostics_of("< >x"), [(ERR_EMPTY_NAME, 2)]);
+    assert_eq!(
+        diagnostics_of("<<<>>>"),
+        [(ERR_NAME_HAS_LESS_THAN, 1), (ERR_NAME_HAS_LESS_THAN, 2), (ERR_EMPTY_NAME, 3)]
+    );
+    assert_eq!(
+        diagnostics_of("<a></>"),
+        [(ERR_EMPTY_NAME, 5), (diagnostics::WARN_ELEMENT_NOT_CLOSED, 1)]
+    );
+    assert_eq!(
+        diagnostics_of("<a =\"1\"/>"),
+        [(ERR_EMPTY_NAME, 3), (diagnostics::WARN_ELEMENT_NOT_CLOSED, 1)]
+    );
+    // the reader stops after the error, it does not loop on the same position
+    let results: Vec<_> = ReaderForMicroXml::new("<<<>>>").collect();
+    assert!(matches!(results.as_slice(), [Err(ERR_NAME_HAS_LESS_THAN)]));
+}
@@ -0,0 +1,219 @@
+//! test_for_schema
+#![cfg(feature = "alloc")]
+use reader_for_microxml::schema::{self, Content, Datatype, Particle, Schema, SchemaError};
+
+const LIBRARY: &str = r#"
+# a small library
+start = library
+library = { @version? book* }
+book = { @id @year:integer? title author+ (isbn | issn)? }
+title = text
+author = text
+isbn = token
+issn = token
+note = { @lang? text em* }
+em = text
+"#;
+
+/// the violations as `message name pos`
+fn violations(input: &str) -> Vec<(&'static str, String, usize)> {
+    let schema = Schema::parse(LIBRARY).unwrap();
+    schema
+        .validate(input)
+        .into_iter()
+        .map(|violation| (violation.message, violation.name, violation.pos))
+        .collect()
+}
+
+#[test]
+/// the schema model
+fn test_01() {
+    let schema = Schema::parse(LIBRARY).unwrap();
+    assert_eq!(schema.start.as_deref(), Some("library"));
+    let book = schema.element("book").unwrap();
+    assert_eq!(book.attributes.len(), 2);
+    assert_eq!(
+        (book.attributes[1].datatype, book.attributes[1].optional),
+        (Datatype::Integer, true)
+    );
+    assert_eq!(
+        book.content,
+        Content::Elements {
+            model: Particle::Sequence(vec![
+                Particle::Element("title".to_string()),
+                Particle::OneOrMore(Box::new(Particle::Element("author".to_string()))),
+                Particle::Optional(Box::new(Particle::Choice(vec![
+                    Particle::Element("isbn".to_string()),
+                    Particle::Element("issn".to_string()),
+                ]))),
+            ]),
+            mixed: false,
+        }
+    );
+    assert_eq!(schema.element("title").unwrap().content, Content::Datatype(Datatype::Text));
+    assert!(matches!(
+        schema.element("note").unwrap().content,
+        Content::Elements { mixed: true, .. }
+    ));
+}
+
+#[test]
+/// valid documents have no violations
+fn test_02() {
+    assert_eq!(violations("<library/>"), vec![]);
+    assert_eq!(
+        violations(
+            r#"<library version="1">
+  <book id="a" year="-2020"><title>T &amp; U</title><author>A</author><author>B</author><isbn>123</isbn></book>
+  <!-- comment -->
+  <book id="b"><title/><author>C</author></book>
+</library>"#
+        ),
+        vec![]
+    );
+}
+
+#[test]
+/// positioned violations
+fn test_03() {
+    let input = r#"<library x="1"><book year="y"><author>A</author><title>T</title>text</book><book id="b"><title>T</title><author>A</author><isbn>1 2</isbn><issn>3</issn></book></library>"#;
+    assert_eq!(
+        violations(input),
+        vec![
+            (schema::ERR_UNDECLARED_ATTRIBUTE, "x".to_string(), 9),
+            (schema::ERR_ATTRIBUTE_DATATYPE, "year".to_string(), 21),
+            (schema::ERR_MISSING_ATTRIBUTE, "id".to_string(), 16),
+            (schema::ERR_ELEMENT_NOT_ALLOWED, "author".to_string(), 31),
+            (schema::ERR_TEXT_NOT_ALLOWED, "book".to_string(), 64),
+            (schema::ERR_CONTENT_NOT_COMPLETE, "book".to_string(), 70),
+            (schema::ERR_TEXT_DATATYPE, "isbn".to_string(), 133),
+            (schema::ERR_ELEMENT_NOT_ALLOWED, "issn".to_string(), 139),
+        ]
+    );
+    assert_eq!(violations("<book/>")[0], (schema::ERR_ROOT_NOT_START, "book".to_string(), 1));
+    assert_eq!(violations(""), vec![(schema::ERR_NO_ROOT, String::new(), 0)]);
+    // the reader error is the last violation
+    let violations = violations(r#"<library><book id="1" / x>"#);
+    assert_eq!(violations.len(), 1);
+    assert_eq!(violations[0].0, "Error: Tag has / but not />");
+}
+
+#[test]
+/// schema syntax errors and datatypes
+fn test_04() {
+    let cases = [
+        (
+            "a = { b }",
+            SchemaError {
+                pos: 6,
+                message: schema::ERR_UNDEFINED_ELEMENT,
+            },
+        ),
+        (
+            "a = text\na = text",
+            SchemaError {
+                pos: 9,
+                message: schema::ERR_DUPLICATE_DEFINITION,
+            },
+        ),
+        (
+            "a = number",
+            SchemaError {
+                pos: 4,
+                message: schema::ERR_UNKNOWN_DATATYPE,
+            },
+        ),
+        (
+            "a { }",
+            SchemaError {
+                pos: 2,
+                message: schema::ERR_EXPECTED_EQUALS,
+            },
+        ),
+        (
+            "a = { (b }\nb = {}",
+            SchemaError {
+                pos: 9,
+                message: schema::ERR_EXPECTED_CLOSING,
+            },
+        ),
+        (
+            "a = { @ }",
+            SchemaError {
+                pos: 7,
+                message: schema::ERR_EXPECTED_NAME,
+            },
+        ),
+    ];
+    for (text, err) in cases.iter() {
+        assert_eq!(Schema::parse(text).unwrap_err(), *err, "{}", text);
+    }
+    let schema = Schema::parse("r = { (a, b)* | c+ }  a = {} b = {} c = {}").unwrap();
+    for (input, is_valid) in [
+        ("<r/>", true),
+        ("<r><a/><b/><a/><b/></r>", true),
+        ("<r><c/><c/></r>", true),
+        ("<r><a/></r>", false),
+        ("<r><a/><b/><c/></r>", false),
+    ] {
+        assert_eq!(schema.validate(input).is_empty(), is_valid, "{}", input);
+    }
+    for (datatype, valid, invalid) in [
+        (Datatype::Integer, "+12", "1.5"),
+        (Datatype::Decimal, "-.5", "1."),
+        (Datatype::Boolean, "false", "yes"),
+        (Datatype::Token, "a-b", "a b"),
+    ] {
+        assert!(datatype.is_valid(valid) && !datatype.is_valid(invalid));
+    }
+}
+
+#[test]
+/// the leading and trailing whitespace of the text and attribute values is not significant for the datatype
+fn test_05() {
+    let schema = Schema::parse("a = { @n:integer b c }  b = decimal  c = token").unwrap();
+    assert!(schema.validate("<a n=\" 1 \"><b>\n  2.5\n</b><c> x </c></a>").is_empty());
+    let violations = schema.validate("<a n=\"1 2\"><b>2 .5</b><c> x y </c></a>");
+    let messages: Vec<_> = violations.iter().map(|violation| violation.message).collect();
+    assert_eq!(
+        messages,
+        [schema::ERR_ATTRIBUTE_DATATYPE, schema::ERR_TEXT_DATATYPE, schema::ERR_TEXT_DATATYPE]
+    );
+    assert!(Datatype::Boolean.is_valid(" true\n"));
+    assert!(!Datatype::Token.is_valid("  "));
+}
+
+#[test]
+/// the skip edge of an optional group and the exit of a repetition do not leak into the inner loops
+fn test_06() {
+    let schema = Schema::parse("r = { (a, b*)? }  a = {}  b = {}").unwrap();
+    assert!(schema.validate("<r></r>").is_empty());
+    assert!(schema.validate("<r><a/><b/><b/></r>").is_empty());
+    assert!(!schema.validate("<r><b/></r>").is_empty());
+    assert!(!schema.validate("<r><b/><b/></r>").is_empty());
+
+    let schema = Schema::parse("r = { (x, a*)?, y }  x = {}  a = {}  y = {}").unwrap();
+    assert!(schema.validate("<r><y/></r>").is_empty());
+    assert!(schema.validate("<r><x/><a/><y/></r>").is_empty());
+    assert!(!schema.validate("<r><a/><y/></r>").is_empty());
+
+    let schema = Schema::parse("r = { (x, a*)*, y }  x = {}  a = {}  y = {}").unwrap();
+    assert!(schema.validate("<r><x/><a/><x/><y/></r>").is_empty());
+    assert!(!schema.validate("<r><a/><y/></r>").is_empty());
+
+    let schema = Schema::parse("r = { (x, a*)+ }  x = {}  a = {}").unwrap();
+    assert!(schema.validate("<r><x/><a/><a/><x/></r>").is_empty());
+    assert!(!schema.validate("<r></r>").is_empty());
+    assert!(!schema.validate("<r><a/></r>").is_empty());
+}
+
+#[test]
+/// the schema prints in the same syntax
+fn test_07() {
+    let schema = Schema::parse(LIBRARY).unwrap();
+    assert_eq!(Schema::parse(&schema.to_string()).unwrap(), schema);
+    let text = "r = { @a:integer? @b ((x?)* (x y | z)+ () | text) }\nx = {}\ny = boolean\nz = { @c }\n";
+    let schema = Schema::parse(text).unwrap();
+    assert_eq!(schema.to_string(), text);
+    assert_eq!(Schema::parse(&schema.to_string()).unwrap(), schema);
+}
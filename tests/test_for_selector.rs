@@ -0,0 +1,100 @@
+//! test_for_selector
+#![cfg(feature = "alloc")]
+use reader_for_microxml::dom::{self, Node};
+use reader_for_microxml::selector::Selector;
+
+const STR_XML: &str = r#"<html>
+<nav id="top" class="menu main">
+    <a href="/home">Home</a>
+    <a href="https://example.com" class="external">Ext</a>
+    <span><a name="x">no href</a></span>
+</nav>
+<ul>
+    <li>one</li><li class="odd">two</li><li>three</li><li>four</li>
+</ul>
+<a href="/outside">Outside</a>
+</html>"#;
+
+/// names and text of all selected elements
+fn select(nodes: &[Node], expression: &str) -> Vec<String> {
+    let selector = Selector::parse(expression).unwrap();
+    selector
+        .select(nodes)
+        .iter()
+        .map(|element| match element.nodes.first() {
+            Some(Node::Text(txt)) => format!("{}:{}", element.name, txt),
+            _ => element.name.clone(),
+        })
+        .collect()
+}
+
+#[test]
+/// type, id, class and attribute selectors
+fn test_01() {
+    let nodes = dom::parse_fragment(STR_XML).unwrap();
+    assert_eq!(select(&nodes, "nav a[href]"), ["a:Home", "a:Ext"]);
+    assert_eq!(select(&nodes, "#top"), ["nav"]);
+    assert_eq!(select(&nodes, ".main"), ["nav"]);
+    assert_eq!(select(&nodes, "a.external"), ["a:Ext"]);
+    assert_eq!(select(&nodes, "a[href^=http]"), ["a:Ext"]);
+    assert_eq!(select(&nodes, r#"a[href="/home"]"#), ["a:Home"]);
+    assert_eq!(select(&nodes, "a[href$='side']"), ["a:Outside"]);
+    assert_eq!(select(&nodes, "*[name]"), ["a:no href"]);
+}
+
+#[test]
+/// combinators
+fn test_02() {
+    let nodes = dom::parse_fragment(STR_XML).unwrap();
+    assert_eq!(select(&nodes, "nav > a"), ["a:Home", "a:Ext"]);
+    assert_eq!(select(&nodes, "html > a"), ["a:Outside"]);
+    assert_eq!(select(&nodes, "nav span a"), ["a:no href"]);
+    assert_eq!(select(&nodes, "li.odd + li"), ["li:three"]);
+    assert_eq!(select(&nodes, "li.odd ~ li"), ["li:three", "li:four"]);
+    assert_eq!(select(&nodes, "nav ~ a"), ["a:Outside"]);
+    assert_eq!(select(&nodes, "ul, span"), ["span", "ul"]);
+}
+
+#[test]
+/// pseudo-classes count only elements
+fn test_03() {
+    let nodes = dom::parse_fragment(STR_XML).unwrap();
+    assert_eq!(select(&nodes, "li:first-child"), ["li:one"]);
+    assert_eq!(select(&nodes, "li:nth-child(2)"), ["li:two"]);
+    assert_eq!(select(&nodes, "li:nth-child(odd)"), ["li:one", "li:three"]);
+    assert_eq!(select(&nodes, "li:nth-child(2n)"), ["li:two", "li:four"]);
+    assert_eq!(select(&nodes, "li:nth-child(-n+2)"), ["li:one", "li:two"]);
+    assert_eq!(select(&nodes, "nav a:first-child"), ["a:Home", "a:no href"]);
+    // the extreme values from user input do not overflow
+    assert_eq!(select(&nodes, "li:nth-child(2n-9223372036854775808)"), ["li:two", "li:four"]);
+    assert!(select(&nodes, "li:nth-child(-9223372036854775808n+9223372036854775807)").is_empty());
+    assert!(select(&nodes, "li:nth-child(-n-9223372036854775808)").is_empty());
+}
+
+#[test]
+/// compiled selector is reused and errors point into the expression
+fn test_04() {
+    let nodes = dom::parse_fragment(STR_XML).unwrap();
+    let selector: Selector = "li".parse().unwrap();
+    assert_eq!(selector.select(&nodes).len(), 4);
+    assert_eq!(selector.select_first(&nodes).unwrap().nodes[0], Node::Text("one".to_string()));
+    let err = Selector::parse("a[href").unwrap_err();
+    assert_eq!(err.pos, 6);
+    let err = Selector::parse("a:hover").unwrap_err();
+    assert_eq!((err.pos, err.message), (2, "Error: Unsupported pseudo-class."));
+    assert!(Selector::parse("a >").is_err());
+    assert!(Selector::parse("").is_err());
+}
+
+#[test]
+/// matches() on a siblings slice, the index out of the slice does not match
+fn test_05() {
+    let nodes = dom::parse_fragment("<a class=\"x\"/>text<b/>").unwrap();
+    let selector = Selector::parse(".x, b, i:first-child").unwrap();
+    assert!(selector.matches(&nodes, 0));
+    assert!(!selector.matches(&nodes, 1));
+    assert!(selector.matches(&nodes, 2));
+    assert!(!selector.matches(&nodes, 3));
+    assert!(!selector.matches(&[], 0));
+    assert!(!selector.matches(&nodes, usize::MAX));
+}
@@ -0,0 +1,44 @@
+//! test_for_stream_match
+#![cfg(feature = "alloc")]
+use reader_for_microxml::stream_match::StreamMatcher;
+use reader_for_microxml::ReaderForMicroXml;
+
+const STR_XML: &str = r#"<catalog>
+<book id="b1"><title>Rust</title><price>10</price></book>
+<book id="b2"><title>Micro<em>XML</em></title></book>
+<magazine id="m1"><title>Weekly</title></magazine>
+</catalog>"#;
+
+#[test]
+/// attributes are matched at once, elements at their end with all the text inside
+fn test_01() {
+    let reader_iterator = ReaderForMicroXml::new(STR_XML);
+    let matcher = StreamMatcher::new(reader_iterator, &["/catalog/book/title", "/catalog/book/@id"]).unwrap();
+    let matches: Vec<_> = matcher.map(Result::unwrap).collect();
+    let values: Vec<_> = matches.iter().map(|m| (m.path_index, m.value.as_ref())).collect();
+    assert_eq!(values, [(1, "b1"), (0, "Rust"), (1, "b2"), (0, "MicroXML")]);
+    assert_eq!(matches[1].context, ["catalog", "book", "title"]);
+    // the title and the id of the same book
+    assert_eq!(matches[1].parent_index, Some(matches[0].element_index));
+    assert_eq!(matches[3].parent_index, Some(matches[2].element_index));
+}
+
+#[test]
+/// the * matches any element name
+fn test_02() {
+    let reader_iterator = ReaderForMicroXml::new(STR_XML);
+    let matcher = StreamMatcher::new(reader_iterator, &["/catalog/*/@id"]).unwrap();
+    let values: Vec<_> = matcher.map(|m| m.unwrap().value.into_owned()).collect();
+    assert_eq!(values, ["b1", "b2", "m1"]);
+}
+
+#[test]
+/// errors of the paths and of the reader
+fn test_03() {
+    let err = StreamMatcher::new(ReaderForMicroXml::new(""), &["/a/@id/b"]).err().unwrap();
+    assert_eq!(err.pos, 7);
+    assert!(StreamMatcher::new(ReaderForMicroXml::new(""), &["a/b"]).is_err());
+    assert!(StreamMatcher::new(ReaderForMicroXml::new(""), &["/a[1]"]).is_err());
+    let mut matcher = StreamMatcher::new(ReaderForMicroXml::new("<a no_good >x</a>"), &["/a"]).unwrap();
+    assert!(matcher.next().unwrap().is_err());
+}
@@ -0,0 +1,180 @@
+//! test_for_template
+#![cfg(feature = "alloc")]
+use std::borrow::Cow;
+use std::fmt;
+
+use reader_for_microxml::template::{render_to_string, TemplateData};
+
+/// data for a list of books
+struct Library {
+    title: &'static str,
+    books: Vec<Book>,
+}
+
+struct Book {
+    title: &'static str,
+    url: &'static str,
+    is_new: bool,
+}
+
+impl TemplateData for Library {
+    fn value(&self, name: &str) -> Option<Cow<'_, str>> {
+        match name {
+            "title" => Some(Cow::Borrowed(self.title)),
+            _ => None,
+        }
+    }
+    fn repeat(&self, name: &str, render_item: &mut dyn FnMut(&dyn TemplateData) -> fmt::Result) -> fmt::Result {
+        if name == "books" {
+            for book in self.books.iter() {
+                render_item(book)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl TemplateData for Book {
+    fn value(&self, name: &str) -> Option<Cow<'_, str>> {
+        match name {
+            "title" => Some(Cow::Borrowed(self.title)),
+            "url" => Some(Cow::Borrowed(self.url)),
+            _ => None,
+        }
+    }
+    fn condition(&self, name: &str) -> bool {
+        name == "is_new" && self.is_new
+    }
+}
+
+#[test]
+/// text placeholder and attribute placeholder with escaping
+fn test_01() {
+    let data: &[(&str, &str)] = &[("name", "Tom & Jerry"), ("url", "a?b=\"1\"")];
+    let result = render_to_string(
+        r#"<p><!--t=name-->John Doe <a href="x" data-t-href="url">link</a><!--comment--></p>"#,
+        data,
+    )
+    .unwrap();
+    assert_eq!(
+        result,
+        r#"<p>Tom &amp; Jerry<a href="a?b=&quot;1&quot;">link</a><!--comment--></p>"#
+    );
+}
+
+#[test]
+/// missing value leaves the template content, placeholder attribute adds a missing attribute
+fn test_02() {
+    let data: &[(&str, &str)] = &[("id", "x1")];
+    let result = render_to_string(
+        r#"<p data-t-id="id" data-t-class="cls"><!--t=missing-->default<span><!--t=id--></span></p>"#,
+        data,
+    )
+    .unwrap();
+    assert_eq!(result, r#"<p id="x1">default<span>x1</span></p>"#);
+}
+
+#[test]
+/// repeat with the item data and condition for every item
+fn test_03() {
+    let library = Library {
+        title: "My books",
+        books: vec![
+            Book {
+                title: "Rust",
+                url: "/rust",
+                is_new: true,
+            },
+            Book {
+                title: "C<C++",
+                url: "/c",
+                is_new: false,
+            },
+        ],
+    };
+    let result = render_to_string(
+        r#"<div><h1><!--t=title-->Title</h1><ul><li data-t-repeat="books"><a href="" data-t-href="url"><!--t=title-->book</a><b data-t-if="is_new">new</b><i data-t-if="!is_new">old</i></li></ul></div>"#,
+        &library,
+    )
+    .unwrap();
+    assert_eq!(
+        result,
+        r#"<div><h1>My books</h1><ul><li><a href="/rust">Rust</a><b>new</b></li><li><a href="/c">C&lt;C++</a><i>old</i></li></ul></div>"#
+    );
+}
+
+#[test]
+/// the default condition is a non-empty value, the error of the template is returned
+fn test_04() {
+    let data: &[(&str, &str)] = &[("yes", "1"), ("no", "")];
+    let result = render_to_string(
+        r#"<p><b data-t-if="yes">y</b><b data-t-if="no">n</b><b data-t-if="none">n</b></p>"#,
+        data,
+    )
+    .unwrap();
+    assert_eq!(result, "<p><b>y</b></p>");
+    assert!(render_to_string("<p x=1></p>", data).is_err());
+}
+
+#[test]
+/// compiled template renders like the template, the static chunks are merged
+fn test_05() {
+    use reader_for_microxml::template::{CompiledTemplate, Instruction};
+    let template = r#"<p class="c"><b>x &amp; y</b><!--t=name-->default</p>"#;
+    let compiled = CompiledTemplate::compile(template).unwrap();
+    assert_eq!(compiled.instructions().len(), 3);
+    assert_eq!(
+        compiled.instructions()[0],
+        Instruction::Static(r#"<p class="c"><b>x &amp; y</b>"#.into())
+    );
+    let data: &[(&str, &str)] = &[("name", "<Tom>")];
+    assert_eq!(compiled.render_to_string(data).unwrap(), render_to_string(template, data).unwrap());
+    assert_eq!(
+        compiled.render_to_string(data).unwrap(),
+        r#"<p class="c"><b>x &amp; y</b>&lt;Tom&gt;</p>"#
+    );
+}
+
+#[test]
+/// bytes round trip with loops and conditionals, invalid bytes are an error
+fn test_06() {
+    use reader_for_microxml::template::CompiledTemplate;
+    let compiled =
+        CompiledTemplate::compile(r#"<ul><li data-t-repeat="books" data-t-if="is_new"><!--t=title-->x</li></ul><a data-t-href="url"/>"#)
+            .unwrap();
+    let bytes = compiled.to_bytes().unwrap();
+    let loaded = CompiledTemplate::from_bytes(&bytes).unwrap();
+    assert_eq!(loaded, compiled);
+    let library = Library {
+        title: "",
+        books: vec![
+            Book {
+                title: "Rust",
+                url: "",
+                is_new: true,
+            },
+            Book {
+                title: "C",
+                url: "",
+                is_new: false,
+            },
+        ],
+    };
+    assert_eq!(loaded.render_to_string(&library).unwrap(), "<ul><li>Rust</li></ul><a/>");
+    assert!(CompiledTemplate::from_bytes(b"XXX").is_err());
+    assert!(CompiledTemplate::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+    // the whitespace and unknown references survive the bytes
+    let bytes = CompiledTemplate::compile("<p> <b>&nbsp;</b> </p>").unwrap().to_bytes().unwrap();
+    let loaded = CompiledTemplate::from_bytes(&bytes).unwrap();
+    assert_eq!(loaded.render_to_string(&library).unwrap(), "<p> <b>&nbsp;</b> </p>");
+}
+
+#[test]
+/// the whitespace and unknown references of the template stay
+fn test_07() {
+    let data: &[(&str, &str)] = &[("name", "Tom")];
+    let result = render_to_string("<p>\n  <b>Hello</b> <i><!--t=name-->x</i>&nbsp;&copy;\n</p>", data).unwrap();
+    assert_eq!(result, "<p>\n  <b>Hello</b> <i>Tom</i>&nbsp;&copy;\n</p>");
+    let result = render_to_string(r#"<p title="&nbsp;" data-t-id="name"> <!--t=name-->x</p>"#, data).unwrap();
+    assert_eq!(result, r#"<p title="&nbsp;" id="Tom"> Tom</p>"#);
+}
@@ -0,0 +1,103 @@
+//! test_for_tree_sink
+#![cfg(feature = "alloc")]
+use reader_for_microxml::dom::{self, Node};
+use reader_for_microxml::tree_sink::{build, DomSink, TreeSink};
+use reader_for_microxml::ReaderForMicroXml;
+use std::borrow::Cow;
+
+/// arena tree: nodes in a vector, children are indexes
+#[derive(Debug, Default)]
+struct Arena<'a> {
+    nodes: Vec<ArenaNode<'a>>,
+    roots: Vec<usize>,
+}
+
+#[derive(Debug)]
+struct ArenaNode<'a> {
+    label: String,
+    attributes: Vec<(&'a str, &'a str)>,
+    children: Vec<usize>,
+}
+
+impl<'a> Arena<'a> {
+    fn push(&mut self, label: String) -> usize {
+        self.nodes.push(ArenaNode {
+            label,
+            attributes: vec![],
+            children: vec![],
+        });
+        self.nodes.len() - 1
+    }
+    fn append(&mut self, parent: Option<&usize>, child: usize) {
+        match parent {
+            Some(parent) => self.nodes[*parent].children.push(child),
+            None => self.roots.push(child),
+        }
+    }
+    fn to_debug_string(&self, index: usize) -> String {
+        let node = &self.nodes[index];
+        let attributes: Vec<String> = node.attributes.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+        let children: Vec<String> = node.children.iter().map(|child| self.to_debug_string(*child)).collect();
+        format!("{}[{}]({})", node.label, attributes.join(","), children.join(" "))
+    }
+}
+
+impl<'a> TreeSink<'a> for Arena<'a> {
+    type Handle = usize;
+    type Output = String;
+    fn create_element(&mut self, name: &'a str) -> usize {
+        self.push(name.to_string())
+    }
+    fn set_attribute(&mut self, element: &usize, name: &'a str, value: &'a str) {
+        self.nodes[*element].attributes.push((name, value));
+    }
+    fn append_child(&mut self, parent: Option<&usize>, child: usize) {
+        self.append(parent, child);
+    }
+    fn append_text(&mut self, parent: Option<&usize>, text: Cow<'a, str>) {
+        let child = self.push(format!("'{}'", text));
+        self.append(parent, child);
+    }
+    fn append_comment(&mut self, parent: Option<&usize>, text: &'a str) {
+        let child = self.push(format!("#{}", text));
+        self.append(parent, child);
+    }
+    fn finish(self) -> String {
+        let roots: Vec<String> = self.roots.iter().map(|root| self.to_debug_string(*root)).collect();
+        roots.join(" ")
+    }
+}
+
+#[test]
+/// arena tree keeps the order of children, attributes are not decoded
+fn test_01() {
+    let reader_iterator = ReaderForMicroXml::new(r#"<a x="1&amp;2">t<b/><!--c-->u</a>"#);
+    let result = build(reader_iterator, Arena::default()).unwrap();
+    assert_eq!(result, "a[x=1&amp;2]('t'[]() b[]() #c[]() 'u'[]())");
+}
+
+#[test]
+/// fragment with more roots and not closed elements
+fn test_02() {
+    let reader_iterator = ReaderForMicroXml::new_fragment("x<a><b>y");
+    let result = build(reader_iterator, Arena::default()).unwrap();
+    assert_eq!(result, "'x'[]() a[](b[]('y'[]()))");
+}
+
+#[test]
+/// DomSink builds the same dom as parse_fragment
+fn test_03() {
+    let input = r#"<a x="1&amp;2">t&lt;<b/><!--c--></a>"#;
+    let nodes = build(ReaderForMicroXml::new(input), DomSink::default()).unwrap();
+    assert_eq!(nodes, dom::parse_fragment(input).unwrap());
+    let element = nodes[0].as_element().unwrap();
+    assert_eq!(element.attribute("x"), Some("1&2"));
+    assert_eq!(element.nodes[0], Node::Text("t<".to_string()));
+}
+
+#[test]
+/// the error of the reader is returned
+fn test_04() {
+    let reader_iterator = ReaderForMicroXml::new("<a><b x=1></b></a>");
+    assert!(build(reader_iterator, Arena::default()).is_err());
+}
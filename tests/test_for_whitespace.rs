@@ -0,0 +1,75 @@
+//! test_for_whitespace
+use reader_for_microxml::*;
+
+/// returns only the text nodes, separated with |
+fn read_text_nodes(str_xml: &str, whitespace: WhitespacePolicy) -> String {
+    let mut result = String::new();
+    for result_token in ReaderForMicroXml::new(str_xml).with_whitespace(whitespace) {
+        if let Token::TextNode(txt) = result_token.unwrap() {
+            result.push_str(txt);
+            result.push('|');
+        }
+    }
+    result
+}
+
+const STR_XML: &str = "  <a>\n  <b> one  two </b>\n  <!--c--> three\t</a>  ";
+
+#[test]
+/// the default is the same as before the policy existed
+fn test_01() {
+    assert_eq!(WhitespacePolicy::default(), WhitespacePolicy::DropWhitespaceOnly);
+    assert_eq!(
+        read_text_nodes(STR_XML, WhitespacePolicy::DropWhitespaceOnly),
+        " one  two | three\t|"
+    );
+}
+
+#[test]
+/// preserve returns also whitespace-only text nodes
+fn test_02() {
+    assert_eq!(
+        read_text_nodes(STR_XML, WhitespacePolicy::Preserve),
+        "  |\n  | one  two |\n  | three\t|  |"
+    );
+}
+
+#[test]
+fn test_03() {
+    assert_eq!(read_text_nodes(STR_XML, WhitespacePolicy::Trim), "one  two|three|");
+    assert_eq!(read_text_nodes(STR_XML, WhitespacePolicy::Collapse), "one  two|three|");
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+/// collapse allocates only if there is something to collapse
+fn test_04() {
+    use std::borrow::Cow;
+    assert!(matches!(
+        WhitespacePolicy::Collapse.apply(" one two "),
+        Some(Cow::Borrowed("one two"))
+    ));
+    assert_eq!(WhitespacePolicy::Collapse.apply(" one \n\t two ").unwrap(), "one two");
+    assert_eq!(WhitespacePolicy::Collapse.apply(" \n "), None);
+    assert_eq!(whitespace::collapse_whitespace("a\tb"), "a b");
+    assert!(matches!(WhitespacePolicy::Preserve.apply(" a  b "), Some(Cow::Borrowed(" a  b "))));
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+/// the reader returns the trimmed slice, the alloc consumers collapse it
+fn test_05() {
+    let str_xml = "<p> x   y </p>";
+    let mut reader_iterator = ReaderForMicroXml::new(str_xml).with_whitespace(WhitespacePolicy::Collapse);
+    reader_iterator.next();
+    let txt = match reader_iterator.next() {
+        Some(Ok(Token::TextNode(txt))) => txt,
+        other => panic!("{:?}", other),
+    };
+    assert_eq!(txt, "x   y");
+    assert_eq!(reader_iterator.whitespace().apply(txt).unwrap(), "x y");
+    let nodes = dom::read_nodes(ReaderForMicroXml::new(str_xml).with_whitespace(WhitespacePolicy::Collapse)).unwrap();
+    assert_eq!(dom::nodes_to_string(&nodes), "<p>x y</p>");
+    let nodes = dom::read_nodes(ReaderForMicroXml::new(str_xml).with_whitespace(WhitespacePolicy::Trim)).unwrap();
+    assert_eq!(dom::nodes_to_string(&nodes), "<p>x   y</p>");
+}
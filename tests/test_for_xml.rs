@@ -0,0 +1,74 @@
+//! test_for_xml
+use reader_for_microxml::xml::{self, NamespacePolicy, XmlReader};
+use reader_for_microxml::Token;
+
+#[test]
+#[cfg(feature = "alloc")]
+/// declaration, PI, DOCTYPE with internal subset and CDATA
+fn test_01() {
+    let input = r#"<?xml version="1.0"?>
+<!DOCTYPE a [ <!ENTITY e "x"> ]>
+<a x='say "hi"'><?pi data?><![CDATA[1 < 2 &amp;]]><!--c--></a>"#;
+    let tokens: Vec<Token> = XmlReader::new(input).map(|token| token.unwrap()).collect();
+    assert_eq!(
+        format!("{:?}", tokens),
+        r#"[StartElement("a"), Attribute("x", "say \"hi\""), TextNode("1 < 2 &amp;"), Comment("c"), EndElement("a")]"#
+    );
+    assert_eq!(
+        xml::to_microxml(input, NamespacePolicy::default()).unwrap(),
+        r#"<a x="say &quot;hi&quot;">1 &lt; 2 &amp;amp;<!--c--></a>"#
+    );
+}
+
+#[test]
+#[cfg(feature = "alloc")]
+/// the namespace policies
+fn test_02() {
+    let input = r#"<svg:svg xmlns:svg="urn:svg" xmlns="urn:x" xml:lang="en"><svg:rect svg:w="1" h="2"><b/></svg:rect><c/></svg:svg>"#;
+    assert_eq!(
+        xml::to_microxml(input, NamespacePolicy::StripPrefix).unwrap(),
+        r#"<svg lang="en"><rect w="1" h="2"><b/></rect><c/></svg>"#
+    );
+    assert_eq!(
+        xml::to_microxml(input, NamespacePolicy::Flatten).unwrap(),
+        r#"<svg-svg lang="en"><svg-rect svg-w="1" h="2"><b/></svg-rect><c/></svg-svg>"#
+    );
+    let input = r#"<a xmlns:x="urn:x" x:y="1" z="2"><x:b><c>t</c></x:b><x:d/><e/></a>"#;
+    assert_eq!(
+        xml::to_microxml(input, NamespacePolicy::DropPrefixed).unwrap(),
+        r#"<a z="2"><e/></a>"#
+    );
+}
+
+#[test]
+/// the tokens of Flatten have the qualified name, is_cdata marks the CDATA text
+fn test_03() {
+    let mut xml_reader = XmlReader::new("<p:a>x<![CDATA[y]]></p:a >").with_namespace_policy(NamespacePolicy::Flatten);
+    assert!(matches!(xml_reader.next(), Some(Ok(Token::StartElement("p:a")))));
+    assert!(matches!(xml_reader.next(), Some(Ok(Token::TextNode("x")))));
+    assert!(!xml_reader.is_cdata());
+    assert!(matches!(xml_reader.next(), Some(Ok(Token::TextNode("y")))));
+    assert!(xml_reader.is_cdata());
+    assert!(matches!(xml_reader.next(), Some(Ok(Token::EndElement("p:a")))));
+    assert!(xml_reader.next().is_none());
+}
+
+#[test]
+/// errors end the iteration
+fn test_04() {
+    let cases = [
+        ("<a><!-- c", xml::ERR_NOT_CLOSED_COMMENT),
+        ("<a><![CDATA[x", xml::ERR_NOT_CLOSED_CDATA),
+        ("<?xml", xml::ERR_NOT_CLOSED_PI),
+        ("<!DOCTYPE a [ >", xml::ERR_NOT_CLOSED_DOCTYPE),
+        ("<a x", xml::ERR_ATTRIBUTE_WITHOUT_VALUE),
+        ("<a x='1", xml::ERR_NOT_CLOSED_ATTRIBUTE_VALUE),
+        ("<a", xml::ERR_NOT_CLOSED_TAG),
+        ("< a/>", xml::ERR_EMPTY_NAME),
+    ];
+    for (input, err_msg) in cases.iter() {
+        let mut xml_reader = XmlReader::new(input);
+        assert_eq!(xml_reader.find_map(|token| token.err()), Some(*err_msg), "{}", input);
+        assert!(xml_reader.next().is_none());
+    }
+}